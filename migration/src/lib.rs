@@ -12,6 +12,21 @@ mod m20240502_130956_courses;
 mod m20240510_081433_index_users_unique_name;
 mod m20240525_133501_problems;
 mod m20240609_093230_problem_tasks;
+mod m20240617_142301_submissions;
+mod m20240621_071544_add_submissions_tasks;
+mod m20240625_083012_course_members;
+mod m20240627_061742_problem_courses;
+mod m20240629_104518_problem_tags;
+mod m20240701_090512_add_problems_test_case_stale;
+mod m20240703_142207_add_problems_archived_at;
+mod m20240706_074125_problem_test_case_versions;
+mod m20240708_061833_add_problems_checker;
+mod m20240710_083152_problem_templates;
+mod m20240712_094416_add_submissions_handwritten;
+mod m20240714_073055_homeworks;
+mod m20240716_092417_contests;
+mod m20240716_092705_contest_participants;
+mod m20240716_093122_contest_scores;
 pub struct Migrator;
 
 #[async_trait::async_trait]
@@ -27,6 +42,21 @@ impl MigratorTrait for Migrator {
             Box::new(m20240510_081433_index_users_unique_name::Migration),
             Box::new(m20240525_133501_problems::Migration),
             Box::new(m20240609_093230_problem_tasks::Migration),
+            Box::new(m20240617_142301_submissions::Migration),
+            Box::new(m20240621_071544_add_submissions_tasks::Migration),
+            Box::new(m20240625_083012_course_members::Migration),
+            Box::new(m20240627_061742_problem_courses::Migration),
+            Box::new(m20240629_104518_problem_tags::Migration),
+            Box::new(m20240701_090512_add_problems_test_case_stale::Migration),
+            Box::new(m20240703_142207_add_problems_archived_at::Migration),
+            Box::new(m20240706_074125_problem_test_case_versions::Migration),
+            Box::new(m20240708_061833_add_problems_checker::Migration),
+            Box::new(m20240710_083152_problem_templates::Migration),
+            Box::new(m20240712_094416_add_submissions_handwritten::Migration),
+            Box::new(m20240714_073055_homeworks::Migration),
+            Box::new(m20240716_092417_contests::Migration),
+            Box::new(m20240716_092705_contest_participants::Migration),
+            Box::new(m20240716_093122_contest_scores::Migration),
         ]
     }
 }
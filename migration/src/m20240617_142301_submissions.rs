@@ -0,0 +1,81 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_auto(Submissions::Table)
+                    .col(pk_auto(Submissions::Id))
+                    .col(integer(Submissions::UserId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-submission-user")
+                            .from(Submissions::Table, Submissions::UserId)
+                            .to(Users::Table, Users::Id),
+                    )
+                    .col(integer(Submissions::ProblemId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-submission-problem")
+                            .from(Submissions::Table, Submissions::ProblemId)
+                            .to(Problems::Table, Problems::Id),
+                    )
+                    .col(integer(Submissions::Language))
+                    .col(string(Submissions::CodeId))
+                    // pending by default
+                    .col(integer(Submissions::Status).default(-1))
+                    .col(integer(Submissions::Score).default(0))
+                    .col(integer(Submissions::ExecTime).default(-1))
+                    .col(integer(Submissions::MemoryUsage).default(-1))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-submission-problem-user")
+                    .table(Submissions::Table)
+                    .col(Submissions::ProblemId)
+                    .col(Submissions::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Submissions::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Submissions {
+    Table,
+    Id,
+    UserId,
+    ProblemId,
+    Language,
+    CodeId,
+    Status,
+    Score,
+    ExecTime,
+    MemoryUsage,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Problems {
+    Table,
+    Id,
+}
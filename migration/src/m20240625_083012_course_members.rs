@@ -0,0 +1,104 @@
+use sea_orm_migration::{
+    prelude::*,
+    schema::*,
+    sea_orm::{EnumIter, Iterable},
+    sea_query::extension::postgres::Type,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden, EnumIter)]
+enum CourseRole {
+    Student,
+    Ta,
+    Teacher,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let course_role_name = || Alias::new("course_role");
+
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(course_role_name())
+                    .values(CourseRole::iter())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                table_auto(CourseMembers::Table)
+                    .col(pk_auto(CourseMembers::Id))
+                    .col(integer(CourseMembers::CourseId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-course_member-course")
+                            .from(CourseMembers::Table, CourseMembers::CourseId)
+                            .to(Courses::Table, Courses::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(integer(CourseMembers::UserId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-course_member-user")
+                            .from(CourseMembers::Table, CourseMembers::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(
+                        ColumnDef::new(CourseMembers::Role)
+                            .enumeration(course_role_name(), CourseRole::iter())
+                            .default(CourseRole::Student.to_string())
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-course_member-course-user")
+                    .table(CourseMembers::Table)
+                    .col(CourseMembers::CourseId)
+                    .col(CourseMembers::UserId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CourseMembers::Table).to_owned())
+            .await?;
+        manager
+            .drop_type(Type::drop().name(Alias::new("course_role")).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum CourseMembers {
+    Table,
+    Id,
+    CourseId,
+    UserId,
+    Role,
+}
+
+#[derive(DeriveIden)]
+enum Courses {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,71 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_auto(ProblemCourses::Table)
+                    .col(pk_auto(ProblemCourses::Id))
+                    .col(integer(ProblemCourses::ProblemId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-problem_course-problem")
+                            .from(ProblemCourses::Table, ProblemCourses::ProblemId)
+                            .to(Problems::Table, Problems::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(integer(ProblemCourses::CourseId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-problem_course-course")
+                            .from(ProblemCourses::Table, ProblemCourses::CourseId)
+                            .to(Courses::Table, Courses::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-problem_course-problem-course")
+                    .table(ProblemCourses::Table)
+                    .col(ProblemCourses::ProblemId)
+                    .col(ProblemCourses::CourseId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ProblemCourses::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ProblemCourses {
+    Table,
+    Id,
+    ProblemId,
+    CourseId,
+}
+
+#[derive(DeriveIden)]
+enum Problems {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Courses {
+    Table,
+    Id,
+}
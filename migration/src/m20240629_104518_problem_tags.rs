@@ -0,0 +1,68 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_auto(ProblemTags::Table)
+                    .col(pk_auto(ProblemTags::Id))
+                    .col(integer(ProblemTags::ProblemId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-problem_tag-problem")
+                            .from(ProblemTags::Table, ProblemTags::ProblemId)
+                            .to(Problems::Table, Problems::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(string_len(ProblemTags::Tag, 64))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-problem_tag-problem-tag")
+                    .table(ProblemTags::Table)
+                    .col(ProblemTags::ProblemId)
+                    .col(ProblemTags::Tag)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-problem_tag-tag")
+                    .table(ProblemTags::Table)
+                    .col(ProblemTags::Tag)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ProblemTags::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ProblemTags {
+    Table,
+    Id,
+    ProblemId,
+    Tag,
+}
+
+#[derive(DeriveIden)]
+enum Problems {
+    Table,
+    Id,
+}
@@ -0,0 +1,118 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_auto(ProblemTestCaseVersions::Table)
+                    .col(pk_auto(ProblemTestCaseVersions::Id))
+                    .col(integer(ProblemTestCaseVersions::ProblemId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-problem_test_case_version-problem")
+                            .from(
+                                ProblemTestCaseVersions::Table,
+                                ProblemTestCaseVersions::ProblemId,
+                            )
+                            .to(Problems::Table, Problems::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(integer(ProblemTestCaseVersions::UploaderId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-problem_test_case_version-uploader")
+                            .from(
+                                ProblemTestCaseVersions::Table,
+                                ProblemTestCaseVersions::UploaderId,
+                            )
+                            .to(Users::Table, Users::Id),
+                    )
+                    .col(string_uniq(ProblemTestCaseVersions::TestCaseId))
+                    .col(big_integer(ProblemTestCaseVersions::Size))
+                    .col(string_len(ProblemTestCaseVersions::Sha256, 64))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-problem_test_case_version-problem")
+                    .table(ProblemTestCaseVersions::Table)
+                    .col(ProblemTestCaseVersions::ProblemId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Submissions::Table)
+                    .add_column_if_not_exists(integer_null(Submissions::TestCaseVersionId))
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk-submission-test_case_version")
+                            .from_tbl(Submissions::Table)
+                            .from_col(Submissions::TestCaseVersionId)
+                            .to_tbl(ProblemTestCaseVersions::Table)
+                            .to_col(ProblemTestCaseVersions::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Submissions::Table)
+                    .drop_foreign_key(Alias::new("fk-submission-test_case_version"))
+                    .drop_column(Submissions::TestCaseVersionId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(ProblemTestCaseVersions::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ProblemTestCaseVersions {
+    Table,
+    Id,
+    ProblemId,
+    UploaderId,
+    TestCaseId,
+    Size,
+    Sha256,
+}
+
+#[derive(DeriveIden)]
+enum Problems {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Submissions {
+    Table,
+    TestCaseVersionId,
+}
@@ -0,0 +1,60 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_auto(ProblemTemplates::Table)
+                    .col(pk_auto(ProblemTemplates::Id))
+                    .col(integer(ProblemTemplates::ProblemId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-problem_template-problem")
+                            .from(ProblemTemplates::Table, ProblemTemplates::ProblemId)
+                            .to(Problems::Table, Problems::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(integer(ProblemTemplates::Language))
+                    .col(text(ProblemTemplates::Content))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-problem_template-problem-language")
+                    .table(ProblemTemplates::Table)
+                    .col(ProblemTemplates::ProblemId)
+                    .col(ProblemTemplates::Language)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ProblemTemplates::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ProblemTemplates {
+    Table,
+    Id,
+    ProblemId,
+    Language,
+    Content,
+}
+
+#[derive(DeriveIden)]
+enum Problems {
+    Table,
+    Id,
+}
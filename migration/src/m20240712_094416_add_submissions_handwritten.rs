@@ -0,0 +1,42 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+enum Submissions {
+    Table,
+    Language,
+    ContentType,
+    Comment,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // handwritten submissions have no language
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Submissions::Table)
+                    .modify_column(ColumnDef::new(Submissions::Language).integer().null())
+                    .add_column_if_not_exists(string_null(Submissions::ContentType))
+                    .add_column_if_not_exists(text_null(Submissions::Comment))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Submissions::Table)
+                    .modify_column(ColumnDef::new(Submissions::Language).integer().not_null())
+                    .drop_column(Submissions::ContentType)
+                    .drop_column(Submissions::Comment)
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,131 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_auto(Homeworks::Table)
+                    .col(pk_auto(Homeworks::Id))
+                    .col(integer(Homeworks::CourseId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-homework-course")
+                            .from(Homeworks::Table, Homeworks::CourseId)
+                            .to(Courses::Table, Courses::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(string_len(Homeworks::Name, 64))
+                    .col(text(Homeworks::Description))
+                    .col(timestamp(Homeworks::StartAt))
+                    .col(timestamp(Homeworks::EndAt))
+                    .col(timestamp_null(Homeworks::LateEndAt))
+                    .col(integer(Homeworks::Penalty).default(0))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-homework-course-name")
+                    .table(Homeworks::Table)
+                    .col(Homeworks::CourseId)
+                    .col(Homeworks::Name)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                table_auto(HomeworkProblems::Table)
+                    .col(pk_auto(HomeworkProblems::Id))
+                    .col(integer(HomeworkProblems::HomeworkId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-homework_problem-homework")
+                            .from(HomeworkProblems::Table, HomeworkProblems::HomeworkId)
+                            .to(Homeworks::Table, Homeworks::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(integer(HomeworkProblems::ProblemId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-homework_problem-problem")
+                            .from(HomeworkProblems::Table, HomeworkProblems::ProblemId)
+                            .to(Problems::Table, Problems::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-homework_problem-homework-problem")
+                    .table(HomeworkProblems::Table)
+                    .col(HomeworkProblems::HomeworkId)
+                    .col(HomeworkProblems::ProblemId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-homework_problem-problem")
+                    .table(HomeworkProblems::Table)
+                    .col(HomeworkProblems::ProblemId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(HomeworkProblems::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Homeworks::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Homeworks {
+    Table,
+    Id,
+    CourseId,
+    Name,
+    Description,
+    StartAt,
+    EndAt,
+    LateEndAt,
+    Penalty,
+}
+
+#[derive(DeriveIden)]
+enum HomeworkProblems {
+    Table,
+    Id,
+    HomeworkId,
+    ProblemId,
+}
+
+#[derive(DeriveIden)]
+enum Courses {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Problems {
+    Table,
+    Id,
+}
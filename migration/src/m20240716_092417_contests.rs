@@ -0,0 +1,118 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_auto(Contests::Table)
+                    .col(pk_auto(Contests::Id))
+                    .col(string_len_uniq(Contests::Name, 64))
+                    .col(text(Contests::Description))
+                    .col(integer(Contests::OwnerId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-contest-owner")
+                            .from(Contests::Table, Contests::OwnerId)
+                            .to(Users::Table, Users::Id),
+                    )
+                    .col(integer(Contests::Rule))
+                    .col(timestamp(Contests::StartAt))
+                    .col(timestamp(Contests::EndAt))
+                    .col(integer(Contests::FreezeMinutes).default(0))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                table_auto(ContestProblems::Table)
+                    .col(pk_auto(ContestProblems::Id))
+                    .col(integer(ContestProblems::ContestId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-contest_problem-contest")
+                            .from(ContestProblems::Table, ContestProblems::ContestId)
+                            .to(Contests::Table, Contests::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(integer(ContestProblems::ProblemId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-contest_problem-problem")
+                            .from(ContestProblems::Table, ContestProblems::ProblemId)
+                            .to(Problems::Table, Problems::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-contest_problem-contest-problem")
+                    .table(ContestProblems::Table)
+                    .col(ContestProblems::ContestId)
+                    .col(ContestProblems::ProblemId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-contest_problem-problem")
+                    .table(ContestProblems::Table)
+                    .col(ContestProblems::ProblemId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ContestProblems::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Contests::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Contests {
+    Table,
+    Id,
+    Name,
+    Description,
+    OwnerId,
+    Rule,
+    StartAt,
+    EndAt,
+    FreezeMinutes,
+}
+
+#[derive(DeriveIden)]
+enum ContestProblems {
+    Table,
+    Id,
+    ContestId,
+    ProblemId,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Problems {
+    Table,
+    Id,
+}
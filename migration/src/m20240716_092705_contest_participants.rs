@@ -0,0 +1,71 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_auto(ContestParticipants::Table)
+                    .col(pk_auto(ContestParticipants::Id))
+                    .col(integer(ContestParticipants::ContestId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-contest_participant-contest")
+                            .from(ContestParticipants::Table, ContestParticipants::ContestId)
+                            .to(Contests::Table, Contests::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(integer(ContestParticipants::UserId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-contest_participant-user")
+                            .from(ContestParticipants::Table, ContestParticipants::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-contest_participant-contest-user")
+                    .table(ContestParticipants::Table)
+                    .col(ContestParticipants::ContestId)
+                    .col(ContestParticipants::UserId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ContestParticipants::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ContestParticipants {
+    Table,
+    Id,
+    ContestId,
+    UserId,
+}
+
+#[derive(DeriveIden)]
+enum Contests {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,99 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_auto(ContestScores::Table)
+                    .col(pk_auto(ContestScores::Id))
+                    .col(integer(ContestScores::ContestId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-contest_score-contest")
+                            .from(ContestScores::Table, ContestScores::ContestId)
+                            .to(Contests::Table, Contests::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(integer(ContestScores::UserId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-contest_score-user")
+                            .from(ContestScores::Table, ContestScores::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(integer(ContestScores::ProblemId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-contest_score-problem")
+                            .from(ContestScores::Table, ContestScores::ProblemId)
+                            .to(Problems::Table, Problems::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(integer(ContestScores::Attempts).default(0))
+                    .col(timestamp_null(ContestScores::AcceptedAt))
+                    .col(integer(ContestScores::Score).default(0))
+                    .col(integer(ContestScores::PublicAttempts).default(0))
+                    .col(timestamp_null(ContestScores::PublicAcceptedAt))
+                    .col(integer(ContestScores::PublicScore).default(0))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-contest_score-contest-user-problem")
+                    .table(ContestScores::Table)
+                    .col(ContestScores::ContestId)
+                    .col(ContestScores::UserId)
+                    .col(ContestScores::ProblemId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ContestScores::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ContestScores {
+    Table,
+    Id,
+    ContestId,
+    UserId,
+    ProblemId,
+    Attempts,
+    AcceptedAt,
+    Score,
+    PublicAttempts,
+    PublicAcceptedAt,
+    PublicScore,
+}
+
+#[derive(DeriveIden)]
+enum Contests {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Problems {
+    Table,
+    Id,
+}
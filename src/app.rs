@@ -7,7 +7,7 @@ use loco_rs::{
     controller::AppRoutes,
     db::{self, truncate_table},
     environment::Environment,
-    storage::{self, Storage},
+    storage::Storage,
     task::Tasks,
     worker::{AppWorker, Processor},
     Result,
@@ -17,9 +17,13 @@ use sea_orm::{ConnectionTrait, DatabaseBackend, DatabaseConnection, Statement};
 
 use crate::{
     controllers,
-    models::_entities::{courses, problem_descriptions, problem_tasks, problems, users},
+    models::_entities::{
+        contest_participants, contest_problems, contest_scores, contests, course_members, courses,
+        homework_problems, homeworks, problem_courses, problem_descriptions, problem_tags,
+        problem_tasks, problem_templates, problem_test_case_versions, problems, submissions, users,
+    },
     tasks,
-    workers::downloader::DownloadWorker,
+    workers::{downloader::DownloadWorker, judge::JudgeWorker},
 };
 
 pub struct App;
@@ -47,7 +51,10 @@ impl Hooks for App {
         AppRoutes::with_default_routes()
             .prefix("/api")
             .add_route(controllers::problems::routes())
+            .add_route(controllers::submissions::routes())
             .add_route(controllers::courses::routes())
+            .add_route(controllers::contests::routes())
+            .add_route(controllers::homeworks::routes())
             .add_route(controllers::notes::routes())
             .add_route(controllers::auth::routes())
             .add_route(controllers::user::routes())
@@ -55,6 +62,7 @@ impl Hooks for App {
 
     fn connect_workers<'a>(p: &'a mut Processor, ctx: &'a AppContext) {
         p.register(DownloadWorker::build(ctx));
+        p.register(JudgeWorker::build(ctx));
     }
 
     fn register_tasks(tasks: &mut Tasks) {
@@ -62,8 +70,20 @@ impl Hooks for App {
     }
 
     async fn truncate(db: &DatabaseConnection) -> Result<()> {
+        truncate_table(db, contest_scores::Entity).await?;
+        truncate_table(db, contest_participants::Entity).await?;
+        truncate_table(db, contest_problems::Entity).await?;
+        truncate_table(db, contests::Entity).await?;
+        truncate_table(db, submissions::Entity).await?;
+        truncate_table(db, homework_problems::Entity).await?;
+        truncate_table(db, homeworks::Entity).await?;
+        truncate_table(db, problem_test_case_versions::Entity).await?;
+        truncate_table(db, problem_courses::Entity).await?;
+        truncate_table(db, problem_tags::Entity).await?;
         truncate_table(db, problem_tasks::Entity).await?;
+        truncate_table(db, problem_templates::Entity).await?;
         truncate_table(db, problems::Entity).await?;
+        truncate_table(db, course_members::Entity).await?;
         truncate_table(db, courses::Entity).await?;
         truncate_table(db, users::Entity).await?;
         truncate_table(db, problem_descriptions::Entity).await?;
@@ -74,6 +94,11 @@ impl Hooks for App {
         db::seed::<users::ActiveModel>(db, &base.join("users.yaml").display().to_string()).await?;
         db::seed::<courses::ActiveModel>(db, &base.join("courses.yaml").display().to_string())
             .await?;
+        db::seed::<course_members::ActiveModel>(
+            db,
+            &base.join("course_members.yaml").display().to_string(),
+        )
+        .await?;
 
         // update auto inc id
         // ref: https://stackoverflow.com/a/55024610
@@ -81,9 +106,21 @@ impl Hooks for App {
         let tables = [
             "users",
             "courses",
+            "course_members",
+            "contests",
+            "contest_problems",
+            "contest_participants",
+            "contest_scores",
+            "homeworks",
+            "homework_problems",
             "problems",
+            "problem_courses",
             "problem_descriptions",
+            "problem_tags",
             "problem_tasks",
+            "problem_templates",
+            "problem_test_case_versions",
+            "submissions",
         ];
         for table in tables {
             db.execute(Statement::from_string(
@@ -99,11 +136,7 @@ impl Hooks for App {
     }
 
     async fn after_context(ctx: AppContext) -> Result<AppContext> {
-        let store = if ctx.environment == Environment::Test {
-            storage::drivers::mem::new()
-        } else {
-            storage::drivers::local::new_with_prefix("storage").map_err(Box::from)?
-        };
+        let store = crate::storage::driver(&ctx.environment)?;
 
         Ok(AppContext {
             storage: Storage::single(store).into(),
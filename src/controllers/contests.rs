@@ -0,0 +1,280 @@
+use crate::{
+    models::{
+        self,
+        contests::{self, participants},
+        users,
+    },
+    views::contests::{
+        ContestListResponse, ContestResponse, ParticipantListResponse, ScoreboardResponse,
+    },
+};
+use axum::http::StatusCode;
+use loco_rs::prelude::*;
+use sea_orm::TransactionTrait;
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{find_user_by_auth, permission_denied, verify_admin};
+
+/// Find a contest by id, responds 404 if it does not exist.
+async fn find_contest(
+    ctx: &AppContext,
+    contest_id: i32,
+) -> Result<contests::Model, Result<Response>> {
+    match contests::Model::find_by_id(&ctx.db, contest_id).await {
+        Ok(c) => Ok(c),
+        Err(ModelError::EntityNotFound) => Err(format::render()
+            .status(StatusCode::NOT_FOUND)
+            .json(json!({"msg": "Contest not found"}))),
+        Err(e) => Err(Err(e.into())),
+    }
+}
+
+/// Find a contest which can be managed by the user.
+async fn find_managed_contest(
+    ctx: &AppContext,
+    auth: &auth::JWT,
+    contest_id: i32,
+) -> Result<contests::Model, Result<Response>> {
+    let user = find_user_by_auth(ctx, auth).await?;
+    let contest = find_contest(ctx, contest_id).await?;
+    if !contest.is_managed_by(&user) {
+        return Err(permission_denied());
+    }
+
+    Ok(contest)
+}
+
+/// Find a contest which the user manages or participates in.
+async fn find_joined_contest(
+    ctx: &AppContext,
+    auth: &auth::JWT,
+    contest_id: i32,
+) -> Result<contests::Model, Result<Response>> {
+    let user = find_user_by_auth(ctx, auth).await?;
+    let contest = find_contest(ctx, contest_id).await?;
+    if !contest.is_managed_by(&user) {
+        match participants::Model::exists(&ctx.db, contest.id, user.id).await {
+            Ok(true) => {}
+            Ok(false) => return Err(permission_denied()),
+            Err(e) => return Err(Err(e.into())),
+        }
+    }
+
+    Ok(contest)
+}
+
+async fn list(State(ctx): State<AppContext>, auth: auth::JWT) -> Result<Response> {
+    let user = match find_user_by_auth(&ctx, &auth).await {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+
+    let mut data = vec![];
+    for contest in contests::Model::list(&ctx.db, &user).await? {
+        let problem_ids = contest.problem_ids(&ctx.db).await?;
+        data.push((contest, problem_ids));
+    }
+    format::json(ContestListResponse::new(data).done())
+}
+
+async fn get_one(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path(contest_id): Path<i32>,
+) -> Result<Response> {
+    let contest = match find_joined_contest(&ctx, &auth, contest_id).await {
+        Ok(c) => c,
+        Err(e) => return e,
+    };
+
+    let problem_ids = contest.problem_ids(&ctx.db).await?;
+    format::json(ContestResponse::new(contest, problem_ids))
+}
+
+async fn create(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Json(params): Json<contests::Params>,
+) -> Result<Response> {
+    let user = match verify_admin(&ctx, &auth).await {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+
+    match contests::Model::add(&ctx.db, &user, &params).await {
+        Ok(contest) => {
+            let problem_ids = contest.problem_ids(&ctx.db).await?;
+            format::render()
+                .status(StatusCode::CREATED)
+                .json(ContestResponse::new(contest, problem_ids))
+        }
+        Err(ModelError::EntityAlreadyExists) => format::render()
+            .status(StatusCode::CONFLICT)
+            .json(json!({"msg": "Contest exists"})),
+        Err(e) => Err(e.into()),
+    }
+}
+
+async fn edit(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path(contest_id): Path<i32>,
+    Json(params): Json<contests::Params>,
+) -> Result<Response> {
+    let user = match find_user_by_auth(&ctx, &auth).await {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+    let contest = match find_contest(&ctx, contest_id).await {
+        Ok(c) => c,
+        Err(e) => return e,
+    };
+    if !contest.is_managed_by(&user) {
+        return permission_denied();
+    }
+
+    match contest.edit(&ctx.db, &user, &params).await {
+        Ok(contest) => {
+            let problem_ids = contest.problem_ids(&ctx.db).await?;
+            format::json(ContestResponse::new(contest, problem_ids))
+        }
+        Err(ModelError::EntityAlreadyExists) => format::render()
+            .status(StatusCode::CONFLICT)
+            .json(json!({"msg": "Contest exists"})),
+        Err(e) => Err(e.into()),
+    }
+}
+
+async fn remove(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path(contest_id): Path<i32>,
+) -> Result<Response> {
+    let contest = match find_managed_contest(&ctx, &auth, contest_id).await {
+        Ok(c) => c,
+        Err(e) => return e,
+    };
+    contest.delete(&ctx.db).await?;
+    tracing::info!(contest_id, "contest deleted");
+
+    format::empty_json()
+}
+
+async fn list_participants(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path(contest_id): Path<i32>,
+) -> Result<Response> {
+    let contest = match find_joined_contest(&ctx, &auth, contest_id).await {
+        Ok(c) => c,
+        Err(e) => return e,
+    };
+
+    let participants = participants::Model::list(&ctx.db, contest.id).await?;
+    format::json(ParticipantListResponse::new(&participants).done())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddParticipantsRequest {
+    /// usernames of users to be added
+    pub users: Vec<String>,
+}
+
+async fn add_participants(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path(contest_id): Path<i32>,
+    Json(params): Json<AddParticipantsRequest>,
+) -> Result<Response> {
+    let contest = match find_managed_contest(&ctx, &auth, contest_id).await {
+        Ok(c) => c,
+        Err(e) => return e,
+    };
+
+    let txn = ctx.db.begin().await?;
+    let mut user_ids = vec![];
+    for username in &params.users {
+        match users::Model::find_by_username(&txn, username).await {
+            Ok(u) => user_ids.push(u.id),
+            Err(ModelError::EntityNotFound) => {
+                return format::render()
+                    .status(StatusCode::NOT_FOUND)
+                    .json(json!({"msg": format!("User {username} not found")}));
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+    contest.add_participants(&txn, &user_ids).await?;
+    txn.commit().await?;
+
+    let participants = participants::Model::list(&ctx.db, contest.id).await?;
+    format::json(ParticipantListResponse::new(&participants).done())
+}
+
+async fn remove_participant(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path((contest_id, username)): Path<(i32, String)>,
+) -> Result<Response> {
+    let contest = match find_managed_contest(&ctx, &auth, contest_id).await {
+        Ok(c) => c,
+        Err(e) => return e,
+    };
+
+    let participant = users::Model::find_by_username(&ctx.db, &username).await?;
+    match contest.remove_participant(&ctx.db, participant.id).await {
+        Ok(()) => format::empty_json(),
+        Err(ModelError::EntityNotFound) => format::render()
+            .status(StatusCode::NOT_FOUND)
+            .json(json!({"msg": "User is not a participant of contest"})),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Scoreboard of participants. Only managers can see submissions made while the
+/// scoreboard is frozen.
+async fn scoreboard(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path(contest_id): Path<i32>,
+) -> Result<Response> {
+    let user = match find_user_by_auth(&ctx, &auth).await {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+    let contest = match find_contest(&ctx, contest_id).await {
+        Ok(c) => c,
+        Err(e) => return e,
+    };
+    let is_manager = contest.is_managed_by(&user);
+    if !is_manager && !participants::Model::exists(&ctx.db, contest.id, user.id).await? {
+        return permission_denied();
+    }
+
+    let frozen = !is_manager && contest.is_frozen_at(models::now());
+    let (problem_ids, standings) = contest.scoreboard(&ctx.db, frozen).await?;
+    format::json(ScoreboardResponse::new(
+        &contest,
+        frozen,
+        problem_ids,
+        standings,
+    ))
+}
+
+pub fn routes() -> Routes {
+    Routes::new()
+        .prefix("contests")
+        .add("/", get(list))
+        .add("/", post(create))
+        .add("/:contest_id", get(get_one))
+        .add("/:contest_id", put(edit))
+        .add("/:contest_id", delete(remove))
+        .add("/:contest_id/participants", get(list_participants))
+        .add("/:contest_id/participants", post(add_participants))
+        .add(
+            "/:contest_id/participants/:username",
+            delete(remove_participant),
+        )
+        .add("/:contest_id/scoreboard", get(scoreboard))
+}
@@ -1,19 +1,222 @@
-use crate::models::courses;
+use crate::{
+    models::{
+        course_members::{self, CourseRole},
+        courses, users,
+    },
+    views::courses::CourseMemberListResponse,
+};
+use axum::http::StatusCode;
 use loco_rs::prelude::*;
+use sea_orm::TransactionTrait;
+use serde::Deserialize;
+use serde_json::json;
 
-#[allow(clippy::missing_errors_doc)]
-pub async fn list(State(ctx): State<AppContext>) -> Result<Response> {
-    format::json(courses::Entity::find().all(&ctx.db).await?)
+use super::{find_user_by_auth, permission_denied, verify_admin};
+
+async fn list(State(ctx): State<AppContext>, auth: auth::JWT) -> Result<Response> {
+    let user = match find_user_by_auth(&ctx, &auth).await {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+
+    format::json(courses::Model::list_visible_to(&ctx.db, &user).await?)
+}
+
+async fn get_one(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path(name): Path<String>,
+) -> Result<Response> {
+    let user = match find_user_by_auth(&ctx, &auth).await {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+    let course = courses::Model::find_by_name(&ctx.db, &name).await?;
+    if user.role != users::Role::Admin && course.role_of(&ctx.db, &user).await?.is_none() {
+        return permission_denied();
+    }
+
+    format::json(course)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateCourseRequest {
+    pub name: String,
+    /// username of the course's teacher
+    pub teacher: String,
+}
+
+async fn create(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Json(params): Json<CreateCourseRequest>,
+) -> Result<Response> {
+    if let Err(e) = verify_admin(&ctx, &auth).await {
+        return e;
+    }
+    let teacher = users::Model::find_by_username(&ctx.db, &params.teacher).await?;
+
+    let params = courses::AddParams {
+        name: params.name,
+        teacher,
+    };
+    match courses::Model::add(&ctx.db, &params).await {
+        Ok(course) => format::render().status(StatusCode::CREATED).json(course),
+        Err(ModelError::EntityAlreadyExists) => format::render()
+            .status(StatusCode::CONFLICT)
+            .json(json!({"msg": "Course exists"})),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EditCourseRequest {
+    /// new name of the course
+    pub name: Option<String>,
+    /// username of the new teacher
+    pub teacher: Option<String>,
+}
+
+async fn edit(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path(name): Path<String>,
+    Json(params): Json<EditCourseRequest>,
+) -> Result<Response> {
+    if let Err(e) = verify_admin(&ctx, &auth).await {
+        return e;
+    }
+    let course = courses::Model::find_by_name(&ctx.db, &name).await?;
+    let teacher = match &params.teacher {
+        Some(t) => Some(users::Model::find_by_username(&ctx.db, t).await?),
+        None => None,
+    };
+
+    let params = courses::EditParams {
+        name: params.name,
+        teacher,
+    };
+    match course.edit(&ctx.db, &params).await {
+        Ok(course) => format::json(course),
+        Err(ModelError::EntityAlreadyExists) => format::render()
+            .status(StatusCode::CONFLICT)
+            .json(json!({"msg": "Course exists"})),
+        Err(e) => Err(e.into()),
+    }
+}
+
+async fn remove(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path(name): Path<String>,
+) -> Result<Response> {
+    if let Err(e) = verify_admin(&ctx, &auth).await {
+        return e;
+    }
+    let course = courses::Model::find_by_name(&ctx.db, &name).await?;
+    course.delete(&ctx.db).await?;
+    tracing::info!(name, "course deleted");
+
+    format::empty_json()
+}
+
+async fn list_members(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path(name): Path<String>,
+) -> Result<Response> {
+    let user = match find_user_by_auth(&ctx, &auth).await {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+    let course = courses::Model::find_by_name(&ctx.db, &name).await?;
+    if user.role != users::Role::Admin && course.role_of(&ctx.db, &user).await?.is_none() {
+        return permission_denied();
+    }
+
+    let members = course.members(&ctx.db).await?;
+    format::json(CourseMemberListResponse::new(&members).done())
 }
 
-#[allow(clippy::missing_errors_doc)]
-pub async fn get_one(Path(name): Path<String>, State(ctx): State<AppContext>) -> Result<Response> {
-    format::json(courses::Model::find_by_name(&ctx.db, &name).await?)
+#[derive(Debug, Deserialize)]
+pub struct AddMembersRequest {
+    /// usernames of users to be added
+    pub users: Vec<String>,
+    pub role: CourseRole,
+}
+
+async fn add_members(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path(name): Path<String>,
+    Json(params): Json<AddMembersRequest>,
+) -> Result<Response> {
+    let user = match find_user_by_auth(&ctx, &auth).await {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+    let course = courses::Model::find_by_name(&ctx.db, &name).await?;
+    if !course.is_managed_by(&ctx.db, &user).await? {
+        return permission_denied();
+    }
+
+    let txn = ctx.db.begin().await?;
+    for username in &params.users {
+        let member = match users::Model::find_by_username(&txn, username).await {
+            Ok(u) => u,
+            Err(ModelError::EntityNotFound) => {
+                return format::render()
+                    .status(StatusCode::NOT_FOUND)
+                    .json(json!({"msg": format!("User {username} not found")}));
+            }
+            Err(e) => return Err(e.into()),
+        };
+        course_members::Model::upsert(&txn, course.id, member.id, params.role).await?;
+    }
+    txn.commit().await?;
+
+    let members = course.members(&ctx.db).await?;
+    format::json(CourseMemberListResponse::new(&members).done())
+}
+
+async fn remove_member(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path((name, username)): Path<(String, String)>,
+) -> Result<Response> {
+    let user = match find_user_by_auth(&ctx, &auth).await {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+    let course = courses::Model::find_by_name(&ctx.db, &name).await?;
+    if !course.is_managed_by(&ctx.db, &user).await? {
+        return permission_denied();
+    }
+
+    let member = users::Model::find_by_username(&ctx.db, &username).await?;
+    if member.id == course.teacher_id {
+        return format::render()
+            .status(StatusCode::BAD_REQUEST)
+            .json(json!({"msg": "Cannot remove the teacher of course"}));
+    }
+    match course_members::Model::remove(&ctx.db, course.id, member.id).await {
+        Ok(()) => format::empty_json(),
+        Err(ModelError::EntityNotFound) => format::render()
+            .status(StatusCode::NOT_FOUND)
+            .json(json!({"msg": "User is not a member of course"})),
+        Err(e) => Err(e.into()),
+    }
 }
 
 pub fn routes() -> Routes {
     Routes::new()
         .prefix("courses")
         .add("/", get(list))
+        .add("/", post(create))
         .add("/:name", get(get_one))
+        .add("/:name", put(edit))
+        .add("/:name", delete(remove))
+        .add("/:name/members", get(list_members))
+        .add("/:name/members", post(add_members))
+        .add("/:name/members/:username", delete(remove_member))
 }
@@ -0,0 +1,222 @@
+use crate::{
+    models::{course_members::CourseRole, courses, homeworks, users},
+    views::homeworks::{HomeworkListResponse, HomeworkResponse, ScoreboardResponse},
+};
+use axum::{
+    body::Body,
+    http::{header, StatusCode},
+};
+use loco_rs::prelude::*;
+use serde_json::json;
+
+use super::{find_user_by_auth, permission_denied};
+
+/// Find the course and check whether the user can read its homeworks, only
+/// admins and course members are allowed.
+async fn find_readable_course(
+    ctx: &AppContext,
+    user: &users::Model,
+    name: &str,
+) -> Result<(courses::Model, Option<CourseRole>), Result<Response>> {
+    let course = courses::Model::find_by_name(&ctx.db, name)
+        .await
+        .map_err(|e| Err(e.into()))?;
+    let role = course
+        .role_of(&ctx.db, user)
+        .await
+        .map_err(|e| Err(e.into()))?;
+    if user.role != users::Role::Admin && role.is_none() {
+        return Err(permission_denied());
+    }
+
+    Ok((course, role))
+}
+
+async fn list(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path(name): Path<String>,
+) -> Result<Response> {
+    let user = match find_user_by_auth(&ctx, &auth).await {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+    let (course, _) = match find_readable_course(&ctx, &user, &name).await {
+        Ok(c) => c,
+        Err(e) => return e,
+    };
+
+    let mut data = vec![];
+    for homework in homeworks::Model::list_in_course(&ctx.db, course.id).await? {
+        let problem_ids = homework.problem_ids(&ctx.db).await?;
+        data.push((homework, problem_ids));
+    }
+    format::json(HomeworkListResponse::new(data).done())
+}
+
+async fn get_one(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path((name, homework_id)): Path<(String, i32)>,
+) -> Result<Response> {
+    let user = match find_user_by_auth(&ctx, &auth).await {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+    let (course, _) = match find_readable_course(&ctx, &user, &name).await {
+        Ok(c) => c,
+        Err(e) => return e,
+    };
+
+    let homework = homeworks::Model::find_in_course(&ctx.db, course.id, homework_id).await?;
+    let problem_ids = homework.problem_ids(&ctx.db).await?;
+    format::json(HomeworkResponse::new(homework, problem_ids))
+}
+
+async fn create(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path(name): Path<String>,
+    Json(params): Json<homeworks::Params>,
+) -> Result<Response> {
+    let user = match find_user_by_auth(&ctx, &auth).await {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+    let course = courses::Model::find_by_name(&ctx.db, &name).await?;
+    if !course.is_managed_by(&ctx.db, &user).await? {
+        return permission_denied();
+    }
+
+    match homeworks::Model::add(&ctx.db, &course, &params).await {
+        Ok(homework) => {
+            let problem_ids = homework.problem_ids(&ctx.db).await?;
+            format::render()
+                .status(StatusCode::CREATED)
+                .json(HomeworkResponse::new(homework, problem_ids))
+        }
+        Err(ModelError::EntityAlreadyExists) => format::render()
+            .status(StatusCode::CONFLICT)
+            .json(json!({"msg": "Homework exists"})),
+        Err(e) => Err(e.into()),
+    }
+}
+
+async fn edit(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path((name, homework_id)): Path<(String, i32)>,
+    Json(params): Json<homeworks::Params>,
+) -> Result<Response> {
+    let user = match find_user_by_auth(&ctx, &auth).await {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+    let course = courses::Model::find_by_name(&ctx.db, &name).await?;
+    if !course.is_managed_by(&ctx.db, &user).await? {
+        return permission_denied();
+    }
+
+    let homework = homeworks::Model::find_in_course(&ctx.db, course.id, homework_id).await?;
+    match homework.edit(&ctx.db, &params).await {
+        Ok(homework) => {
+            let problem_ids = homework.problem_ids(&ctx.db).await?;
+            format::json(HomeworkResponse::new(homework, problem_ids))
+        }
+        Err(ModelError::EntityAlreadyExists) => format::render()
+            .status(StatusCode::CONFLICT)
+            .json(json!({"msg": "Homework exists"})),
+        Err(e) => Err(e.into()),
+    }
+}
+
+async fn remove(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path((name, homework_id)): Path<(String, i32)>,
+) -> Result<Response> {
+    let user = match find_user_by_auth(&ctx, &auth).await {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+    let course = courses::Model::find_by_name(&ctx.db, &name).await?;
+    if !course.is_managed_by(&ctx.db, &user).await? {
+        return permission_denied();
+    }
+
+    let homework = homeworks::Model::find_in_course(&ctx.db, course.id, homework_id).await?;
+    homework.delete(&ctx.db).await?;
+    tracing::info!(course = name, homework_id, "homework deleted");
+
+    format::empty_json()
+}
+
+/// Build the scoreboard of a homework, only admins, teachers and TAs of the
+/// course can see scores of all students.
+async fn build_scoreboard(
+    ctx: &AppContext,
+    auth: &auth::JWT,
+    name: &str,
+    homework_id: i32,
+) -> Result<ScoreboardResponse, Result<Response>> {
+    let user = find_user_by_auth(ctx, auth).await?;
+    let (course, role) = find_readable_course(ctx, &user, name).await?;
+    if user.role != users::Role::Admin && role == Some(CourseRole::Student) {
+        return Err(permission_denied());
+    }
+
+    let scoreboard = async {
+        let homework = homeworks::Model::find_in_course(&ctx.db, course.id, homework_id).await?;
+        let problem_ids = homework.problem_ids(&ctx.db).await?;
+        let scores = homework.scoreboard(&ctx.db, &problem_ids).await?;
+        Ok::<_, ModelError>(ScoreboardResponse::new(problem_ids, scores))
+    };
+    scoreboard.await.map_err(|e| Err(e.into()))
+}
+
+async fn scoreboard(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path((name, homework_id)): Path<(String, i32)>,
+) -> Result<Response> {
+    match build_scoreboard(&ctx, &auth, &name, homework_id).await {
+        Ok(scoreboard) => format::json(scoreboard),
+        Err(e) => e,
+    }
+}
+
+async fn scoreboard_csv(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path((name, homework_id)): Path<(String, i32)>,
+) -> Result<Response> {
+    let scoreboard = match build_scoreboard(&ctx, &auth, &name, homework_id).await {
+        Ok(s) => s,
+        Err(e) => return e,
+    };
+    let content = scoreboard.to_csv().map_err(|e| Error::Any(e.into()))?;
+
+    Ok(format::render()
+        .header(header::CONTENT_TYPE, "text/csv")
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{name}-{homework_id}.csv\""),
+        )
+        .response()
+        .body(Body::from(content))?)
+}
+
+pub fn routes() -> Routes {
+    Routes::new()
+        .prefix("courses")
+        .add("/:name/homeworks", get(list))
+        .add("/:name/homeworks", post(create))
+        .add("/:name/homeworks/:homework_id", get(get_one))
+        .add("/:name/homeworks/:homework_id", put(edit))
+        .add("/:name/homeworks/:homework_id", delete(remove))
+        .add("/:name/homeworks/:homework_id/scoreboard", get(scoreboard))
+        .add(
+            "/:name/homeworks/:homework_id/scoreboard/csv",
+            get(scoreboard_csv),
+        )
+}
@@ -1,7 +1,10 @@
 pub mod auth;
+pub mod contests;
 pub mod courses;
+pub mod homeworks;
 pub mod notes;
 pub mod problems;
+pub mod submissions;
 pub mod user;
 
 /// utils
@@ -9,7 +12,10 @@ use axum::http::StatusCode;
 use loco_rs::{controller::middleware, prelude::*};
 use serde_json::json;
 
-use crate::models::users::{self, Role};
+use crate::models::{
+    self,
+    users::{self, Role},
+};
 
 async fn verify_admin(
     ctx: &AppContext,
@@ -39,3 +45,26 @@ async fn find_user_by_auth(
         .map_err(|e| Err(e.into()))?;
     Ok(user)
 }
+
+/// Find a problem readable by the user, responds 404 if the problem does not
+/// exist and 403 if it is not visible to the user.
+async fn find_visible_problem(
+    ctx: &AppContext,
+    user: &users::Model,
+    problem_id: i32,
+) -> Result<models::problems::Model, Result<Response>> {
+    let problem = match models::problems::Model::find_by_id(&ctx.db, problem_id).await {
+        Ok(p) => p,
+        Err(ModelError::EntityNotFound) => {
+            return Err(format::render()
+                .status(StatusCode::NOT_FOUND)
+                .json(json!({"msg": "Problem not found"})));
+        }
+        Err(e) => return Err(Err(e.into())),
+    };
+    match problem.is_visible_to(&ctx.db, user).await {
+        Ok(true) => Ok(problem),
+        Ok(false) => Err(permission_denied()),
+        Err(e) => Err(Err(e.into())),
+    }
+}
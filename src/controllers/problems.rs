@@ -1,17 +1,33 @@
+use std::{fs::File, io::Cursor};
+
 use crate::{
+    judge::SandboxKind,
     models::{
         self,
-        problems::{self, Type, Visibility},
-        transform_db_error, users,
+        problems::{self, templates, test_case_versions, Type, Visibility},
+        submissions, transform_db_error, users, Language, LanguageSet,
     },
-    views::problems::{ProblemDetailResponse, ProblemListResponse},
+    settings::Settings,
+    storage,
+    views::problems::{ProblemDetailResponse, ProblemListResponse, TestCaseVersionListResponse},
+};
+use axum::{
+    body::{Body, Bytes},
+    extract::{multipart::MultipartError, DefaultBodyLimit, Multipart, Query},
+    http::{header, StatusCode},
+    routing::patch,
+};
+use loco_rs::{
+    controller::{format::render, views::pagination::PagerMeta},
+    prelude::*,
 };
-use axum::extract::{DefaultBodyLimit, Multipart, Query};
-use loco_rs::{controller::format::render, prelude::*};
+use sea_orm::DatabaseTransaction;
 use serde::Deserialize;
-use std::path::PathBuf;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncWriteExt;
 
-use super::{find_user_by_auth, permission_denied, verify_admin};
+use super::{find_user_by_auth, find_visible_problem, permission_denied, verify_admin};
 
 #[derive(Debug, Deserialize)]
 pub struct CreateProblemRequest {
@@ -23,9 +39,11 @@ pub struct CreateProblemRequest {
     /// Problem description struct
     pub description: problems::descriptions::AddParams,
     pub r#type: Option<Type>,
-    pub allowed_language: Option<i32>,
+    pub allowed_language: Option<LanguageSet>,
     pub quota: Option<i32>,
     pub tasks: Vec<problems::tasks::AddParams>,
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 async fn create(
@@ -48,6 +66,7 @@ async fn create(
         allowed_language: params.allowed_language,
         quota: params.quota,
         tasks: params.tasks,
+        tags: params.tags,
     };
 
     let problem = problems::Model::add(&ctx.db, &params).await?;
@@ -57,17 +76,21 @@ async fn create(
 
 #[derive(Debug, Deserialize)]
 pub struct ListProblemRequest {
-    pub offset: Option<usize>,
-    pub count: Option<usize>,
+    pub offset: Option<u64>,
+    pub count: Option<u64>,
     pub name: Option<String>,
     pub tags: Option<String>,
     pub course: Option<String>,
+    #[serde(default)]
+    pub sort_by: problems::SortBy,
+    #[serde(default)]
+    pub order: problems::SortOrder,
 }
 
 async fn list(
     State(ctx): State<AppContext>,
     auth: auth::JWT,
-    params: Query<ListProblemRequest>,
+    Query(params): Query<ListProblemRequest>,
 ) -> Result<Response> {
     let user = users::Model::find_by_claims_key(&ctx.db, &auth.claims.pid).await?;
 
@@ -75,27 +98,45 @@ async fn list(
         viewer: user,
         offset: params.offset,
         count: params.count,
-        name: params.name.clone(),
+        sort_by: params.sort_by,
+        order: params.order,
+        name: params.name,
         tags: params
             .tags
             .as_ref()
             .map(|t| t.split(',').map(str::to_string).collect()),
-        course: params.course.clone(),
+        course: params.course,
     };
 
-    let problems = problems::Model::list(&ctx.db, &params).await?;
+    let (problems, total) = problems::Model::list(&ctx.db, &params).await?;
+    let problem_ids = problems.iter().map(|p| p.id).collect::<Vec<_>>();
+    let stats = submissions::Model::problem_stats(&ctx.db, &problem_ids).await?;
+    let tags = problems::tags::Model::find_by_problems(&ctx.db, &problem_ids).await?;
+    // all problems are in a single page if count is not given
+    let page_size = params.count.unwrap_or(total).max(1);
+    let meta = PagerMeta {
+        page: params.offset.unwrap_or(0) / page_size + 1,
+        page_size,
+        total_pages: total.div_ceil(page_size),
+    };
 
-    format::json(ProblemListResponse::new(&problems).done())
+    format::json(ProblemListResponse::new(&problems, &stats, &tags, meta).done())
 }
 
 async fn get_problem(
     State(ctx): State<AppContext>,
-    // auth: auth::JWT,
+    auth: auth::JWT,
     Path(problem_id): Path<i32>,
 ) -> Result<Response> {
-    // TOOD: authz
+    let viewer = match find_user_by_auth(&ctx, &auth).await {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
 
-    let prob = problems::Model::find_by_id(&ctx.db, problem_id).await?;
+    let prob = match find_visible_problem(&ctx, &viewer, problem_id).await {
+        Ok(p) => p,
+        Err(e) => return e,
+    };
     let desc = prob
         .find_related(models::_entities::problem_descriptions::Entity)
         .one(&ctx.db)
@@ -108,64 +149,549 @@ async fn get_problem(
         .await
         .map_err(transform_db_error)?
         .ok_or(ModelError::EntityNotFound)?;
+    let courses = prob.courses(&ctx.db).await?;
+    let tags = prob.tags(&ctx.db).await?;
     let tasks = prob.tasks(&ctx.db).await?;
+    let templates = if prob.problem_type() == Type::FillInTemplate {
+        templates::Model::find_for_problem(&ctx.db, prob.id).await?
+    } else {
+        vec![]
+    };
+    let stats = submissions::Model::problem_stats(&ctx.db, &[prob.id])
+        .await?
+        .remove(&prob.id)
+        .unwrap_or_default();
+    let high_score = submissions::Model::high_score(&ctx.db, prob.id, viewer.id).await?;
+    let remaining_quota = submissions::Model::remaining_quota(&ctx.db, &prob, &viewer).await?;
 
-    format::json(ProblemDetailResponse::new(&prob, &desc, &owner, &tasks).done())
+    format::json(
+        ProblemDetailResponse::new(
+            &prob,
+            &desc,
+            &owner,
+            &courses,
+            &tags,
+            &tasks,
+            &templates,
+            prob.is_managed_by(&viewer),
+            &stats,
+            high_score,
+            remaining_quota,
+        )
+        .done(),
+    )
 }
 
-async fn upload_test_case(
+async fn edit(
     State(ctx): State<AppContext>,
     auth: auth::JWT,
     Path(problem_id): Path<i32>,
-    mut multipart: Multipart,
+    Json(params): Json<problems::EditParams>,
 ) -> Result<Response> {
     let user = match find_user_by_auth(&ctx, &auth).await {
         Ok(u) => u,
         Err(e) => return e,
     };
     let prob = problems::Model::find_by_id(&ctx.db, problem_id).await?;
-    if user.id != prob.owner_id {
+    if !prob.is_managed_by(&user) {
+        return permission_denied();
+    }
+
+    let problem = prob.edit(&ctx.db, &user, &params).await?;
+
+    format::json(problem)
+}
+
+async fn archive(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path(problem_id): Path<i32>,
+) -> Result<Response> {
+    let user = match find_user_by_auth(&ctx, &auth).await {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+    let prob = problems::Model::find_by_id(&ctx.db, problem_id).await?;
+    if !prob.is_managed_by(&user) {
+        return permission_denied();
+    }
+
+    let problem = prob.into_active_model().archive(&ctx.db).await?;
+    tracing::info!(problem_id, "problem archived");
+
+    format::json(problem)
+}
+
+async fn restore(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path(problem_id): Path<i32>,
+) -> Result<Response> {
+    if let Err(e) = verify_admin(&ctx, &auth).await {
+        return e;
+    }
+    let prob = problems::Model::find_by_id(&ctx.db, problem_id).await?;
+
+    let problem = prob.into_active_model().restore(&ctx.db).await?;
+    tracing::info!(problem_id, "problem restored");
+
+    format::json(problem)
+}
+
+async fn remove(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path(problem_id): Path<i32>,
+) -> Result<Response> {
+    if let Err(e) = verify_admin(&ctx, &auth).await {
+        return e;
+    }
+    let prob = problems::Model::find_by_id(&ctx.db, problem_id).await?;
+
+    let paths = prob.delete_with_relations(&ctx.db).await?;
+    tracing::info!(problem_id, "problem deleted");
+    for path in paths {
+        // the problem is already gone, leftover files should not fail the request
+        if let Err(err) = ctx.storage.as_ref().delete(&path).await {
+            tracing::warn!(error = ?err, path = %path.display(), "could not delete file");
+        }
+    }
+
+    format::empty_json()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UploadCheckerRequest {
+    pub language: Language,
+    /// Source code
+    pub code: String,
+}
+
+/// Delete the replaced or removed checker from storage.
+async fn delete_old_checker(ctx: &AppContext, checker_id: Option<&String>) {
+    let Some(checker_id) = checker_id else {
+        return;
+    };
+    let path = problems::checker_path(checker_id);
+    if let Err(err) = ctx.storage.as_ref().delete(&path).await {
+        tracing::warn!(error = ?err, path = %path.display(), "could not delete file");
+    }
+}
+
+async fn upload_checker(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path(problem_id): Path<i32>,
+    Json(params): Json<UploadCheckerRequest>,
+) -> Result<Response> {
+    let user = match find_user_by_auth(&ctx, &auth).await {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+    let prob = problems::Model::find_by_id(&ctx.db, problem_id).await?;
+    if !prob.is_managed_by(&user) {
+        return permission_denied();
+    }
+    if Settings::from_context(&ctx)?.sandbox.kind == SandboxKind::Remote {
+        return format::render()
+            .status(StatusCode::BAD_REQUEST)
+            .json(json!({"msg": "Custom checker is not supported by the sandbox"}));
+    }
+
+    let checker_id = uuid::Uuid::new_v4().to_string();
+    ctx.storage
+        .as_ref()
+        .upload(
+            problems::checker_path(&checker_id).as_path(),
+            &Bytes::from(params.code),
+        )
+        .await?;
+
+    let old_checker_id = prob.checker_id.clone();
+    let problem = prob
+        .into_active_model()
+        .update_checker(&ctx.db, Some((checker_id, params.language)))
+        .await?;
+    tracing::info!(problem_id, "checker uploaded");
+    delete_old_checker(&ctx, old_checker_id.as_ref()).await;
+
+    format::json(problem)
+}
+
+/// Remove the custom checker, so that outputs are compared line by line again.
+async fn remove_checker(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path(problem_id): Path<i32>,
+) -> Result<Response> {
+    let user = match find_user_by_auth(&ctx, &auth).await {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+    let prob = problems::Model::find_by_id(&ctx.db, problem_id).await?;
+    if !prob.is_managed_by(&user) {
+        return permission_denied();
+    }
+
+    let old_checker_id = prob.checker_id.clone();
+    let problem = prob
+        .into_active_model()
+        .update_checker(&ctx.db, None)
+        .await?;
+    tracing::info!(problem_id, "checker removed");
+    delete_old_checker(&ctx, old_checker_id.as_ref()).await;
+
+    format::json(problem)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UploadTemplateRequest {
+    /// Source code with blanks, see [`templates`] for the format
+    pub template: String,
+}
+
+async fn upload_template(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path((problem_id, language)): Path<(i32, Language)>,
+    Json(params): Json<UploadTemplateRequest>,
+) -> Result<Response> {
+    let user = match find_user_by_auth(&ctx, &auth).await {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+    let prob = problems::Model::find_by_id(&ctx.db, problem_id).await?;
+    if !prob.is_managed_by(&user) {
+        return permission_denied();
+    }
+    if let Err(err) = templates::validate(&params.template) {
+        return format::render()
+            .status(StatusCode::BAD_REQUEST)
+            .json(json!({"msg": format!("Invalid template: {err}")}));
+    }
+
+    let template = templates::Model::set(&ctx.db, prob.id, language, params.template).await?;
+    tracing::info!(problem_id, language = language.name(), "template uploaded");
+
+    format::json(template)
+}
+
+async fn remove_template(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path((problem_id, language)): Path<(i32, Language)>,
+) -> Result<Response> {
+    let user = match find_user_by_auth(&ctx, &auth).await {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+    let prob = problems::Model::find_by_id(&ctx.db, problem_id).await?;
+    if !prob.is_managed_by(&user) {
+        return permission_denied();
+    }
+
+    match templates::Model::remove(&ctx.db, prob.id, language).await {
+        Ok(()) => {}
+        Err(ModelError::EntityNotFound) => {
+            return format::render()
+                .status(StatusCode::NOT_FOUND)
+                .json(json!({"msg": "Template not found"}));
+        }
+        Err(e) => return Err(e.into()),
+    }
+    tracing::info!(problem_id, language = language.name(), "template removed");
+
+    format::empty_json()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SandboxTokenQuery {
+    pub token: String,
+}
+
+/// Read the current test case zip of a problem from storage.
+async fn test_case_response(ctx: &AppContext, problem: &problems::Model) -> Result<Response> {
+    let Some(test_case_id) = &problem.test_case_id else {
+        return format::render()
+            .status(StatusCode::NOT_FOUND)
+            .json(json!({"msg": "Test case not found"}));
+    };
+    let content: Vec<u8> = ctx
+        .storage
+        .as_ref()
+        .download(problems::test_case_path(test_case_id).as_path())
+        .await?;
+
+    Ok(format::render()
+        .header(header::CONTENT_TYPE, "application/zip")
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}.zip\"", problem.id),
+        )
+        .response()
+        .body(Body::from(content))?)
+}
+
+async fn download_test_case(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path(problem_id): Path<i32>,
+) -> Result<Response> {
+    let user = match find_user_by_auth(&ctx, &auth).await {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+    let prob = problems::Model::find_by_id(&ctx.db, problem_id).await?;
+    if !prob.is_managed_by(&user) {
         return permission_denied();
     }
 
-    let file_content = loop {
-        let Some(field) = multipart.next_field().await.map_err(|err| {
-            tracing::error!(error = ?err,"could not read multipart");
-            Error::BadRequest("could not read multipart".into())
-        })?
-        else {
-            return Err(Error::BadRequest("cloud not find test case file".into()));
-        };
+    test_case_response(&ctx, &prob).await
+}
+
+/// Test case download for remote sandbox, authenticated by the shared token.
+async fn download_test_case_for_sandbox(
+    State(ctx): State<AppContext>,
+    Path(problem_id): Path<i32>,
+    Query(params): Query<SandboxTokenQuery>,
+) -> Result<Response> {
+    let settings = Settings::from_context(&ctx)?;
+    if !settings.sandbox.verify_token(&params.token) {
+        return permission_denied();
+    }
+    let prob = problems::Model::find_by_id(&ctx.db, problem_id).await?;
+
+    test_case_response(&ctx, &prob).await
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct UploadTestCaseQuery {
+    /// Replace problem tasks with the ones declared in `meta.json`
+    #[serde(default)]
+    pub replace_tasks: bool,
+}
+
+/// Test case zip spooled to a temporary file
+struct SpooledTestCase {
+    file: File,
+    /// Size in bytes
+    size: u64,
+    /// Hex encoded SHA-256 digest
+    sha256: String,
+}
 
+/// Stream the first `application/x-zip` field of the form into a temporary
+/// file and compute its checksum on the way, so that the upload is never held
+/// in memory as a whole. Returns `None` if there is no such field.
+async fn spool_test_case(multipart: &mut Multipart) -> Result<Option<SpooledTestCase>> {
+    let multipart_error = |err: MultipartError| {
+        tracing::error!(error = ?err, "could not read multipart");
+        Error::BadRequest("could not read multipart".into())
+    };
+
+    while let Some(mut field) = multipart.next_field().await.map_err(multipart_error)? {
         if !matches!(field.content_type(), Some("application/x-zip")) {
             continue;
         }
 
-        break field.bytes().await.map_err(|err| {
-            tracing::error!(error = ?err,"could not read bytes");
-            Error::BadRequest("could not read bytes".into())
-        })?;
+        let mut file = tokio::fs::File::from_std(tempfile::tempfile()?);
+        let mut hasher = Sha256::new();
+        let mut size = 0u64;
+        while let Some(chunk) = field.chunk().await.map_err(multipart_error)? {
+            hasher.update(&chunk);
+            file.write_all(&chunk).await?;
+            size += chunk.len() as u64;
+        }
+        file.flush().await?;
+
+        return Ok(Some(SpooledTestCase {
+            file: file.into_std().await,
+            size,
+            sha256: format!("{:x}", hasher.finalize()),
+        }));
+    }
+
+    Ok(None)
+}
+
+async fn upload_test_case(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path(problem_id): Path<i32>,
+    Query(params): Query<UploadTestCaseQuery>,
+    mut multipart: Multipart,
+) -> Result<Response> {
+    let user = match find_user_by_auth(&ctx, &auth).await {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+    let prob = problems::Model::find_by_id(&ctx.db, problem_id).await?;
+    if user.id != prob.owner_id {
+        return permission_denied();
+    }
+
+    let Some(spooled) = spool_test_case(&mut multipart).await? else {
+        return format::render().status(StatusCode::BAD_REQUEST).json(
+            json!({"msg": "Test case not found, expected a multipart field of type application/x-zip"}),
+        );
     };
 
-    prob.validate_test_case(&ctx.db, &file_content).await?;
+    let limits = Settings::from_context(&ctx)?.test_case;
+    let meta = prob
+        .validate_test_case(
+            &ctx.db,
+            spooled.file.try_clone()?,
+            params.replace_tasks,
+            &limits,
+        )
+        .await?;
     tracing::info!(problem_id = prob.id, "test case validated");
 
     let test_case_id = uuid::Uuid::new_v4();
-    let file_name = format!("{test_case_id}.zip");
-    let path = PathBuf::from("test-case").join(file_name);
-    ctx.storage
-        .as_ref()
-        .upload(path.as_path(), &file_content)
-        .await?;
+    storage::upload_file(
+        &ctx,
+        problems::test_case_path(&test_case_id.to_string()).as_path(),
+        spooled.file,
+    )
+    .await?;
     tracing::info!(test_case_id = ?test_case_id, "test case uploaded");
 
-    prob.into_active_model()
-        .update_test_case_id(&ctx.db, Some(test_case_id.to_string()))
-        .await?;
+    let txn = ctx.db.begin().await?;
+    let version = test_case_versions::Model::add(
+        &txn,
+        &test_case_versions::AddParams {
+            problem_id: prob.id,
+            uploader_id: user.id,
+            test_case_id: test_case_id.to_string(),
+            size: i64::try_from(spooled.size).unwrap_or(i64::MAX),
+            sha256: spooled.sha256,
+        },
+    )
+    .await?;
+    switch_test_case(&txn, prob, meta, params.replace_tasks, version.test_case_id).await?;
+    txn.commit().await?;
 
     format::empty_json()
 }
 
+/// Point the problem to a stored test case, and replace its tasks with the ones
+/// declared in `meta.json` if requested.
+async fn switch_test_case(
+    db: &DatabaseTransaction,
+    prob: problems::Model,
+    meta: Option<problems::test_case::Meta>,
+    replace_tasks: bool,
+    test_case_id: String,
+) -> Result<problems::Model> {
+    if let Some(meta) = meta.filter(|_| replace_tasks) {
+        let tasks = meta.tasks.iter().map(Into::into).collect::<Vec<_>>();
+        problems::tasks::Model::set_for_problem(db, prob.id, &tasks).await?;
+        tracing::info!(problem_id = prob.id, "tasks replaced by meta.json");
+    }
+    let problem = prob
+        .into_active_model()
+        .update_test_case_id(db, Some(test_case_id))
+        .await?;
+
+    Ok(problem)
+}
+
+async fn list_test_case_versions(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path(problem_id): Path<i32>,
+) -> Result<Response> {
+    let user = match find_user_by_auth(&ctx, &auth).await {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+    let prob = problems::Model::find_by_id(&ctx.db, problem_id).await?;
+    if !prob.is_managed_by(&user) {
+        return permission_denied();
+    }
+
+    let versions = test_case_versions::Model::list_for_problem(&ctx.db, prob.id).await?;
+
+    format::json(TestCaseVersionListResponse::new(&prob, &versions).done())
+}
+
+/// Switch back to a previous test case version. The zip is validated against
+/// current tasks again, unless they are replaced by its `meta.json`.
+async fn rollback_test_case(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path((problem_id, version_id)): Path<(i32, i32)>,
+    Query(params): Query<UploadTestCaseQuery>,
+) -> Result<Response> {
+    let user = match find_user_by_auth(&ctx, &auth).await {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+    let prob = problems::Model::find_by_id(&ctx.db, problem_id).await?;
+    if !prob.is_managed_by(&user) {
+        return permission_denied();
+    }
+    let Ok(version) =
+        test_case_versions::Model::find_in_problem(&ctx.db, prob.id, version_id).await
+    else {
+        return format::render()
+            .status(StatusCode::NOT_FOUND)
+            .json(json!({"msg": "Test case version not found"}));
+    };
+
+    let content: Vec<u8> = ctx
+        .storage
+        .as_ref()
+        .download(problems::test_case_path(&version.test_case_id).as_path())
+        .await?;
+    let limits = Settings::from_context(&ctx)?.test_case;
+    let meta = prob
+        .validate_test_case(&ctx.db, Cursor::new(content), params.replace_tasks, &limits)
+        .await?;
+
+    let txn = ctx.db.begin().await?;
+    let problem =
+        switch_test_case(&txn, prob, meta, params.replace_tasks, version.test_case_id).await?;
+    txn.commit().await?;
+    tracing::info!(problem_id, version_id, "test case rolled back");
+
+    format::json(problem)
+}
+
+/// Remove test case versions which are neither in use nor judged against, and
+/// list the remaining ones.
+async fn collect_test_case_garbage(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path(problem_id): Path<i32>,
+) -> Result<Response> {
+    let user = match find_user_by_auth(&ctx, &auth).await {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+    let prob = problems::Model::find_by_id(&ctx.db, problem_id).await?;
+    if !prob.is_managed_by(&user) {
+        return permission_denied();
+    }
+
+    let paths = test_case_versions::Model::remove_unreferenced(&ctx.db, &prob).await?;
+    tracing::info!(
+        problem_id,
+        count = paths.len(),
+        "unused test case versions removed"
+    );
+    for path in paths {
+        // the versions are already gone, leftover files should not fail the request
+        if let Err(err) = ctx.storage.as_ref().delete(&path).await {
+            tracing::warn!(error = ?err, path = %path.display(), "could not delete file");
+        }
+    }
+
+    let versions = test_case_versions::Model::list_for_problem(&ctx.db, prob.id).await?;
+
+    format::json(TestCaseVersionListResponse::new(&prob, &versions).done())
+}
+
 pub fn routes() -> Routes {
     Routes::new()
         .prefix("problems")
@@ -174,7 +700,35 @@ pub fn routes() -> Routes {
         .add("/", get(list))
         .add("/:problem_id", get(get_problem))
         .add("/view/:problem_id", get(get_problem))
+        .add("/:problem_id/test-case", get(download_test_case))
+        .add("/:problem_id/testdata", get(download_test_case_for_sandbox))
         .add("/manage/:problem_id", put(upload_test_case))
+        .add("/manage/:problem_id", patch(edit))
+        .add("/manage/:problem_id", delete(remove))
+        .add("/manage/:problem_id/archive", post(archive))
+        .add("/manage/:problem_id/restore", post(restore))
+        .add(
+            "/manage/:problem_id/test-case/versions",
+            get(list_test_case_versions),
+        )
+        .add(
+            "/manage/:problem_id/test-case/versions/:version_id/rollback",
+            post(rollback_test_case),
+        )
+        .add("/manage/:problem_id/checker", put(upload_checker))
+        .add("/manage/:problem_id/checker", delete(remove_checker))
+        .add(
+            "/manage/:problem_id/templates/:language",
+            put(upload_template),
+        )
+        .add(
+            "/manage/:problem_id/templates/:language",
+            delete(remove_template),
+        )
+        .add(
+            "/manage/:problem_id/test-case/gc",
+            post(collect_test_case_garbage),
+        )
         .add(
             "/:problem_id",
             // change body limit to 128 MB
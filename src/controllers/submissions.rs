@@ -0,0 +1,489 @@
+use crate::{
+    judge::remote::CompleteRequest,
+    models::{
+        self, homeworks,
+        problems::{self, templates, Type},
+        submissions::{self, Status},
+        users, Language,
+    },
+    settings::Settings,
+    views::submissions::{SubmissionDetailResponse, SubmissionListResponse},
+    workers::judge::{JudgeWorker, JudgeWorkerArgs},
+};
+use axum::{
+    body::{Body, Bytes},
+    extract::{multipart::MultipartError, DefaultBodyLimit, Multipart, Query},
+    http::{header, StatusCode},
+};
+use loco_rs::prelude::*;
+use sea_orm::{ConnectionTrait, TransactionTrait};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{find_user_by_auth, find_visible_problem, permission_denied};
+
+#[derive(Debug, Deserialize)]
+pub struct CreateSubmissionRequest {
+    pub problem_id: i32,
+    pub language: Language,
+    /// Source code, not accepted by fill-in-template problems
+    pub code: Option<String>,
+    /// Code of each blank in the template, required by fill-in-template problems
+    pub blanks: Option<Vec<String>>,
+}
+
+/// Get the complete source code of a submission. Returns the error response
+/// if the request does not match the problem type.
+async fn source_code(
+    ctx: &AppContext,
+    problem: &problems::Model,
+    params: CreateSubmissionRequest,
+) -> Result<String, Result<Response>> {
+    let bad_request = |msg: String| {
+        format::render()
+            .status(StatusCode::BAD_REQUEST)
+            .json(json!({ "msg": msg }))
+    };
+
+    match (problem.problem_type(), params.code, params.blanks) {
+        (Type::FillInTemplate, None, Some(blanks)) => {
+            let template = match templates::Model::find_by_language(
+                &ctx.db,
+                problem.id,
+                params.language,
+            )
+            .await
+            {
+                Ok(t) => t,
+                Err(ModelError::EntityNotFound) => {
+                    return Err(bad_request(
+                        "The problem has no template in this language".into(),
+                    ))
+                }
+                Err(e) => return Err(Err(e.into())),
+            };
+            templates::fill(&template.content, &blanks)
+                .map_err(|e| bad_request(format!("Invalid blanks: {e}")))
+        }
+        (Type::FillInTemplate, _, _) => Err(bad_request(
+            "Only blanks of the template are expected".into(),
+        )),
+        (Type::Handwritten, _, _) => Err(bad_request(
+            "Handwritten submissions should be uploaded as files".into(),
+        )),
+        (_, Some(code), None) => Ok(code),
+        _ => Err(bad_request("Source code is expected".into())),
+    }
+}
+
+/// Responds 403 if the user has used all quotas of the problem. Lock the quota
+/// with [`submissions::Model::lock_quota`] first to hold it until the submission
+/// is created in the same transaction.
+async fn verify_quota<C: ConnectionTrait>(
+    db: &C,
+    problem: &problems::Model,
+    user: &users::Model,
+) -> Result<(), Result<Response>> {
+    match submissions::Model::remaining_quota(db, problem, user).await {
+        Ok(0) => Err(format::render()
+            .status(StatusCode::FORBIDDEN)
+            .json(json!({"msg": "You have used all your quotas"}))),
+        Ok(_) => Ok(()),
+        Err(e) => Err(Err(e.into())),
+    }
+}
+
+/// Students cannot submit to a homework problem outside the homework's window,
+/// see [`homeworks::Model::accepts_submission`].
+async fn verify_homework_open(
+    ctx: &AppContext,
+    problem: &problems::Model,
+    user: &users::Model,
+) -> Result<(), Result<Response>> {
+    match homeworks::Model::accepts_submission(&ctx.db, problem, user, models::now()).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(format::render()
+            .status(StatusCode::FORBIDDEN)
+            .json(json!({"msg": "Homework is not open for submission"}))),
+        Err(e) => Err(Err(e.into())),
+    }
+}
+
+async fn create(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Json(params): Json<CreateSubmissionRequest>,
+) -> Result<Response> {
+    let user = match find_user_by_auth(&ctx, &auth).await {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+    let problem = match find_visible_problem(&ctx, &user, params.problem_id).await {
+        Ok(p) => p,
+        Err(e) => return e,
+    };
+    if problem.test_case_stale {
+        return format::render()
+            .status(StatusCode::FORBIDDEN)
+            .json(json!({"msg": "Test case of the problem is outdated"}));
+    }
+    // do not use the quota on submissions which can only end as judge error
+    if let Some(msg) = Settings::from_context(&ctx)?
+        .sandbox
+        .kind
+        .unsupported(&problem)
+    {
+        return format::render()
+            .status(StatusCode::FORBIDDEN)
+            .json(json!({ "msg": msg }));
+    }
+    if !problem.allowed_languages().contains(params.language) {
+        return format::render()
+            .status(StatusCode::FORBIDDEN)
+            .json(json!({"msg": "Language is not allowed"}));
+    }
+    if let Err(e) = verify_quota(&ctx.db, &problem, &user).await {
+        return e;
+    }
+    if let Err(e) = verify_homework_open(&ctx, &problem, &user).await {
+        return e;
+    }
+
+    let language = params.language;
+    let code = match source_code(&ctx, &problem, params).await {
+        Ok(code) => code,
+        Err(e) => return e,
+    };
+    // check again because concurrent submissions may have used the quota
+    let txn = ctx.db.begin().await?;
+    submissions::Model::lock_quota(&txn, &user).await?;
+    if let Err(e) = verify_quota(&txn, &problem, &user).await {
+        return e;
+    }
+    let code_id = uuid::Uuid::new_v4().to_string();
+    let submission = submissions::Model::add(
+        &txn,
+        &submissions::AddParams {
+            user: user.clone(),
+            problem,
+            language,
+            code_id: code_id.clone(),
+        },
+    )
+    .await?;
+    ctx.storage
+        .as_ref()
+        .upload(
+            submissions::code_path(&code_id).as_path(),
+            &Bytes::from(code),
+        )
+        .await?;
+    txn.commit().await?;
+    tracing::info!(submission_id = submission.id, "submission created");
+
+    JudgeWorker::perform_later(
+        &ctx,
+        JudgeWorkerArgs {
+            submission_id: submission.id,
+        },
+    )
+    .await
+    .map_err(Box::from)?;
+    let submission = submissions::Model::find_by_id(&ctx.db, submission.id).await?;
+
+    format::json(SubmissionDetailResponse::new(&submission, &user, None, true).done())
+}
+
+/// Upload a PDF or image as a handwritten submission, it is graded manually by
+/// [`grade`] instead of being judged.
+async fn create_handwritten(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path(problem_id): Path<i32>,
+    mut multipart: Multipart,
+) -> Result<Response> {
+    let user = match find_user_by_auth(&ctx, &auth).await {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+    let problem = match find_visible_problem(&ctx, &user, problem_id).await {
+        Ok(p) => p,
+        Err(e) => return e,
+    };
+    if problem.problem_type() != Type::Handwritten {
+        return format::render()
+            .status(StatusCode::BAD_REQUEST)
+            .json(json!({"msg": "The problem does not accept handwritten submissions"}));
+    }
+    if let Err(e) = verify_quota(&ctx.db, &problem, &user).await {
+        return e;
+    }
+    if let Err(e) = verify_homework_open(&ctx, &problem, &user).await {
+        return e;
+    }
+
+    let multipart_error = |err: MultipartError| {
+        tracing::error!(error = ?err, "could not read multipart");
+        Error::BadRequest("could not read multipart".into())
+    };
+    let mut file = None;
+    while let Some(field) = multipart.next_field().await.map_err(multipart_error)? {
+        let Some(content_type) = field.content_type().and_then(|t| {
+            submissions::HANDWRITTEN_CONTENT_TYPES
+                .into_iter()
+                .find(|c| *c == t)
+        }) else {
+            continue;
+        };
+        file = Some((content_type, field.bytes().await.map_err(multipart_error)?));
+        break;
+    }
+    let Some((content_type, content)) = file else {
+        return format::render()
+            .status(StatusCode::BAD_REQUEST)
+            .json(json!({
+                "msg": format!(
+                    "File not found, expected a multipart field of type {}",
+                    submissions::HANDWRITTEN_CONTENT_TYPES.join(", ")
+                )
+            }));
+    };
+
+    // check again because concurrent submissions may have used the quota
+    let txn = ctx.db.begin().await?;
+    submissions::Model::lock_quota(&txn, &user).await?;
+    if let Err(e) = verify_quota(&txn, &problem, &user).await {
+        return e;
+    }
+    let code_id = uuid::Uuid::new_v4().to_string();
+    let submission = submissions::Model::add_handwritten(
+        &txn,
+        &submissions::AddHandwrittenParams {
+            user: user.clone(),
+            problem,
+            code_id: code_id.clone(),
+            content_type: content_type.to_string(),
+        },
+    )
+    .await?;
+    ctx.storage
+        .as_ref()
+        .upload(submissions::code_path(&code_id).as_path(), &content)
+        .await?;
+    txn.commit().await?;
+    tracing::info!(
+        submission_id = submission.id,
+        "handwritten submission created"
+    );
+
+    format::json(SubmissionDetailResponse::new(&submission, &user, None, true).done())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GradeSubmissionRequest {
+    pub score: i32,
+    pub comment: Option<String>,
+}
+
+async fn grade(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path(submission_id): Path<i32>,
+    Json(params): Json<GradeSubmissionRequest>,
+) -> Result<Response> {
+    let user = match find_user_by_auth(&ctx, &auth).await {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+    let submission = submissions::Model::find_by_id(&ctx.db, submission_id).await?;
+    if !submission.is_gradable_by(&ctx.db, &user).await? {
+        return permission_denied();
+    }
+    if !submission.is_handwritten() {
+        return format::render()
+            .status(StatusCode::BAD_REQUEST)
+            .json(json!({"msg": "Only handwritten submissions can be graded"}));
+    }
+    if !(0..=submissions::MAX_GRADE).contains(&params.score) {
+        return format::render().status(StatusCode::BAD_REQUEST).json(
+            json!({"msg": format!("Score should be between 0 and {}", submissions::MAX_GRADE)}),
+        );
+    }
+
+    let submitter = submission
+        .find_related(users::Entity)
+        .one(&ctx.db)
+        .await?
+        .ok_or(ModelError::EntityNotFound)?;
+    let submission = submission
+        .into_active_model()
+        .grade(&ctx.db, params.score, params.comment)
+        .await?;
+    tracing::info!(submission_id, grader_id = user.id, "submission graded");
+
+    format::json(SubmissionDetailResponse::new(&submission, &submitter, None, true).done())
+}
+
+/// Download the uploaded file of a handwritten submission.
+async fn download_file(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path(submission_id): Path<i32>,
+) -> Result<Response> {
+    let viewer = match find_user_by_auth(&ctx, &auth).await {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+    let submission = submissions::Model::find_by_id(&ctx.db, submission_id).await?;
+    let Some(content_type) = &submission.content_type else {
+        return format::render()
+            .status(StatusCode::NOT_FOUND)
+            .json(json!({"msg": "Submission has no uploaded file"}));
+    };
+    if !submission.is_code_visible_to(&ctx.db, &viewer).await?
+        && !submission.is_gradable_by(&ctx.db, &viewer).await?
+    {
+        return permission_denied();
+    }
+
+    let content: Vec<u8> = ctx
+        .storage
+        .as_ref()
+        .download(submissions::code_path(&submission.code_id).as_path())
+        .await?;
+
+    Ok(format::render()
+        .header(header::CONTENT_TYPE, content_type.as_str())
+        .response()
+        .body(Body::from(content))?)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListSubmissionRequest {
+    pub offset: Option<u64>,
+    pub count: Option<u64>,
+    /// submitter's username
+    pub user: Option<String>,
+    pub problem_id: Option<i32>,
+    pub status: Option<i8>,
+    pub language: Option<i8>,
+    pub course: Option<String>,
+}
+
+async fn list(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Query(params): Query<ListSubmissionRequest>,
+) -> Result<Response> {
+    use num_traits::FromPrimitive;
+
+    let user = users::Model::find_by_claims_key(&ctx.db, &auth.claims.pid).await?;
+
+    let status = params
+        .status
+        .map(|s| Status::from_i8(s).ok_or_else(|| Error::BadRequest("invalid status".into())))
+        .transpose()?;
+    let language = params
+        .language
+        .map(|l| Language::from_i8(l).ok_or_else(|| Error::BadRequest("invalid language".into())))
+        .transpose()?;
+
+    let params = submissions::ListParams {
+        viewer: user,
+        offset: params.offset,
+        count: params.count,
+        username: params.user,
+        problem_id: params.problem_id,
+        status,
+        language,
+        course: params.course,
+    };
+    let submissions = submissions::Model::list(&ctx.db, &params).await?;
+
+    format::json(SubmissionListResponse::new(&submissions).done())
+}
+
+async fn get_one(
+    State(ctx): State<AppContext>,
+    auth: auth::JWT,
+    Path(submission_id): Path<i32>,
+) -> Result<Response> {
+    let viewer = users::Model::find_by_claims_key(&ctx.db, &auth.claims.pid).await?;
+    let submission = submissions::Model::find_by_id(&ctx.db, submission_id).await?;
+    if submission.is_hidden_by_contest(&ctx.db, &viewer).await? {
+        return permission_denied();
+    }
+    // graders can read submissions even if the problem is hidden from them
+    if submission.user_id != viewer.id && !submission.is_gradable_by(&ctx.db, &viewer).await? {
+        if let Err(e) = find_visible_problem(&ctx, &viewer, submission.problem_id).await {
+            return e;
+        }
+    }
+    let submitter = submission
+        .find_related(users::Entity)
+        .one(&ctx.db)
+        .await?
+        .ok_or(ModelError::EntityNotFound)?;
+
+    let code_visible = submission.is_code_visible_to(&ctx.db, &viewer).await?;
+    // the uploaded file of handwritten submissions is read by `download_file`
+    let code = if !submission.is_handwritten() && code_visible {
+        let code: String = ctx
+            .storage
+            .as_ref()
+            .download(submissions::code_path(&submission.code_id).as_path())
+            .await?;
+        Some(code)
+    } else {
+        None
+    };
+
+    format::json(SubmissionDetailResponse::new(&submission, &submitter, code, code_visible).done())
+}
+
+/// Callback of remote sandbox, see [`crate::judge::remote`].
+async fn complete(
+    State(ctx): State<AppContext>,
+    Path(submission_id): Path<i32>,
+    Json(params): Json<CompleteRequest>,
+) -> Result<Response> {
+    let settings = Settings::from_context(&ctx)?;
+    if !settings.sandbox.verify_token(&params.token) {
+        return permission_denied();
+    }
+
+    let submission = submissions::Model::find_by_id(&ctx.db, submission_id).await?;
+    if submission.status != Status::Pending as i32 {
+        return format::render()
+            .status(StatusCode::CONFLICT)
+            .json(json!({"msg": "Submission is not pending"}));
+    }
+
+    let submission = submission
+        .into_active_model()
+        .update_result(&ctx.db, &params.task_results())
+        .await?;
+    tracing::info!(
+        submission_id = submission.id,
+        status = submission.status,
+        "submission judged"
+    );
+
+    format::empty_json()
+}
+
+pub fn routes() -> Routes {
+    Routes::new()
+        .prefix("submissions")
+        .add("/", post(create))
+        .add("/", get(list))
+        .add("/:submission_id", get(get_one))
+        .add("/:submission_id/file", get(download_file))
+        .add("/:submission_id/grade", put(grade))
+        .add(
+            "/handwritten/:problem_id",
+            // change body limit to 16 MB
+            post(create_handwritten).layer(DefaultBodyLimit::max(16 * 1024 * 1024)),
+        )
+        .add("/:submission_id/complete", put(complete))
+}
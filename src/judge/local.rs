@@ -0,0 +1,701 @@
+//! Judge submissions by compiling and running them as local processes, see [`LocalSandbox`].
+//!
+//! Each test case is executed with `RLIMIT_CPU` and `RLIMIT_AS` derived from the
+//! task's `time_limit` (ms) and `memory_limit` (KB), plus a wall clock watchdog in
+//! case the program is blocked without consuming CPU time. The address space limit
+//! is twice the memory limit because runtimes map much more memory than they touch,
+//! so MLE is decided by the peak resident set size. A single allocation larger than
+//! the address space limit fails inside the program and is usually reported as RE.
+//!
+//! If the problem has a custom checker, it is compiled like a submission and run
+//! for every case as `checker <input> <expected output> <contestant output>`
+//! under the same limits as compilation. Exiting with 0 means accepted, and 1
+//! means wrong answer, in which case the checker may print a percentage (0-100)
+//! of partial credit to stdout. Any other outcome is reported as judge error.
+//!
+//! Interactive problems ship an interactor inside the test case instead. It runs
+//! alongside the submission as `interactor <input> <result>`, with its stdout
+//! piped to the submission's stdin and vice versa. The exit code is interpreted
+//! like a checker's, and partial credit is written to the `<result>` file. A
+//! rejection from the interactor takes precedence over runtime errors of the
+//! submission.
+//!
+//! Note that this provides no isolation between the submission and the host.
+
+use std::{
+    fs,
+    io::{self, Read},
+    mem,
+    os::unix::process::{CommandExt, ExitStatusExt},
+    path::Path,
+    process::{Command, ExitStatus, Stdio},
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use loco_rs::prelude::{AppContext, Result as AppResult};
+
+use super::{source_name, Sandbox, TaskSpec};
+use crate::models::{
+    problems::{self, Type},
+    submissions::{self, CaseResult, Status, TaskResult},
+    Language,
+};
+
+/// Max size of stdout/stderr a program can write.
+const OUTPUT_LIMIT: u64 = 64 << 20;
+/// Max length of stderr or compiler message kept in judge result.
+const MESSAGE_LIMIT: u64 = 4096;
+const COMPILE_TIME_LIMIT: Duration = Duration::from_secs(10);
+const CHECKER_TIME_LIMIT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+    #[error("error reading test case: {0}")]
+    Zip(#[from] zip::result::ZipError),
+    #[error("invalid path found in test case: {0}")]
+    InvalidPath(String),
+    #[error("could not compile checker: {0}")]
+    CheckerCompileError(String),
+    #[error("interactor not found in test case")]
+    InteractorNotFound,
+    #[error("could not compile interactor: {0}")]
+    InteractorCompileError(String),
+}
+
+/// Source code of a custom checker
+#[derive(Debug)]
+pub struct Checker<'a> {
+    pub language: Language,
+    pub code: &'a [u8],
+}
+
+#[derive(Debug)]
+pub struct JudgeRequest<'a> {
+    pub language: Language,
+    pub code: &'a [u8],
+    /// Test case zip, see [`crate::models::problems::Model::validate_test_case`]
+    /// for its layout.
+    pub test_case: &'a [u8],
+    pub tasks: &'a [TaskSpec],
+    /// Custom checker, outputs are compared line by line if not set.
+    pub checker: Option<Checker<'a>>,
+    /// Run the submission against the interactor in test case, the checker is
+    /// ignored in this case.
+    pub interactive: bool,
+}
+
+/// How the output of a case is judged
+#[derive(Clone, Copy, Debug)]
+enum OutputJudge<'a> {
+    /// Compare with expected output by [`is_output_matched`]
+    Compare,
+    /// Compiled custom checker in the directory
+    Checker(Language, &'a Path),
+    /// Compiled interactor in the directory
+    Interactor(Language, &'a Path),
+}
+
+/// Compile the submission and run it against every test case. This function
+/// blocks until all cases are finished.
+///
+/// # Errors
+///
+/// When the judge could not prepare the workspace or the test case is broken.
+/// Errors caused by the submission are reported as its judge result instead.
+pub fn judge(req: &JudgeRequest<'_>) -> Result<Vec<TaskResult>, Error> {
+    let work_dir = tempfile::tempdir()?;
+    let src_dir = work_dir.path().join("src");
+    let data_dir = work_dir.path().join("data");
+    fs::create_dir(&src_dir)?;
+    fs::create_dir(&data_dir)?;
+
+    fs::write(src_dir.join(source_name(req.language)), req.code)?;
+    if let Err(message) = compile(req.language, &src_dir)? {
+        return Ok(compile_error(req.tasks, &message));
+    }
+    extract_test_case(req.test_case, &data_dir)?;
+
+    let checker_dir = work_dir.path().join("checker");
+    let interactor_dir = work_dir.path().join("interactor");
+    let output_judge = if req.interactive {
+        let (name, language) = problems::test_case::INTERACTOR_FILES
+            .iter()
+            .find(|(f, _)| data_dir.join(f).is_file())
+            .ok_or(Error::InteractorNotFound)?;
+        let code = fs::read(data_dir.join(name))?;
+        build(*language, &code, &interactor_dir)?.map_err(Error::InteractorCompileError)?;
+        OutputJudge::Interactor(*language, &interactor_dir)
+    } else if let Some(checker) = &req.checker {
+        build(checker.language, checker.code, &checker_dir)?.map_err(Error::CheckerCompileError)?;
+        OutputJudge::Checker(checker.language, &checker_dir)
+    } else {
+        OutputJudge::Compare
+    };
+
+    req.tasks
+        .iter()
+        .enumerate()
+        .map(|(i, task)| {
+            let cases = (0..task.test_case_count)
+                .map(|j| {
+                    let case_dir = data_dir.join("test-case").join(format!("{i:02}{j:02}"));
+                    run_case(req.language, &src_dir, &case_dir, task, output_judge)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(TaskResult::from_cases(cases))
+        })
+        .collect()
+}
+
+/// Judge submissions on this machine with [`judge`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LocalSandbox;
+
+#[async_trait]
+impl Sandbox for LocalSandbox {
+    async fn judge(
+        &self,
+        ctx: &AppContext,
+        submission: &submissions::Model,
+    ) -> AppResult<Option<Vec<TaskResult>>> {
+        use loco_rs::Error;
+
+        let problem = problems::Model::find_by_id(&ctx.db, submission.problem_id).await?;
+        let Some(test_case_id) = &problem.test_case_id else {
+            return Err(Error::string("test case of the problem is not uploaded"));
+        };
+        let tasks = problem
+            .tasks(&ctx.db)
+            .await?
+            .iter()
+            .map(TaskSpec::from)
+            .collect::<Vec<_>>();
+        let language = submission
+            .language()
+            .ok_or_else(|| Error::string("invalid submission language"))?;
+
+        let storage = ctx.storage.as_ref();
+        let code: Vec<u8> = storage
+            .download(submissions::code_path(&submission.code_id).as_path())
+            .await?;
+        let test_case: Vec<u8> = storage
+            .download(problems::test_case_path(test_case_id).as_path())
+            .await?;
+        let interactive = problem.problem_type() == Type::Interactive;
+        let checker = match (&problem.checker_id, problem.checker_language()) {
+            (Some(checker_id), Some(language)) => {
+                let code: Vec<u8> = storage
+                    .download(problems::checker_path(checker_id).as_path())
+                    .await?;
+                Some((language, code))
+            }
+            _ => None,
+        };
+
+        let results = tokio::task::spawn_blocking(move || {
+            judge(&JudgeRequest {
+                language,
+                code: &code,
+                test_case: &test_case,
+                tasks: &tasks,
+                checker: checker.as_ref().map(|(language, code)| Checker {
+                    language: *language,
+                    code,
+                }),
+                interactive,
+            })
+        })
+        .await
+        .map_err(|e| Error::Any(e.into()))?
+        .map_err(|e| Error::Any(e.into()))?;
+
+        Ok(Some(results))
+    }
+}
+
+fn compile_command(language: Language) -> Command {
+    let mut cmd;
+    match language {
+        Language::C => {
+            cmd = Command::new("gcc");
+            cmd.args(["-DONLINE_JUDGE", "-O2", "-w", "-std=c11", "main.c", "-lm"]);
+            cmd.args(["-o", "main"]);
+        }
+        Language::Cpp => {
+            cmd = Command::new("g++");
+            cmd.args(["-DONLINE_JUDGE", "-O2", "-w", "-std=c++17", "main.cpp"]);
+            cmd.args(["-o", "main"]);
+        }
+        Language::Python => {
+            cmd = Command::new("python3");
+            cmd.args(["-m", "py_compile", "main.py"]);
+        }
+    }
+    cmd
+}
+
+fn run_command(language: Language) -> Command {
+    match language {
+        Language::C | Language::Cpp => Command::new("./main"),
+        Language::Python => {
+            let mut cmd = Command::new("python3");
+            cmd.arg("main.py");
+            cmd
+        }
+    }
+}
+
+/// Returns compiler message if the compilation failed.
+fn compile(language: Language, src_dir: &Path) -> io::Result<Result<(), String>> {
+    let stderr_path = src_dir.join("compile.stderr");
+    let mut cmd = compile_command(language);
+    cmd.current_dir(src_dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(fs::File::create(&stderr_path)?);
+
+    let usage = execute(
+        cmd,
+        &Limits {
+            cpu_time: COMPILE_TIME_LIMIT,
+            wall_time: COMPILE_TIME_LIMIT * 2,
+            memory: None,
+        },
+    )?;
+
+    if usage.killed || !usage.status.success() {
+        let mut message = read_truncated(&stderr_path)?;
+        if usage.killed {
+            message.push_str("\ncompilation time limit exceeded");
+        }
+        return Ok(Err(message));
+    }
+
+    Ok(Ok(()))
+}
+
+/// Write the source code of a checker or interactor into `dir` and compile it,
+/// returns compiler message if the compilation failed.
+fn build(language: Language, code: &[u8], dir: &Path) -> io::Result<Result<(), String>> {
+    fs::create_dir(dir)?;
+    fs::write(dir.join(source_name(language)), code)?;
+    compile(language, dir)
+}
+
+fn compile_error(tasks: &[TaskSpec], message: &str) -> Vec<TaskResult> {
+    tasks
+        .iter()
+        .map(|t| {
+            let cases = (0..t.test_case_count)
+                .map(|_| CaseResult {
+                    status: Status::CompileError,
+                    exec_time: -1,
+                    memory_usage: -1,
+                    stderr: message.to_string(),
+                    partial_score: None,
+                })
+                .collect();
+            TaskResult::from_cases(cases)
+        })
+        .collect()
+}
+
+fn extract_test_case(test_case: &[u8], dest: &Path) -> Result<(), Error> {
+    let mut zipfile = zip::ZipArchive::new(io::Cursor::new(test_case))?;
+    for i in 0..zipfile.len() {
+        let mut file = zipfile.by_index(i)?;
+        if file.is_symlink() {
+            return Err(Error::InvalidPath(file.name().to_string()));
+        }
+        let name = file
+            .enclosed_name()
+            .ok_or_else(|| Error::InvalidPath(file.name().to_string()))?;
+        let path = dest.join(name);
+        if file.is_dir() {
+            fs::create_dir_all(&path)?;
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        io::copy(&mut file, &mut fs::File::create(&path)?)?;
+    }
+    Ok(())
+}
+
+fn run_case(
+    language: Language,
+    src_dir: &Path,
+    case_dir: &Path,
+    task: &TaskSpec,
+    output_judge: OutputJudge<'_>,
+) -> Result<CaseResult, Error> {
+    let output_dir = tempfile::tempdir()?;
+    let stdout_path = output_dir.path().join("stdout");
+    let stderr_path = output_dir.path().join("stderr");
+    let result_path = output_dir.path().join("result");
+
+    let mut cmd = run_command(language);
+    cmd.current_dir(src_dir)
+        .stderr(fs::File::create(&stderr_path)?);
+
+    let time_limit = Duration::from_millis(u64::try_from(task.time_limit).unwrap_or(0));
+    let memory_limit = u64::try_from(task.memory_limit).unwrap_or(0);
+    let limits = Limits {
+        cpu_time: time_limit,
+        wall_time: time_limit * 2 + Duration::from_secs(1),
+        memory: Some(memory_limit),
+    };
+    let (usage, interactor_usage) =
+        if let OutputJudge::Interactor(interactor_language, interactor_dir) = output_judge {
+            let (program_stdin, interactor_stdout) = io::pipe()?;
+            let (interactor_stdin, program_stdout) = io::pipe()?;
+            cmd.stdin(program_stdin).stdout(program_stdout);
+            let mut interactor = run_command(interactor_language);
+            interactor
+                .current_dir(interactor_dir)
+                .arg(case_dir.join("STDIN"))
+                .arg(&result_path)
+                .stdin(interactor_stdin)
+                .stdout(interactor_stdout)
+                .stderr(Stdio::null());
+            let interactor_limits = Limits {
+                cpu_time: CHECKER_TIME_LIMIT,
+                wall_time: limits.wall_time + CHECKER_TIME_LIMIT,
+                memory: None,
+            };
+
+            // each process is traced by the thread which spawns it
+            let (usage, interactor_usage) = thread::scope(|s| {
+                let interactor = s.spawn(move || execute(interactor, &interactor_limits));
+                let usage = execute(cmd, &limits);
+                (usage, interactor.join())
+            });
+            let interactor_usage =
+                interactor_usage.map_err(|_| io::Error::other("interactor thread panicked"))??;
+            (usage?, Some(interactor_usage))
+        } else {
+            cmd.stdin(fs::File::open(case_dir.join("STDIN"))?)
+                .stdout(fs::File::create(&stdout_path)?);
+            (execute(cmd, &limits)?, None)
+        };
+
+    let exec_time = i32::try_from(usage.cpu_time.as_millis()).unwrap_or(i32::MAX);
+    let memory_usage = usage
+        .max_rss
+        .map_or(-1, |m| i32::try_from(m).unwrap_or(i32::MAX));
+    let signal = usage.status.signal();
+    let interactor_verdict = match &interactor_usage {
+        // the submission exits before the interaction is finished
+        Some(u) if u.status.signal() == Some(libc::SIGPIPE) => Some((Status::WrongAnswer, None)),
+        Some(u) => Some(verdict(u, &result_path)?),
+        None => None,
+    };
+    let (status, partial_score) =
+        if usage.killed || signal == Some(libc::SIGXCPU) || exec_time > task.time_limit {
+            (Status::TimeLimitExceed, None)
+        } else if memory_usage > task.memory_limit {
+            (Status::MemoryLimitExceed, None)
+        } else if signal == Some(libc::SIGXFSZ) {
+            (Status::OutputLimitExceed, None)
+        } else if let Some(rejected) = interactor_verdict.filter(|(s, _)| *s != Status::Accepted) {
+            // the submission usually fails by writing to the closed pipe after
+            // the interactor gives up, which should not hide the verdict
+            rejected
+        } else if !usage.status.success() {
+            (Status::RuntimeError, None)
+        } else if let Some(accepted) = interactor_verdict {
+            accepted
+        } else if let OutputJudge::Checker(checker_language, checker_dir) = output_judge {
+            run_checker(checker_language, checker_dir, case_dir, &stdout_path)?
+        } else if is_output_matched(
+            &fs::read(case_dir.join("STDOUT"))?,
+            &fs::read(&stdout_path)?,
+        ) {
+            (Status::Accepted, None)
+        } else {
+            (Status::WrongAnswer, None)
+        };
+
+    Ok(CaseResult {
+        status,
+        exec_time,
+        memory_usage,
+        stderr: read_truncated(&stderr_path)?,
+        partial_score,
+    })
+}
+
+/// Judge the output of a case by the compiled custom checker, returns the status
+/// with optional partial credit of a wrong answer.
+fn run_checker(
+    language: Language,
+    checker_dir: &Path,
+    case_dir: &Path,
+    output_path: &Path,
+) -> io::Result<(Status, Option<i32>)> {
+    let output_dir = tempfile::tempdir()?;
+    let stdout_path = output_dir.path().join("stdout");
+
+    let mut cmd = run_command(language);
+    cmd.current_dir(checker_dir)
+        .arg(case_dir.join("STDIN"))
+        .arg(case_dir.join("STDOUT"))
+        .arg(output_path)
+        .stdin(Stdio::null())
+        .stdout(fs::File::create(&stdout_path)?)
+        .stderr(Stdio::null());
+
+    let usage = execute(
+        cmd,
+        &Limits {
+            cpu_time: CHECKER_TIME_LIMIT,
+            wall_time: CHECKER_TIME_LIMIT * 2,
+            memory: None,
+        },
+    )?;
+    verdict(&usage, &stdout_path)
+}
+
+/// Interpret the exit code of a checker or interactor: 0 is accepted, 1 is wrong
+/// answer with optional partial credit written in `score_path`, and anything else
+/// is judge error.
+fn verdict(usage: &Usage, score_path: &Path) -> io::Result<(Status, Option<i32>)> {
+    if usage.killed {
+        return Ok((Status::JudgeError, None));
+    }
+
+    let verdict = match usage.status.code() {
+        Some(0) => (Status::Accepted, None),
+        Some(1) => {
+            // no partial credit is given if the file is not written
+            let partial_score = if score_path.exists() {
+                read_truncated(score_path)?
+                    .split_whitespace()
+                    .next()
+                    .and_then(|s| s.parse::<i32>().ok())
+                    .filter(|s| (0..=100).contains(s))
+            } else {
+                None
+            };
+            (Status::WrongAnswer, partial_score)
+        }
+        _ => (Status::JudgeError, None),
+    };
+    Ok(verdict)
+}
+
+/// Compare outputs line by line, trailing whitespaces and trailing empty lines are ignored.
+fn is_output_matched(expected: &[u8], actual: &[u8]) -> bool {
+    fn normalize(output: &[u8]) -> Vec<&[u8]> {
+        let mut lines = output
+            .split(|c| *c == b'\n')
+            .map(<[u8]>::trim_ascii_end)
+            .collect::<Vec<_>>();
+        while lines.last().is_some_and(|l| l.is_empty()) {
+            lines.pop();
+        }
+        lines
+    }
+
+    normalize(expected) == normalize(actual)
+}
+
+fn read_truncated(path: &Path) -> io::Result<String> {
+    let mut buf = vec![];
+    fs::File::open(path)?
+        .take(MESSAGE_LIMIT)
+        .read_to_end(&mut buf)?;
+    Ok(String::from_utf8_lossy(&buf).into_owned())
+}
+
+struct Limits {
+    cpu_time: Duration,
+    wall_time: Duration,
+    /// Memory limit in KB, the address space limit is twice of it
+    memory: Option<u64>,
+}
+
+struct Usage {
+    status: ExitStatus,
+    /// Whether the process is killed by wall clock watchdog
+    killed: bool,
+    cpu_time: Duration,
+    /// Peak resident set size in KB, `None` if the process was killed before we
+    /// could read it.
+    max_rss: Option<i64>,
+}
+
+fn set_rlimit(resource: libc::__rlimit_resource_t, limit: u64) -> io::Result<()> {
+    let rlim = libc::rlimit {
+        rlim_cur: limit,
+        rlim_max: limit,
+    };
+    // SAFETY: `setrlimit` only reads the given struct.
+    if unsafe { libc::setrlimit(resource, &raw const rlim) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Read `VmHWM` (peak RSS in KB) of a living process
+fn read_peak_rss(pid: libc::pid_t) -> Option<i64> {
+    fs::read_to_string(format!("/proc/{pid}/status"))
+        .ok()?
+        .lines()
+        .find_map(|l| l.strip_prefix("VmHWM:"))?
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Spawn the command with rlimits and wait for it, returning its resource usage.
+///
+/// The child is traced so that it stops right before exiting, which is the only
+/// moment we can read its peak RSS. `ru_maxrss` is not usable here because it also
+/// counts the pages copied from this (large) process when forking.
+fn execute(mut cmd: Command, limits: &Limits) -> io::Result<Usage> {
+    // the process is killed by SIGXCPU after the soft limit, so round up to next second
+    let cpu_secs = limits.cpu_time.as_secs() + 1;
+    let memory = limits
+        .memory
+        .map_or(libc::RLIM_INFINITY, |m| m.saturating_mul(2 * 1024));
+    // SAFETY: the closure only calls async-signal-safe `setrlimit` and `ptrace`
+    unsafe {
+        cmd.pre_exec(move || {
+            set_rlimit(libc::RLIMIT_CPU, cpu_secs)?;
+            set_rlimit(libc::RLIMIT_AS, memory)?;
+            set_rlimit(libc::RLIMIT_FSIZE, OUTPUT_LIMIT)?;
+            set_rlimit(libc::RLIMIT_CORE, 0)?;
+            if libc::ptrace(libc::PTRACE_TRACEME, 0, 0, 0) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+    // put the process in a new group, so that its children can be killed together
+    cmd.process_group(0);
+
+    // the tracer is the thread which forks the child, so everything below
+    // must stay in this thread
+    let child = cmd.spawn()?;
+    // close stdio of the child held by the command, otherwise pipes connected to
+    // another process never reach EOF
+    drop(cmd);
+    let pid = libc::pid_t::try_from(child.id()).map_err(io::Error::other)?;
+
+    let (done_tx, done_rx) = mpsc::channel::<()>();
+    let wall_time = limits.wall_time;
+    let watchdog = thread::spawn(move || {
+        if done_rx.recv_timeout(wall_time) == Err(mpsc::RecvTimeoutError::Timeout) {
+            // SAFETY: the process is not reaped yet, so the pid is not recycled
+            unsafe { libc::kill(-pid, libc::SIGKILL) };
+            return true;
+        }
+        false
+    });
+
+    let max_rss = trace(pid);
+    if max_rss.is_err() {
+        // SAFETY: the process is not reaped yet
+        unsafe { libc::kill(-pid, libc::SIGKILL) };
+    }
+    let _ = done_tx.send(());
+    let killed = watchdog.join().unwrap_or(false);
+
+    let mut status = 0;
+    // SAFETY: `rusage` is a plain C struct which can be zeroed
+    let mut rusage: libc::rusage = unsafe { mem::zeroed() };
+    // SAFETY: both pointers are valid
+    if unsafe { libc::wait4(pid, &raw mut status, 0, &raw mut rusage) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let max_rss = max_rss?;
+
+    let to_duration = |t: libc::timeval| {
+        Duration::from_secs(u64::try_from(t.tv_sec).unwrap_or(0))
+            + Duration::from_micros(u64::try_from(t.tv_usec).unwrap_or(0))
+    };
+
+    Ok(Usage {
+        status: ExitStatus::from_raw(status),
+        killed,
+        cpu_time: to_duration(rusage.ru_utime) + to_duration(rusage.ru_stime),
+        max_rss,
+    })
+}
+
+/// Resume the traced child until it terminates, without reaping it because the
+/// watchdog may still need its pid. Returns the peak RSS read at its exit stop.
+fn trace(pid: libc::pid_t) -> io::Result<Option<i64>> {
+    let mut options_set = false;
+    let mut max_rss = None;
+
+    loop {
+        // SAFETY: `siginfo_t` is a plain C struct which can be zeroed
+        let mut info: libc::siginfo_t = unsafe { mem::zeroed() };
+        // SAFETY: `info` is a valid pointer
+        let ret = unsafe {
+            libc::waitid(
+                libc::P_PID,
+                pid.unsigned_abs(),
+                &raw mut info,
+                libc::WEXITED | libc::WNOWAIT,
+            )
+        };
+        if ret != 0 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Err(err);
+        }
+        if matches!(
+            info.si_code,
+            libc::CLD_EXITED | libc::CLD_KILLED | libc::CLD_DUMPED
+        ) {
+            return Ok(max_rss);
+        }
+
+        // consume the ptrace stop
+        let mut status = 0;
+        // SAFETY: `status` is a valid pointer
+        if unsafe { libc::waitpid(pid, &raw mut status, 0) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        if !libc::WIFSTOPPED(status) {
+            continue;
+        }
+
+        let signal = libc::WSTOPSIG(status);
+        let event = status >> 16;
+        let inject = match (signal, event) {
+            // the first stop after `execve`
+            (libc::SIGTRAP, 0) if !options_set => {
+                let options =
+                    libc::PTRACE_O_TRACEEXIT | libc::PTRACE_O_TRACEEXEC | libc::PTRACE_O_EXITKILL;
+                // SAFETY: the child is in ptrace stop
+                unsafe { libc::ptrace(libc::PTRACE_SETOPTIONS, pid, 0, options) };
+                options_set = true;
+                0
+            }
+            (libc::SIGTRAP, libc::PTRACE_EVENT_EXIT) => {
+                max_rss = read_peak_rss(pid);
+                0
+            }
+            (libc::SIGTRAP, libc::PTRACE_EVENT_EXEC) => 0,
+            // forward other signals to the child
+            (signal, _) => signal,
+        };
+        // SAFETY: the child is in ptrace stop
+        unsafe { libc::ptrace(libc::PTRACE_CONT, pid, 0, inject) };
+    }
+}
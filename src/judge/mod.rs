@@ -0,0 +1,181 @@
+//! Sandbox backends used to judge submissions.
+//!
+//! The judge worker picks a [`Sandbox`] according to `settings.sandbox` in the
+//! app config. [`local::LocalSandbox`] runs submissions on this machine and
+//! returns the result directly, while [`remote::RemoteSandbox`] sends them to a
+//! Normal-OJ sandbox which reports the result later through
+//! `PUT /api/submissions/:id/complete`.
+
+pub mod local;
+pub mod remote;
+
+use async_trait::async_trait;
+use loco_rs::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    models::{
+        problems::{self, tasks},
+        submissions::{self, TaskResult},
+        Language,
+    },
+    settings::Settings,
+};
+
+#[async_trait]
+pub trait Sandbox: Send + Sync {
+    /// Judge a pending submission. Returns `None` if the result will be
+    /// reported asynchronously by the sandbox.
+    ///
+    /// # Errors
+    ///
+    /// When the submission could not be judged, it should be marked as judge error.
+    async fn judge(
+        &self,
+        ctx: &AppContext,
+        submission: &submissions::Model,
+    ) -> Result<Option<Vec<TaskResult>>>;
+}
+
+/// Kind of sandbox, [`SandboxKind::Remote`] is used unless `local` is
+/// configured explicitly.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SandboxKind {
+    /// Only for development, submissions run without isolation from the host
+    Local,
+    #[default]
+    Remote,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct SandboxConfig {
+    pub kind: SandboxKind,
+    /// Base url of the remote sandbox, e.g. `http://sandbox:1450`
+    pub url: Option<String>,
+    /// Secret shared with the remote sandbox, used to authenticate both sides.
+    pub token: String,
+}
+
+impl SandboxConfig {
+    /// Check the token sent by a sandbox, always fails if no token is configured.
+    #[must_use]
+    pub fn verify_token(&self, token: &str) -> bool {
+        // compare every byte to avoid leaking the length of matched prefix
+        !self.token.is_empty()
+            && self.token.len() == token.len()
+            && self
+                .token
+                .bytes()
+                .zip(token.bytes())
+                .fold(0, |acc, (a, b)| acc | (a ^ b))
+                == 0
+    }
+}
+
+impl SandboxKind {
+    /// Why submissions to the problem cannot be judged by this kind of sandbox,
+    /// `None` if they can. The remote sandbox runs neither custom checkers nor
+    /// interactors.
+    #[must_use]
+    pub fn unsupported(self, problem: &problems::Model) -> Option<&'static str> {
+        if self == Self::Local {
+            return None;
+        }
+        if problem.checker_id.is_some() {
+            return Some("Custom checker is not supported by the sandbox");
+        }
+        if problem.problem_type() == problems::Type::Interactive {
+            return Some("Interactive problem is not supported by the sandbox");
+        }
+        None
+    }
+}
+
+/// Build the sandbox configured in app settings.
+///
+/// # Errors
+///
+/// When the settings are invalid.
+pub fn from_context(ctx: &AppContext) -> Result<Box<dyn Sandbox>> {
+    let config = Settings::from_context(ctx)?.sandbox;
+    let sandbox: Box<dyn Sandbox> = match config.kind {
+        SandboxKind::Local => Box::new(local::LocalSandbox),
+        SandboxKind::Remote => {
+            let url = config
+                .url
+                .ok_or_else(|| Error::string("url of remote sandbox is not configured"))?;
+            Box::new(remote::RemoteSandbox::new(&url, config.token))
+        }
+    };
+    Ok(sandbox)
+}
+
+/// Resource limits and case count of a problem task
+#[derive(Clone, Copy, Debug)]
+pub struct TaskSpec {
+    pub test_case_count: i32,
+    /// CPU time limit in ms
+    pub time_limit: i32,
+    /// Memory limit in KB
+    pub memory_limit: i32,
+}
+
+impl From<&tasks::Model> for TaskSpec {
+    fn from(task: &tasks::Model) -> Self {
+        Self {
+            test_case_count: task.test_case_count,
+            time_limit: task.time_limit,
+            memory_limit: task.memory_limit,
+        }
+    }
+}
+
+/// File name of the source code expected by both local and remote sandboxes.
+const fn source_name(language: Language) -> &'static str {
+    match language {
+        Language::C => "main.c",
+        Language::Cpp => "main.cpp",
+        Language::Python => "main.py",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problem(checker_id: Option<&str>, r#type: problems::Type) -> problems::Model {
+        let now = chrono::Local::now().naive_local();
+        problems::Model {
+            created_at: now,
+            updated_at: now,
+            id: 1,
+            name: "problem".to_string(),
+            owner_id: 1,
+            r#type: r#type as i32,
+            status: problems::Visibility::Show as i32,
+            description_id: 1,
+            allowed_language: 0,
+            quota: -1,
+            test_case_id: None,
+            test_case_stale: false,
+            archived_at: None,
+            checker_id: checker_id.map(str::to_string),
+            checker_language: None,
+        }
+    }
+
+    #[test]
+    fn test_unsupported_problems() {
+        let normal = problem(None, problems::Type::Normal);
+        let with_checker = problem(Some("checker"), problems::Type::Normal);
+        let interactive = problem(None, problems::Type::Interactive);
+        for p in [&normal, &with_checker, &interactive] {
+            assert_eq!(SandboxKind::Local.unsupported(p), None);
+        }
+        assert_eq!(SandboxKind::Remote.unsupported(&normal), None);
+        assert!(SandboxKind::Remote.unsupported(&with_checker).is_some());
+        assert!(SandboxKind::Remote.unsupported(&interactive).is_some());
+    }
+}
@@ -0,0 +1,190 @@
+//! Client of the legacy Normal-OJ sandbox.
+//!
+//! A submission is sent to `POST {url}/submit/{submission_id}` as a multipart form
+//! containing the zipped source code, problem id, language and the shared token.
+//! The sandbox fetches test cases of the problem by itself from
+//! `GET /api/problems/:id/testdata?token=...`, and reports the result by
+//! `PUT /api/submissions/:id/complete` with a [`CompleteRequest`] body.
+
+use std::io::{self, Write};
+
+use async_trait::async_trait;
+use loco_rs::prelude::*;
+use serde::Deserialize;
+
+use super::{source_name, Sandbox};
+use crate::models::{
+    problems,
+    submissions::{self, CaseResult, Status, TaskResult},
+    Language,
+};
+
+/// Custom checker is not supported by the sandbox yet, but the field is required.
+/// Checkers cannot be uploaded, and submissions to problems with a checker or an
+/// interactor are refused when this sandbox is configured, see
+/// [`super::SandboxKind::unsupported`].
+const CHECKER: &str = "print('not implement yet. qaq')";
+
+pub struct RemoteSandbox {
+    client: reqwest::Client,
+    url: String,
+    token: String,
+}
+
+impl RemoteSandbox {
+    #[must_use]
+    pub fn new(url: &str, token: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.trim_end_matches('/').to_string(),
+            token,
+        }
+    }
+}
+
+#[async_trait]
+impl Sandbox for RemoteSandbox {
+    async fn judge(
+        &self,
+        ctx: &AppContext,
+        submission: &submissions::Model,
+    ) -> Result<Option<Vec<TaskResult>>> {
+        let problem = problems::Model::find_by_id(&ctx.db, submission.problem_id).await?;
+        if problem.checker_id.is_some() {
+            return Err(Error::string(
+                "custom checker is not supported by the remote sandbox",
+            ));
+        }
+        if problem.problem_type() == problems::Type::Interactive {
+            return Err(Error::string(
+                "interactive problem is not supported by the remote sandbox",
+            ));
+        }
+        let language = submission
+            .language()
+            .ok_or_else(|| Error::string("invalid submission language"))?;
+        let code: Vec<u8> = ctx
+            .storage
+            .as_ref()
+            .download(submissions::code_path(&submission.code_id).as_path())
+            .await?;
+        let src = zip_source(language, &code).map_err(|e| Error::Any(e.into()))?;
+
+        let form = reqwest::multipart::Form::new()
+            .text("token", self.token.clone())
+            .text("problem_id", submission.problem_id.to_string())
+            .text("language", (language as i32).to_string())
+            .text("checker", CHECKER)
+            .part(
+                "src",
+                reqwest::multipart::Part::bytes(src).file_name(format!("{}.zip", submission.id)),
+            );
+        let resp = self
+            .client
+            .post(format!("{}/submit/{}", self.url, submission.id))
+            .multipart(form)
+            .send()
+            .await
+            .map_err(|e| Error::Any(e.into()))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            return Err(Error::string(&format!(
+                "sandbox rejected submission: {status} {body}"
+            )));
+        }
+
+        Ok(None)
+    }
+}
+
+fn zip_source(language: Language, code: &[u8]) -> io::Result<Vec<u8>> {
+    let mut zip = zip::ZipWriter::new(io::Cursor::new(Vec::new()));
+    zip.start_file(
+        source_name(language),
+        zip::write::SimpleFileOptions::default(),
+    )?;
+    zip.write_all(code)?;
+    Ok(zip.finish()?.into_inner())
+}
+
+/// Status string used by the sandbox
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub enum SandboxStatus {
+    #[serde(rename = "AC")]
+    Accepted,
+    #[serde(rename = "WA")]
+    WrongAnswer,
+    #[serde(rename = "CE")]
+    CompileError,
+    #[serde(rename = "TLE")]
+    TimeLimitExceed,
+    #[serde(rename = "MLE")]
+    MemoryLimitExceed,
+    #[serde(rename = "RE")]
+    RuntimeError,
+    #[serde(rename = "JE")]
+    JudgeError,
+    #[serde(rename = "OLE")]
+    OutputLimitExceed,
+}
+
+impl From<SandboxStatus> for Status {
+    fn from(status: SandboxStatus) -> Self {
+        match status {
+            SandboxStatus::Accepted => Self::Accepted,
+            SandboxStatus::WrongAnswer => Self::WrongAnswer,
+            SandboxStatus::CompileError => Self::CompileError,
+            SandboxStatus::TimeLimitExceed => Self::TimeLimitExceed,
+            SandboxStatus::MemoryLimitExceed => Self::MemoryLimitExceed,
+            SandboxStatus::RuntimeError => Self::RuntimeError,
+            SandboxStatus::JudgeError => Self::JudgeError,
+            SandboxStatus::OutputLimitExceed => Self::OutputLimitExceed,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SandboxCaseResult {
+    pub status: SandboxStatus,
+    pub exit_code: i32,
+    #[serde(default)]
+    pub stdout: String,
+    #[serde(default)]
+    pub stderr: String,
+    /// CPU time in ms
+    pub exec_time: i32,
+    /// Memory usage in KB
+    pub memory_usage: i32,
+}
+
+/// Result reported by the sandbox, grouped by task.
+#[derive(Debug, Deserialize)]
+pub struct CompleteRequest {
+    pub tasks: Vec<Vec<SandboxCaseResult>>,
+    pub token: String,
+}
+
+impl CompleteRequest {
+    #[must_use]
+    pub fn task_results(self) -> Vec<TaskResult> {
+        self.tasks
+            .into_iter()
+            .map(|cases| {
+                let cases = cases
+                    .into_iter()
+                    .map(|c| CaseResult {
+                        status: c.status.into(),
+                        exec_time: c.exec_time,
+                        memory_usage: c.memory_usage,
+                        stderr: c.stderr,
+                        partial_score: None,
+                    })
+                    .collect();
+                TaskResult::from_cases(cases)
+            })
+            .collect()
+    }
+}
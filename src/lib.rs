@@ -1,7 +1,10 @@
 pub mod app;
 pub mod controllers;
+pub mod judge;
 pub mod mailers;
 pub mod models;
+pub mod settings;
+pub mod storage;
 pub mod tasks;
 pub mod views;
 pub mod workers;
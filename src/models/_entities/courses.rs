@@ -17,6 +17,12 @@ pub struct Model {
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::course_members::Entity")]
+    CourseMembers,
+    #[sea_orm(has_many = "super::homeworks::Entity")]
+    Homeworks,
+    #[sea_orm(has_many = "super::problem_courses::Entity")]
+    ProblemCourses,
     #[sea_orm(
         belongs_to = "super::users::Entity",
         from = "Column::TeacherId",
@@ -27,8 +33,35 @@ pub enum Relation {
     Users,
 }
 
+impl Related<super::course_members::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::CourseMembers.def()
+    }
+}
+
+impl Related<super::homeworks::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Homeworks.def()
+    }
+}
+
+impl Related<super::problem_courses::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProblemCourses.def()
+    }
+}
+
 impl Related<super::users::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Users.def()
     }
 }
+
+impl Related<super::problems::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::problem_courses::Relation::Problems.def()
+    }
+    fn via() -> Option<RelationDef> {
+        Some(super::problem_courses::Relation::Courses.def().rev())
+    }
+}
@@ -2,10 +2,22 @@
 
 pub mod prelude;
 
+pub mod contest_participants;
+pub mod contest_problems;
+pub mod contest_scores;
+pub mod contests;
+pub mod course_members;
 pub mod courses;
+pub mod homework_problems;
+pub mod homeworks;
 pub mod notes;
+pub mod problem_courses;
 pub mod problem_descriptions;
+pub mod problem_tags;
 pub mod problem_tasks;
+pub mod problem_templates;
+pub mod problem_test_case_versions;
 pub mod problems;
 pub mod sea_orm_active_enums;
+pub mod submissions;
 pub mod users;
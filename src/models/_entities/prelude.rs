@@ -1,8 +1,20 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.15
 
+pub use super::contest_participants::Entity as ContestParticipants;
+pub use super::contest_problems::Entity as ContestProblems;
+pub use super::contest_scores::Entity as ContestScores;
+pub use super::contests::Entity as Contests;
+pub use super::course_members::Entity as CourseMembers;
 pub use super::courses::Entity as Courses;
+pub use super::homework_problems::Entity as HomeworkProblems;
+pub use super::homeworks::Entity as Homeworks;
 pub use super::notes::Entity as Notes;
+pub use super::problem_courses::Entity as ProblemCourses;
 pub use super::problem_descriptions::Entity as ProblemDescriptions;
+pub use super::problem_tags::Entity as ProblemTags;
 pub use super::problem_tasks::Entity as ProblemTasks;
+pub use super::problem_templates::Entity as ProblemTemplates;
+pub use super::problem_test_case_versions::Entity as ProblemTestCaseVersions;
 pub use super::problems::Entity as Problems;
+pub use super::submissions::Entity as Submissions;
 pub use super::users::Entity as Users;
@@ -19,10 +19,22 @@ pub struct Model {
     pub allowed_language: i32,
     pub quota: i32,
     pub test_case_id: Option<String>,
+    pub test_case_stale: bool,
+    pub archived_at: Option<DateTime>,
+    pub checker_id: Option<String>,
+    pub checker_language: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::contest_problems::Entity")]
+    ContestProblems,
+    #[sea_orm(has_many = "super::contest_scores::Entity")]
+    ContestScores,
+    #[sea_orm(has_many = "super::homework_problems::Entity")]
+    HomeworkProblems,
+    #[sea_orm(has_many = "super::problem_courses::Entity")]
+    ProblemCourses,
     #[sea_orm(
         belongs_to = "super::problem_descriptions::Entity",
         from = "Column::DescriptionId",
@@ -31,8 +43,16 @@ pub enum Relation {
         on_delete = "NoAction"
     )]
     ProblemDescriptions,
+    #[sea_orm(has_many = "super::problem_tags::Entity")]
+    ProblemTags,
     #[sea_orm(has_many = "super::problem_tasks::Entity")]
     ProblemTasks,
+    #[sea_orm(has_many = "super::problem_templates::Entity")]
+    ProblemTemplates,
+    #[sea_orm(has_many = "super::problem_test_case_versions::Entity")]
+    ProblemTestCaseVersions,
+    #[sea_orm(has_many = "super::submissions::Entity")]
+    Submissions,
     #[sea_orm(
         belongs_to = "super::users::Entity",
         from = "Column::OwnerId",
@@ -43,20 +63,77 @@ pub enum Relation {
     Users,
 }
 
+impl Related<super::contest_problems::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ContestProblems.def()
+    }
+}
+
+impl Related<super::contest_scores::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ContestScores.def()
+    }
+}
+
+impl Related<super::homework_problems::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::HomeworkProblems.def()
+    }
+}
+
+impl Related<super::problem_courses::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProblemCourses.def()
+    }
+}
+
 impl Related<super::problem_descriptions::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::ProblemDescriptions.def()
     }
 }
 
+impl Related<super::problem_tags::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProblemTags.def()
+    }
+}
+
 impl Related<super::problem_tasks::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::ProblemTasks.def()
     }
 }
 
+impl Related<super::problem_templates::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProblemTemplates.def()
+    }
+}
+
+impl Related<super::problem_test_case_versions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProblemTestCaseVersions.def()
+    }
+}
+
+impl Related<super::submissions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Submissions.def()
+    }
+}
+
 impl Related<super::users::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Users.def()
     }
 }
+
+impl Related<super::courses::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::problem_courses::Relation::Courses.def()
+    }
+    fn via() -> Option<RelationDef> {
+        Some(super::problem_courses::Relation::Problems.def().rev())
+    }
+}
@@ -13,3 +13,15 @@ pub enum Role {
     #[sea_orm(string_value = "teacher")]
     Teacher,
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "course_role")]
+#[serde(rename_all = "lowercase")]
+pub enum CourseRole {
+    #[sea_orm(string_value = "student")]
+    Student,
+    #[sea_orm(string_value = "ta")]
+    Ta,
+    #[sea_orm(string_value = "teacher")]
+    Teacher,
+}
@@ -31,10 +31,46 @@ pub struct Model {
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::contest_participants::Entity")]
+    ContestParticipants,
+    #[sea_orm(has_many = "super::contest_scores::Entity")]
+    ContestScores,
+    #[sea_orm(has_many = "super::contests::Entity")]
+    Contests,
+    #[sea_orm(has_many = "super::course_members::Entity")]
+    CourseMembers,
     #[sea_orm(has_many = "super::courses::Entity")]
     Courses,
+    #[sea_orm(has_many = "super::problem_test_case_versions::Entity")]
+    ProblemTestCaseVersions,
     #[sea_orm(has_many = "super::problems::Entity")]
     Problems,
+    #[sea_orm(has_many = "super::submissions::Entity")]
+    Submissions,
+}
+
+impl Related<super::contest_participants::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ContestParticipants.def()
+    }
+}
+
+impl Related<super::contest_scores::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ContestScores.def()
+    }
+}
+
+impl Related<super::contests::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Contests.def()
+    }
+}
+
+impl Related<super::course_members::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::CourseMembers.def()
+    }
 }
 
 impl Related<super::courses::Entity> for Entity {
@@ -43,8 +79,20 @@ impl Related<super::courses::Entity> for Entity {
     }
 }
 
+impl Related<super::problem_test_case_versions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProblemTestCaseVersions.def()
+    }
+}
+
 impl Related<super::problems::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Problems.def()
     }
 }
+
+impl Related<super::submissions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Submissions.def()
+    }
+}
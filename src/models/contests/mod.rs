@@ -0,0 +1,395 @@
+pub mod participants;
+pub mod problems;
+pub mod scores;
+
+use loco_rs::model::{ModelError, ModelResult};
+use num_derive::FromPrimitive;
+use sea_orm::{
+    entity::prelude::*, sea_query::Query, ActiveValue, Condition, IntoActiveModel, Order,
+    QueryOrder, QuerySelect, TransactionTrait,
+};
+use serde::Deserialize;
+use serde_repr::{Deserialize_repr, Serialize_repr};
+
+use super::_entities::{self, submissions, users};
+use crate::models::transform_db_error;
+pub use _entities::contests::{ActiveModel, Column, Entity, Model};
+
+/// Max length of contest name, limited by the DB column
+const NAME_MAX_LENGTH: usize = 64;
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("contest name should be 1 to 64 characters")]
+    InvalidName,
+    #[error("contest should end after it starts")]
+    InvalidDuration,
+    #[error("scoreboard freeze should not be longer than the contest")]
+    InvalidFreeze,
+    #[error("problem {0} does not exist")]
+    ProblemNotFound(i32),
+    #[error("problem {0} is not visible to the contest owner")]
+    ProblemNotVisible(i32),
+}
+
+/// How participants are ranked on the scoreboard
+#[derive(Clone, Copy, Debug, Serialize_repr, Deserialize_repr, PartialEq, Eq, FromPrimitive)]
+#[repr(i8)]
+pub enum Rule {
+    /// Rank by solved count, then penalty time. Each solved problem costs the
+    /// minutes from the contest start plus [`scores::PENALTY_PER_ATTEMPT`] for
+    /// each rejected submission before it.
+    Icpc = 0,
+    /// Rank by the sum of the best score of each task of each problem
+    Ioi = 1,
+}
+
+/// Content of a contest, used to create or replace it.
+#[derive(Debug, Deserialize)]
+pub struct Params {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub rule: Rule,
+    pub start_at: DateTime,
+    pub end_at: DateTime,
+    /// The scoreboard stops updating for other participants in the last
+    /// minutes of the contest
+    #[serde(default)]
+    pub freeze_minutes: i32,
+    /// Problems in display order
+    #[serde(default)]
+    pub problem_ids: Vec<i32>,
+}
+
+impl Params {
+    /// Check the content, problems should be visible to or managed by the owner.
+    async fn validate<C: ConnectionTrait>(&self, db: &C, owner: &users::Model) -> ModelResult<()> {
+        if self.name.is_empty() || self.name.chars().count() > NAME_MAX_LENGTH {
+            return Err(ModelError::Any(Error::InvalidName.into()));
+        }
+        if self.end_at <= self.start_at {
+            return Err(ModelError::Any(Error::InvalidDuration.into()));
+        }
+        let duration = (self.end_at - self.start_at).num_minutes();
+        if self.freeze_minutes < 0 || i64::from(self.freeze_minutes) > duration {
+            return Err(ModelError::Any(Error::InvalidFreeze.into()));
+        }
+
+        let existing: Vec<i32> = _entities::problems::Entity::find()
+            .select_only()
+            .column(_entities::problems::Column::Id)
+            .filter(_entities::problems::Column::Id.is_in(self.problem_ids.iter().copied()))
+            .into_tuple()
+            .all(db)
+            .await?;
+        if let Some(id) = self.problem_ids.iter().find(|id| !existing.contains(id)) {
+            return Err(ModelError::Any(Error::ProblemNotFound(*id).into()));
+        }
+        // owners can still use their archived problems
+        let accessible: Vec<i32> = _entities::problems::Entity::find()
+            .select_only()
+            .column(_entities::problems::Column::Id)
+            .filter(_entities::problems::Column::Id.is_in(self.problem_ids.iter().copied()))
+            .filter(
+                Condition::any()
+                    .add(super::problems::Model::visible_to(owner))
+                    .add(_entities::problems::Column::OwnerId.eq(owner.id)),
+            )
+            .into_tuple()
+            .all(db)
+            .await?;
+        if let Some(id) = self.problem_ids.iter().find(|id| !accessible.contains(id)) {
+            return Err(ModelError::Any(Error::ProblemNotVisible(*id).into()));
+        }
+
+        Ok(())
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {
+    // extend activemodel below (keep comment for generators)
+}
+
+impl Model {
+    /// Create a contest owned by the user.
+    ///
+    /// # Errors
+    ///
+    /// - When the params are invalid, or the name is already used
+    /// - When has DB query error
+    pub async fn add<C: ConnectionTrait + TransactionTrait>(
+        db: &C,
+        owner: &users::Model,
+        params: &Params,
+    ) -> ModelResult<Self> {
+        params.validate(db, owner).await?;
+
+        let txn = db.begin().await?;
+        let contest = ActiveModel {
+            owner_id: ActiveValue::set(owner.id),
+            ..Default::default()
+        }
+        .fill(params)
+        .insert(&txn)
+        .await
+        .map_err(transform_db_error)?;
+        problems::Model::set_for_contest(&txn, contest.id, &params.problem_ids).await?;
+        txn.commit().await?;
+
+        Ok(contest)
+    }
+
+    /// Replace content and problems of the contest by the user. The scoreboard
+    /// is rebuilt because the time window or problems may be changed.
+    ///
+    /// # Errors
+    ///
+    /// - When the params are invalid, or the name is already used
+    /// - When has DB query error
+    pub async fn edit<C: ConnectionTrait + TransactionTrait>(
+        self,
+        db: &C,
+        user: &users::Model,
+        params: &Params,
+    ) -> ModelResult<Self> {
+        params.validate(db, user).await?;
+
+        let txn = db.begin().await?;
+        let contest = self
+            .into_active_model()
+            .fill(params)
+            .update(&txn)
+            .await
+            .map_err(transform_db_error)?;
+        problems::Model::set_for_contest(&txn, contest.id, &params.problem_ids).await?;
+        contest.rebuild_scores(&txn, None).await?;
+        txn.commit().await?;
+
+        Ok(contest)
+    }
+
+    /// List contests the viewer manages or participates in, the latest one
+    /// comes first. Admins can see all contests.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error.
+    pub async fn list<C: ConnectionTrait>(db: &C, viewer: &users::Model) -> ModelResult<Vec<Self>> {
+        use super::users::Role;
+
+        let mut query = Entity::find();
+        if viewer.role != Role::Admin {
+            query = query.filter(
+                Condition::any().add(Column::OwnerId.eq(viewer.id)).add(
+                    Column::Id.in_subquery(
+                        Query::select()
+                            .column(participants::Column::ContestId)
+                            .from(participants::Entity)
+                            .and_where(participants::Column::UserId.eq(viewer.id))
+                            .to_owned(),
+                    ),
+                ),
+            );
+        }
+        let contests = query
+            .order_by(Column::StartAt, Order::Desc)
+            .order_by(Column::Id, Order::Desc)
+            .all(db)
+            .await?;
+
+        Ok(contests)
+    }
+
+    /// Find a contest by its primary id
+    ///
+    /// # Errors
+    ///
+    /// - When could not query the contest from DB
+    /// - When the contest does not exist
+    pub async fn find_by_id<C: ConnectionTrait>(db: &C, id: i32) -> ModelResult<Self> {
+        Entity::find_by_id(id)
+            .one(db)
+            .await
+            .map_err(transform_db_error)?
+            .ok_or(ModelError::EntityNotFound)
+    }
+
+    /// Ids of problems in this contest
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error.
+    pub async fn problem_ids<C: ConnectionTrait>(&self, db: &C) -> ModelResult<Vec<i32>> {
+        problems::Model::problem_ids(db, self.id).await
+    }
+
+    /// Ranking rule, unknown values are treated as [`Rule::Icpc`].
+    #[must_use]
+    pub fn rule(&self) -> Rule {
+        use num_traits::FromPrimitive;
+
+        Rule::from_i32(self.rule).unwrap_or(Rule::Icpc)
+    }
+
+    /// Whether the user can manage this contest, only the owner and admins are allowed.
+    #[must_use]
+    pub fn is_managed_by(&self, user: &users::Model) -> bool {
+        use super::users::Role;
+
+        user.role == Role::Admin || self.owner_id == user.id
+    }
+
+    /// Time when the scoreboard is frozen, which is the end of contest if there
+    /// is no freeze.
+    #[must_use]
+    pub fn freeze_at(&self) -> DateTime {
+        self.end_at - chrono::Duration::minutes(self.freeze_minutes.into())
+    }
+
+    /// Whether the scoreboard is frozen at the time. The freeze is lifted once
+    /// the contest ends.
+    #[must_use]
+    pub fn is_frozen_at(&self, time: DateTime) -> bool {
+        self.freeze_at() <= time && time < self.end_at
+    }
+
+    /// Register users to the contest, submissions they have made during the
+    /// contest are counted.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error.
+    pub async fn add_participants<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        user_ids: &[i32],
+    ) -> ModelResult<()> {
+        for &user_id in user_ids {
+            participants::Model::add(db, self.id, user_id).await?;
+            self.rebuild_scores(db, Some(user_id)).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Remove the user from the contest along with the user's scores.
+    ///
+    /// # Errors
+    ///
+    /// - When has DB query error
+    /// - When the user is not a participant
+    pub async fn remove_participant<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        user_id: i32,
+    ) -> ModelResult<()> {
+        participants::Model::remove(db, self.id, user_id).await?;
+        scores::Model::clear(db, self.id, Some(user_id)).await
+    }
+
+    /// Recompute cached scores of all participants, or only the given one.
+    async fn rebuild_scores<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        user_id: Option<i32>,
+    ) -> ModelResult<()> {
+        scores::Model::clear(db, self.id, user_id).await?;
+        let user_ids = match user_id {
+            Some(id) => vec![id],
+            None => participants::Model::list(db, self.id)
+                .await?
+                .iter()
+                .map(|u| u.id)
+                .collect(),
+        };
+        for problem_id in self.problem_ids(db).await? {
+            for &user_id in &user_ids {
+                scores::Model::refresh(db, self, user_id, problem_id).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Update scoreboards of contests the submission counts for, should be called
+    /// whenever the submission's result is changed.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error.
+    pub async fn record_submission<C: ConnectionTrait>(
+        db: &C,
+        submission: &submissions::Model,
+    ) -> ModelResult<()> {
+        let contests = Entity::find()
+            .filter(
+                Column::Id.in_subquery(
+                    Query::select()
+                        .column(problems::Column::ContestId)
+                        .from(problems::Entity)
+                        .and_where(problems::Column::ProblemId.eq(submission.problem_id))
+                        .to_owned(),
+                ),
+            )
+            .filter(
+                Column::Id.in_subquery(
+                    Query::select()
+                        .column(participants::Column::ContestId)
+                        .from(participants::Entity)
+                        .and_where(participants::Column::UserId.eq(submission.user_id))
+                        .to_owned(),
+                ),
+            )
+            .filter(Column::StartAt.lte(submission.created_at))
+            .filter(Column::EndAt.gte(submission.created_at))
+            .all(db)
+            .await?;
+        for contest in contests {
+            scores::Model::refresh(db, &contest, submission.user_id, submission.problem_id).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Participants ranked by the contest rule with the problem ids of the
+    /// contest. Submissions during the freeze are not counted if `public` is set.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error.
+    pub async fn scoreboard<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        public: bool,
+    ) -> ModelResult<(Vec<i32>, Vec<(users::Model, scores::Standing)>)> {
+        let problem_ids = self.problem_ids(db).await?;
+        let participants = participants::Model::list(db, self.id).await?;
+        let cells = scores::Model::cells_of(db, self.id, public).await?;
+
+        let user_ids = participants.iter().map(|u| u.id).collect::<Vec<_>>();
+        let mut participants = participants
+            .into_iter()
+            .map(|u| (u.id, u))
+            .collect::<std::collections::HashMap<_, _>>();
+        let standings = scores::rank(self, &user_ids, &problem_ids, &cells)
+            .into_iter()
+            .filter_map(|s| participants.remove(&s.user_id).map(|u| (u, s)))
+            .collect();
+
+        Ok((problem_ids, standings))
+    }
+}
+
+impl ActiveModel {
+    fn fill(mut self, params: &Params) -> Self {
+        self.name = ActiveValue::set(params.name.clone());
+        self.description = ActiveValue::set(params.description.clone());
+        self.rule = ActiveValue::set(params.rule as i32);
+        self.start_at = ActiveValue::set(params.start_at);
+        self.end_at = ActiveValue::set(params.end_at);
+        self.freeze_minutes = ActiveValue::set(params.freeze_minutes);
+        self
+    }
+}
@@ -0,0 +1,98 @@
+use loco_rs::model::{ModelError, ModelResult};
+use sea_orm::{entity::prelude::*, sea_query::OnConflict, ActiveValue, Order, QueryOrder};
+
+pub use super::_entities::contest_participants::{ActiveModel, Column, Entity, Model};
+use super::_entities::users;
+
+impl ActiveModelBehavior for ActiveModel {
+    // extend activemodel below (keep comment for generators)
+}
+
+impl Model {
+    /// Register the user to a contest, nothing changes if already registered.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error.
+    pub async fn add<C: ConnectionTrait>(db: &C, contest_id: i32, user_id: i32) -> ModelResult<()> {
+        Entity::insert(ActiveModel {
+            contest_id: ActiveValue::set(contest_id),
+            user_id: ActiveValue::set(user_id),
+            ..Default::default()
+        })
+        .on_conflict(
+            OnConflict::columns([Column::ContestId, Column::UserId])
+                .do_nothing()
+                .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Remove the user from a contest.
+    ///
+    /// # Errors
+    ///
+    /// - When has DB query error
+    /// - When the user is not a participant of the contest
+    pub async fn remove<C: ConnectionTrait>(
+        db: &C,
+        contest_id: i32,
+        user_id: i32,
+    ) -> ModelResult<()> {
+        let result = Entity::delete_many()
+            .filter(Column::ContestId.eq(contest_id))
+            .filter(Column::UserId.eq(user_id))
+            .exec(db)
+            .await?;
+        if result.rows_affected == 0 {
+            return Err(ModelError::EntityNotFound);
+        }
+
+        Ok(())
+    }
+
+    /// List participants of a contest in the order they registered.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error.
+    pub async fn list<C: ConnectionTrait>(
+        db: &C,
+        contest_id: i32,
+    ) -> ModelResult<Vec<users::Model>> {
+        let participants = Entity::find()
+            .find_also_related(users::Entity)
+            .filter(Column::ContestId.eq(contest_id))
+            .order_by(Column::Id, Order::Asc)
+            .all(db)
+            .await?
+            .into_iter()
+            // user should always exist because of the foreign key
+            .filter_map(|(_, u)| u)
+            .collect();
+
+        Ok(participants)
+    }
+
+    /// Whether the user has registered to the contest.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error.
+    pub async fn exists<C: ConnectionTrait>(
+        db: &C,
+        contest_id: i32,
+        user_id: i32,
+    ) -> ModelResult<bool> {
+        let count = Entity::find()
+            .filter(Column::ContestId.eq(contest_id))
+            .filter(Column::UserId.eq(user_id))
+            .count(db)
+            .await?;
+
+        Ok(count > 0)
+    }
+}
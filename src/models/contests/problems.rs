@@ -0,0 +1,125 @@
+use loco_rs::model::ModelResult;
+use sea_orm::{
+    entity::prelude::*,
+    sea_query::{Query, SelectStatement},
+    ActiveValue, Order, QueryOrder, QuerySelect,
+};
+
+pub use super::_entities::contest_problems::{ActiveModel, Column, Entity, Model};
+use super::_entities::{contest_participants, contests};
+use crate::models::transform_db_error;
+
+/// Sub query selecting ids of contests running at the time.
+fn running_contests(now: DateTime) -> SelectStatement {
+    Query::select()
+        .column(contests::Column::Id)
+        .from(contests::Entity)
+        .and_where(contests::Column::StartAt.lte(now))
+        .and_where(contests::Column::EndAt.gte(now))
+        .to_owned()
+}
+
+/// Sub query selecting ids of problems in contests the user participates in
+/// and running at the time.
+#[must_use]
+pub fn problem_ids_in_running_contests_of(user_id: i32, now: DateTime) -> SelectStatement {
+    let joined_contests = Query::select()
+        .column(contest_participants::Column::ContestId)
+        .from(contest_participants::Entity)
+        .and_where(contest_participants::Column::UserId.eq(user_id))
+        .to_owned();
+
+    Query::select()
+        .column(Column::ProblemId)
+        .from(Entity)
+        .and_where(
+            Column::ContestId.in_subquery(
+                running_contests(now)
+                    .and_where(contests::Column::Id.in_subquery(joined_contests))
+                    .to_owned(),
+            ),
+        )
+        .to_owned()
+}
+
+/// Sub query selecting ids of problems in contests running at the time, except
+/// contests owned by the user.
+#[must_use]
+pub fn problem_ids_in_running_contests_not_owned_by(
+    user_id: i32,
+    now: DateTime,
+) -> SelectStatement {
+    Query::select()
+        .column(Column::ProblemId)
+        .from(Entity)
+        .and_where(
+            Column::ContestId.in_subquery(
+                running_contests(now)
+                    .and_where(contests::Column::OwnerId.ne(user_id))
+                    .to_owned(),
+            ),
+        )
+        .to_owned()
+}
+
+impl ActiveModelBehavior for ActiveModel {
+    // extend activemodel below (keep comment for generators)
+}
+
+impl Model {
+    /// Replace problems of a contest, the order is kept and duplicated problems
+    /// are ignored.
+    ///
+    /// # Errors
+    ///
+    /// When there is DB error.
+    pub async fn set_for_contest<C: ConnectionTrait>(
+        db: &C,
+        contest_id: i32,
+        problem_ids: &[i32],
+    ) -> ModelResult<()> {
+        Entity::delete_many()
+            .filter(Column::ContestId.eq(contest_id))
+            .exec(db)
+            .await?;
+
+        let mut unique_ids: Vec<i32> = Vec::with_capacity(problem_ids.len());
+        for id in problem_ids {
+            if !unique_ids.contains(id) {
+                unique_ids.push(*id);
+            }
+        }
+        if unique_ids.is_empty() {
+            return Ok(());
+        }
+
+        Entity::insert_many(unique_ids.into_iter().map(|problem_id| ActiveModel {
+            contest_id: ActiveValue::set(contest_id),
+            problem_id: ActiveValue::set(problem_id),
+            ..Default::default()
+        }))
+        .exec_without_returning(db)
+        .await
+        .map_err(transform_db_error)?;
+
+        Ok(())
+    }
+
+    /// Ids of problems in a contest, in the order they were assigned.
+    ///
+    /// # Errors
+    ///
+    /// When there is DB error.
+    pub async fn problem_ids<C: ConnectionTrait>(db: &C, contest_id: i32) -> ModelResult<Vec<i32>> {
+        let ids = Entity::find()
+            .select_only()
+            .column(Column::ProblemId)
+            .filter(Column::ContestId.eq(contest_id))
+            .order_by(Column::Id, Order::Asc)
+            .into_tuple()
+            .all(db)
+            .await?;
+
+        Ok(ids)
+    }
+}
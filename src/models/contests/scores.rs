@@ -0,0 +1,418 @@
+//! Cached scoreboard cells of contests.
+//!
+//! Each row keeps the result of a participant to a contest problem. The row is
+//! recomputed from the participant's submissions to that problem whenever one
+//! of them is judged, so building the scoreboard only reads and ranks the rows.
+//! The `public_*` columns only count submissions made before the scoreboard is
+//! frozen, see [`super::Model::freeze_at`].
+
+use std::{cmp::Reverse, collections::HashMap, hash::BuildHasher};
+
+use loco_rs::model::ModelResult;
+use num_traits::FromPrimitive;
+use sea_orm::{entity::prelude::*, sea_query::OnConflict, ActiveValue, Order, QueryOrder};
+
+pub use super::_entities::contest_scores::{ActiveModel, Column, Entity, Model};
+use super::{
+    _entities::{contests, submissions},
+    Rule,
+};
+use crate::models::submissions::Status;
+
+/// Penalty minutes of each rejected attempt before the problem is solved
+pub const PENALTY_PER_ATTEMPT: i64 = 20;
+
+/// Result of a participant to a problem
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Cell {
+    /// Count of rejected submissions, submissions after the first accepted one
+    /// are not counted
+    pub attempts: i32,
+    /// Submission time of the first accepted submission
+    pub accepted_at: Option<DateTime>,
+    /// Sum of the best score of each task among all submissions, so subtasks
+    /// solved by different submissions add up
+    pub score: i32,
+}
+
+impl Cell {
+    /// Count a submission, `task_scores` keeps the best score of each task
+    /// among counted submissions.
+    fn push(&mut self, submission: &submissions::Model, task_scores: &mut Vec<i32>) {
+        // compile errors and judge errors are not the participant's fault
+        let status = match Status::from_i32(submission.status) {
+            Some(Status::Pending | Status::CompileError | Status::JudgeError) | None => return,
+            Some(status) => status,
+        };
+        let tasks = submission.task_results();
+        if task_scores.len() < tasks.len() {
+            task_scores.resize(tasks.len(), 0);
+        }
+        for (best, task) in task_scores.iter_mut().zip(&tasks) {
+            *best = (*best).max(task.score);
+        }
+        // handwritten submissions are graded as a whole without task results
+        self.score = self
+            .score
+            .max(submission.score)
+            .max(task_scores.iter().sum());
+        if self.accepted_at.is_some() {
+            return;
+        }
+        if status == Status::Accepted {
+            self.accepted_at = Some(submission.created_at);
+        } else {
+            self.attempts += 1;
+        }
+    }
+
+    /// Penalty minutes of a solved problem under ICPC rule, `None` if unsolved.
+    #[must_use]
+    pub fn penalty(&self, start_at: DateTime) -> Option<i64> {
+        self.accepted_at
+            .map(|t| (t - start_at).num_minutes() + PENALTY_PER_ATTEMPT * i64::from(self.attempts))
+    }
+
+    const fn from_row(row: &Model, public: bool) -> Self {
+        if public {
+            Self {
+                attempts: row.public_attempts,
+                accepted_at: row.public_accepted_at,
+                score: row.public_score,
+            }
+        } else {
+            Self {
+                attempts: row.attempts,
+                accepted_at: row.accepted_at,
+                score: row.score,
+            }
+        }
+    }
+}
+
+/// Compute the cell of submissions ordered by creation time. Returns the cell
+/// counting all submissions, and the one only counting submissions before
+/// `freeze_at`.
+#[must_use]
+pub fn compute(submissions: &[submissions::Model], freeze_at: DateTime) -> (Cell, Cell) {
+    let mut cell = Cell::default();
+    let mut public = Cell::default();
+    let mut task_scores = vec![];
+    let mut public_task_scores = vec![];
+    for submission in submissions {
+        cell.push(submission, &mut task_scores);
+        if submission.created_at < freeze_at {
+            public.push(submission, &mut public_task_scores);
+        }
+    }
+    (cell, public)
+}
+
+/// Ranking of a participant
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Standing {
+    pub user_id: i32,
+    /// Participants with the same result share the same rank
+    pub rank: usize,
+    pub solved: i32,
+    /// Sum of penalty minutes of solved problems
+    pub penalty: i64,
+    /// Sum of scores of cells
+    pub score: i32,
+    /// Cells in the same order as the contest problems
+    pub cells: Vec<Cell>,
+}
+
+/// Rank participants by the contest rule. ICPC rule ranks by solved count, then
+/// penalty; IOI rule ranks by the sum of scores. Participants with the same
+/// result keep the order of `user_ids`.
+#[must_use]
+pub fn rank<S: BuildHasher>(
+    contest: &contests::Model,
+    user_ids: &[i32],
+    problem_ids: &[i32],
+    cells: &HashMap<(i32, i32), Cell, S>,
+) -> Vec<Standing> {
+    let mut standings = user_ids
+        .iter()
+        .map(|&user_id| {
+            let cells = problem_ids
+                .iter()
+                .map(|&p| cells.get(&(user_id, p)).copied().unwrap_or_default())
+                .collect::<Vec<_>>();
+            Standing {
+                user_id,
+                rank: 0,
+                solved: cells
+                    .iter()
+                    .map(|c| i32::from(c.accepted_at.is_some()))
+                    .sum(),
+                penalty: cells
+                    .iter()
+                    .filter_map(|c| c.penalty(contest.start_at))
+                    .sum(),
+                score: cells.iter().map(|c| c.score).sum(),
+                cells,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let rule = contest.rule();
+    let key = |s: &Standing| match rule {
+        Rule::Icpc => (Reverse(i64::from(s.solved)), s.penalty),
+        Rule::Ioi => (Reverse(i64::from(s.score)), 0),
+    };
+    standings.sort_by_key(key);
+    for i in 0..standings.len() {
+        standings[i].rank = if i > 0 && key(&standings[i]) == key(&standings[i - 1]) {
+            standings[i - 1].rank
+        } else {
+            i + 1
+        };
+    }
+
+    standings
+}
+
+impl ActiveModelBehavior for ActiveModel {
+    // extend activemodel below (keep comment for generators)
+}
+
+impl Model {
+    /// Recompute the cell of a participant to a contest problem from the
+    /// participant's submissions during the contest.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error.
+    pub async fn refresh<C: ConnectionTrait>(
+        db: &C,
+        contest: &contests::Model,
+        user_id: i32,
+        problem_id: i32,
+    ) -> ModelResult<()> {
+        let submissions = submissions::Entity::find()
+            .filter(submissions::Column::UserId.eq(user_id))
+            .filter(submissions::Column::ProblemId.eq(problem_id))
+            .filter(submissions::Column::CreatedAt.between(contest.start_at, contest.end_at))
+            .order_by(submissions::Column::CreatedAt, Order::Asc)
+            .order_by(submissions::Column::Id, Order::Asc)
+            .all(db)
+            .await?;
+        let (cell, public) = compute(&submissions, contest.freeze_at());
+
+        Entity::insert(ActiveModel {
+            contest_id: ActiveValue::set(contest.id),
+            user_id: ActiveValue::set(user_id),
+            problem_id: ActiveValue::set(problem_id),
+            attempts: ActiveValue::set(cell.attempts),
+            accepted_at: ActiveValue::set(cell.accepted_at),
+            score: ActiveValue::set(cell.score),
+            public_attempts: ActiveValue::set(public.attempts),
+            public_accepted_at: ActiveValue::set(public.accepted_at),
+            public_score: ActiveValue::set(public.score),
+            ..Default::default()
+        })
+        .on_conflict(
+            OnConflict::columns([Column::ContestId, Column::UserId, Column::ProblemId])
+                .update_columns([
+                    Column::Attempts,
+                    Column::AcceptedAt,
+                    Column::Score,
+                    Column::PublicAttempts,
+                    Column::PublicAcceptedAt,
+                    Column::PublicScore,
+                ])
+                .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Remove cells of the contest, optionally only those of a participant.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error.
+    pub async fn clear<C: ConnectionTrait>(
+        db: &C,
+        contest_id: i32,
+        user_id: Option<i32>,
+    ) -> ModelResult<()> {
+        let mut query = Entity::delete_many().filter(Column::ContestId.eq(contest_id));
+        if let Some(user_id) = user_id {
+            query = query.filter(Column::UserId.eq(user_id));
+        }
+        query.exec(db).await?;
+
+        Ok(())
+    }
+
+    /// Cached cells of the contest keyed by (user id, problem id). Only
+    /// submissions before the freeze are counted if `public` is set.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error.
+    pub async fn cells_of<C: ConnectionTrait>(
+        db: &C,
+        contest_id: i32,
+        public: bool,
+    ) -> ModelResult<HashMap<(i32, i32), Cell>> {
+        let rows = Entity::find()
+            .filter(Column::ContestId.eq(contest_id))
+            .all(db)
+            .await?;
+
+        Ok(rows
+            .iter()
+            .map(|r| ((r.user_id, r.problem_id), Cell::from_row(r, public)))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::models::submissions::TaskResult;
+
+    fn at(minute: u32) -> DateTime {
+        NaiveDate::from_ymd_opt(2024, 7, 1)
+            .unwrap()
+            .and_hms_opt(8 + minute / 60, minute % 60, 0)
+            .unwrap()
+    }
+
+    fn submission(status: Status, score: i32, minute: u32) -> submissions::Model {
+        submissions::Model {
+            created_at: at(minute),
+            updated_at: at(minute),
+            id: 0,
+            user_id: 1,
+            problem_id: 1,
+            language: Some(0),
+            code_id: String::new(),
+            status: status as i32,
+            score,
+            exec_time: 0,
+            memory_usage: 0,
+            tasks: None,
+            test_case_version_id: None,
+            content_type: None,
+            comment: None,
+        }
+    }
+
+    #[test]
+    fn test_compute_cell() {
+        let submissions = [
+            submission(Status::CompileError, 0, 1),
+            submission(Status::WrongAnswer, 30, 10),
+            submission(Status::Accepted, 100, 50),
+            submission(Status::WrongAnswer, 0, 70),
+        ];
+        let (cell, public) = compute(&submissions, at(40));
+        assert_eq!(
+            cell,
+            Cell {
+                attempts: 1,
+                accepted_at: Some(at(50)),
+                score: 100,
+            }
+        );
+        assert_eq!(cell.penalty(at(0)), Some(70));
+        assert_eq!(
+            public,
+            Cell {
+                attempts: 1,
+                accepted_at: None,
+                score: 30,
+            }
+        );
+        assert_eq!(public.penalty(at(0)), None);
+    }
+
+    #[test]
+    fn test_compute_cell_sums_best_task_scores() {
+        let with_tasks = |status, scores: &[i32], minute| {
+            let mut s = submission(status, scores.iter().sum(), minute);
+            let tasks = scores
+                .iter()
+                .map(|&score| TaskResult {
+                    status: if score > 0 {
+                        Status::Accepted
+                    } else {
+                        Status::WrongAnswer
+                    },
+                    score,
+                    exec_time: 0,
+                    memory_usage: 0,
+                    cases: vec![],
+                })
+                .collect::<Vec<_>>();
+            s.tasks = Some(serde_json::to_value(tasks).unwrap());
+            s
+        };
+        let submissions = [
+            with_tasks(Status::WrongAnswer, &[20, 0, 0], 10),
+            with_tasks(Status::WrongAnswer, &[0, 30, 0], 20),
+            with_tasks(Status::JudgeError, &[0, 0, 50], 30),
+            with_tasks(Status::WrongAnswer, &[10, 0, 0], 40),
+        ];
+        let (cell, public) = compute(&submissions, at(15));
+        assert_eq!(cell.score, 50);
+        assert_eq!(cell.attempts, 3);
+        assert_eq!(public.score, 20);
+    }
+
+    #[test]
+    fn test_rank() {
+        let mut contest = contests::Model {
+            created_at: at(0),
+            updated_at: at(0),
+            id: 1,
+            name: "contest".to_string(),
+            description: String::new(),
+            owner_id: 1,
+            rule: Rule::Icpc as i32,
+            start_at: at(0),
+            end_at: at(300),
+            freeze_minutes: 0,
+        };
+        let solved = |minute, score| Cell {
+            attempts: 0,
+            accepted_at: Some(at(minute)),
+            score,
+        };
+        let failed = |score| Cell {
+            attempts: 1,
+            accepted_at: None,
+            score,
+        };
+        let cells = HashMap::from([
+            ((1, 1), solved(30, 100)),
+            ((1, 2), failed(95)),
+            ((2, 1), solved(10, 100)),
+            ((2, 2), failed(90)),
+            ((3, 1), solved(10, 100)),
+            ((3, 2), failed(90)),
+        ]);
+
+        let ranks = |contest: &contests::Model| {
+            rank(contest, &[1, 2, 3, 4], &[1, 2], &cells)
+                .iter()
+                .map(|s| (s.user_id, s.rank))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ranks(&contest), [(2, 1), (3, 1), (1, 3), (4, 4)]);
+        contest.rule = Rule::Ioi as i32;
+        assert_eq!(ranks(&contest), [(1, 1), (2, 2), (3, 2), (4, 4)]);
+
+        let standings = rank(&contest, &[4], &[1, 2], &cells);
+        assert_eq!(standings[0].cells, [Cell::default(), Cell::default()]);
+    }
+}
@@ -0,0 +1,90 @@
+use loco_rs::model::{ModelError, ModelResult};
+use sea_orm::{entity::prelude::*, sea_query::OnConflict, ActiveValue};
+
+pub use super::_entities::course_members::{ActiveModel, Column, Entity, Model};
+pub use super::_entities::sea_orm_active_enums::CourseRole;
+
+impl ActiveModelBehavior for ActiveModel {
+    // extend activemodel below (keep comment for generators)
+}
+
+impl Model {
+    /// Add the user to a course, or change the user's role if already a member.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error.
+    pub async fn upsert<C: ConnectionTrait>(
+        db: &C,
+        course_id: i32,
+        user_id: i32,
+        role: CourseRole,
+    ) -> ModelResult<()> {
+        Entity::insert(ActiveModel {
+            course_id: ActiveValue::set(course_id),
+            user_id: ActiveValue::set(user_id),
+            role: ActiveValue::set(role),
+            ..Default::default()
+        })
+        .on_conflict(
+            OnConflict::columns([Column::CourseId, Column::UserId])
+                .update_column(Column::Role)
+                .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Add the user to a course as a student, keep the user's role unchanged if
+    /// already a member.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error.
+    pub async fn enroll<C: ConnectionTrait>(
+        db: &C,
+        course_id: i32,
+        user_id: i32,
+    ) -> ModelResult<()> {
+        Entity::insert(ActiveModel {
+            course_id: ActiveValue::set(course_id),
+            user_id: ActiveValue::set(user_id),
+            role: ActiveValue::set(CourseRole::Student),
+            ..Default::default()
+        })
+        .on_conflict(
+            OnConflict::columns([Column::CourseId, Column::UserId])
+                .do_nothing()
+                .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Remove the user from a course.
+    ///
+    /// # Errors
+    ///
+    /// - When the user is not a member of the course
+    /// - When has DB query error
+    pub async fn remove<C: ConnectionTrait>(
+        db: &C,
+        course_id: i32,
+        user_id: i32,
+    ) -> ModelResult<()> {
+        let res = Entity::delete_many()
+            .filter(Column::CourseId.eq(course_id))
+            .filter(Column::UserId.eq(user_id))
+            .exec(db)
+            .await?;
+        if res.rows_affected == 0 {
+            return Err(ModelError::EntityNotFound);
+        }
+
+        Ok(())
+    }
+}
@@ -1,15 +1,135 @@
-use super::_entities::courses;
 pub use super::_entities::courses::{ActiveModel, Entity, Model};
+use super::_entities::{course_members, courses, users};
+use super::{course_members::CourseRole, transform_db_error};
 use loco_rs::model::{ModelError, ModelResult};
-use sea_orm::entity::prelude::*;
+use sea_orm::{
+    entity::prelude::*, sea_query::Query, ActiveValue, Condition, IntoActiveModel, Order,
+    QueryOrder,
+};
+
+/// Max length of course name, limited by the DB column
+const NAME_MAX_LENGTH: usize = 64;
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("course name should be 1 to 64 characters without '/'")]
+    InvalidName,
+    #[error("course teacher should be a teacher or admin")]
+    InvalidTeacher,
+}
+
+#[derive(Debug)]
+pub struct AddParams {
+    pub name: String,
+    pub teacher: users::Model,
+}
+
+#[derive(Debug, Default)]
+pub struct EditParams {
+    pub name: Option<String>,
+    pub teacher: Option<users::Model>,
+}
+
+fn validate_name(name: &str) -> ModelResult<()> {
+    if name.is_empty() || name.chars().count() > NAME_MAX_LENGTH || name.contains('/') {
+        return Err(ModelError::Any(Error::InvalidName.into()));
+    }
+    Ok(())
+}
+
+fn validate_teacher(teacher: &users::Model) -> ModelResult<()> {
+    use super::users::Role;
+
+    if !matches!(teacher.role, Role::Teacher | Role::Admin) {
+        return Err(ModelError::Any(Error::InvalidTeacher.into()));
+    }
+    Ok(())
+}
 
 impl super::_entities::courses::Model {
+    /// Create a course
+    ///
+    /// # Errors
+    ///
+    /// - When the name is invalid or already used
+    /// - When the teacher is not a teacher or admin
+    /// - When has DB query error
+    pub async fn add<C: ConnectionTrait>(db: &C, params: &AddParams) -> ModelResult<Self> {
+        validate_name(&params.name)?;
+        validate_teacher(&params.teacher)?;
+
+        let course = ActiveModel {
+            name: ActiveValue::set(params.name.clone()),
+            teacher_id: ActiveValue::set(params.teacher.id),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .map_err(transform_db_error)?;
+
+        Ok(course)
+    }
+
+    /// Rename the course or change its teacher
+    ///
+    /// # Errors
+    ///
+    /// - When the new name is invalid or already used
+    /// - When the new teacher is not a teacher or admin
+    /// - When has DB query error
+    pub async fn edit<C: ConnectionTrait>(self, db: &C, params: &EditParams) -> ModelResult<Self> {
+        let mut course = self.into_active_model();
+        if let Some(name) = &params.name {
+            validate_name(name)?;
+            course.name = ActiveValue::set(name.clone());
+        }
+        if let Some(teacher) = &params.teacher {
+            validate_teacher(teacher)?;
+            course.teacher_id = ActiveValue::set(teacher.id);
+        }
+
+        course.update(db).await.map_err(transform_db_error)
+    }
+
+    /// List courses visible to the user. Admins can see all courses, others can
+    /// only see courses they are members of.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error.
+    pub async fn list_visible_to<C: ConnectionTrait>(
+        db: &C,
+        user: &users::Model,
+    ) -> ModelResult<Vec<Self>> {
+        use super::users::Role;
+
+        let mut q = courses::Entity::find().order_by(courses::Column::Id, Order::Asc);
+        if user.role != Role::Admin {
+            q = q.filter(
+                Condition::any()
+                    .add(courses::Column::TeacherId.eq(user.id))
+                    .add(
+                        courses::Column::Id.in_subquery(
+                            Query::select()
+                                .column(course_members::Column::CourseId)
+                                .from(course_members::Entity)
+                                .and_where(course_members::Column::UserId.eq(user.id))
+                                .to_owned(),
+                        ),
+                    ),
+            );
+        }
+
+        Ok(q.all(db).await?)
+    }
+
     /// finds a course by the provided name
     ///
     /// # Errors
     ///
     /// When could not find user by the given name or DB query error
-    pub async fn find_by_name(db: &DatabaseConnection, name: &str) -> ModelResult<Self> {
+    pub async fn find_by_name<C: ConnectionTrait>(db: &C, name: &str) -> ModelResult<Self> {
         Self::find_by_column(db, courses::Column::Name, name).await
     }
 
@@ -18,12 +138,82 @@ impl super::_entities::courses::Model {
     /// # Errors
     ///
     /// When could not find user by id or DB query error
-    pub async fn find_by_id(db: &DatabaseConnection, id: i32) -> ModelResult<Self> {
+    pub async fn find_by_id<C: ConnectionTrait>(db: &C, id: i32) -> ModelResult<Self> {
         Self::find_by_column(db, courses::Column::Id, id).await
     }
 
-    async fn find_by_column(
-        db: &DatabaseConnection,
+    /// Role of the user in this course, the course's teacher is always a teacher.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error.
+    pub async fn role_of<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        user: &users::Model,
+    ) -> ModelResult<Option<CourseRole>> {
+        if self.teacher_id == user.id {
+            return Ok(Some(CourseRole::Teacher));
+        }
+        let member = self
+            .find_related(course_members::Entity)
+            .filter(course_members::Column::UserId.eq(user.id))
+            .one(db)
+            .await?;
+
+        Ok(member.map(|m| m.role))
+    }
+
+    /// Whether the user can manage members of this course, only admins and the
+    /// course's teachers are allowed.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error.
+    pub async fn is_managed_by<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        user: &users::Model,
+    ) -> ModelResult<bool> {
+        use super::users::Role;
+
+        if user.role == Role::Admin {
+            return Ok(true);
+        }
+        Ok(self.role_of(db, user).await? == Some(CourseRole::Teacher))
+    }
+
+    /// List members with their role, the course's teacher comes first.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error.
+    pub async fn members<C: ConnectionTrait>(
+        &self,
+        db: &C,
+    ) -> ModelResult<Vec<(users::Model, CourseRole)>> {
+        let teacher = users::Entity::find_by_id(self.teacher_id)
+            .one(db)
+            .await?
+            .ok_or(ModelError::EntityNotFound)?;
+        let members = self
+            .find_related(course_members::Entity)
+            .find_also_related(users::Entity)
+            .filter(course_members::Column::UserId.ne(self.teacher_id))
+            .order_by(course_members::Column::Id, Order::Asc)
+            .all(db)
+            .await?
+            .into_iter()
+            // user should always exist because of the foreign key
+            .filter_map(|(m, u)| u.map(|u| (u, m.role)));
+
+        Ok(std::iter::once((teacher, CourseRole::Teacher))
+            .chain(members)
+            .collect())
+    }
+
+    async fn find_by_column<C: ConnectionTrait>(
+        db: &C,
         column: impl sea_orm::ColumnTrait,
         value: impl Into<sea_orm::Value> + Send,
     ) -> ModelResult<Self> {
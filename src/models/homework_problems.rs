@@ -0,0 +1,70 @@
+use loco_rs::model::ModelResult;
+use sea_orm::{entity::prelude::*, ActiveValue, Order, QueryOrder, QuerySelect};
+
+pub use super::_entities::homework_problems::{ActiveModel, Column, Entity, Model};
+use super::transform_db_error;
+
+impl ActiveModelBehavior for ActiveModel {
+    // extend activemodel below (keep comment for generators)
+}
+
+impl Model {
+    /// Replace problems of a homework, the order is kept and duplicated
+    /// problems are ignored.
+    ///
+    /// # Errors
+    ///
+    /// When there is DB error.
+    pub async fn set_for_homework<C: ConnectionTrait>(
+        db: &C,
+        homework_id: i32,
+        problem_ids: &[i32],
+    ) -> ModelResult<()> {
+        Entity::delete_many()
+            .filter(Column::HomeworkId.eq(homework_id))
+            .exec(db)
+            .await?;
+
+        let mut unique_ids: Vec<i32> = Vec::with_capacity(problem_ids.len());
+        for id in problem_ids {
+            if !unique_ids.contains(id) {
+                unique_ids.push(*id);
+            }
+        }
+        if unique_ids.is_empty() {
+            return Ok(());
+        }
+
+        Entity::insert_many(unique_ids.into_iter().map(|problem_id| ActiveModel {
+            homework_id: ActiveValue::set(homework_id),
+            problem_id: ActiveValue::set(problem_id),
+            ..Default::default()
+        }))
+        .exec_without_returning(db)
+        .await
+        .map_err(transform_db_error)?;
+
+        Ok(())
+    }
+
+    /// Ids of problems in a homework, in the order they were assigned.
+    ///
+    /// # Errors
+    ///
+    /// When there is DB error.
+    pub async fn problem_ids<C: ConnectionTrait>(
+        db: &C,
+        homework_id: i32,
+    ) -> ModelResult<Vec<i32>> {
+        let ids = Entity::find()
+            .select_only()
+            .column(Column::ProblemId)
+            .filter(Column::HomeworkId.eq(homework_id))
+            .order_by(Column::Id, Order::Asc)
+            .into_tuple()
+            .all(db)
+            .await?;
+
+        Ok(ids)
+    }
+}
@@ -0,0 +1,355 @@
+use std::collections::HashMap;
+
+use loco_rs::model::{ModelError, ModelResult};
+use sea_orm::{
+    entity::prelude::*, sea_query::Query, ActiveValue, IntoActiveModel, Order, QueryOrder,
+    QuerySelect, TransactionTrait,
+};
+use serde::Deserialize;
+
+pub use super::_entities::homeworks::{ActiveModel, Column, Entity, Model};
+use super::{
+    _entities::{courses, problem_courses, submissions, users},
+    course_members::CourseRole,
+    homework_problems, transform_db_error,
+};
+
+/// Max length of homework name, limited by the DB column
+const NAME_MAX_LENGTH: usize = 64;
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("homework name should be 1 to 64 characters")]
+    InvalidName,
+    #[error("homework should end after it starts, and cannot be closed before it ends")]
+    InvalidDuration,
+    #[error("penalty should be between 0 and 100")]
+    InvalidPenalty,
+    #[error("problem {0} is not assigned to the course")]
+    ProblemNotInCourse(i32),
+}
+
+/// Content of a homework, used to create or replace it.
+#[derive(Debug, Deserialize)]
+pub struct Params {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub start_at: DateTime,
+    pub end_at: DateTime,
+    /// Late submissions are accepted until this time with [`Self::penalty`].
+    /// There is no late submission if not set.
+    pub late_end_at: Option<DateTime>,
+    /// Percentage of score deducted from late submissions
+    #[serde(default)]
+    pub penalty: i32,
+    /// Problems of the course in display order
+    #[serde(default)]
+    pub problem_ids: Vec<i32>,
+}
+
+impl Params {
+    async fn validate<C: ConnectionTrait>(&self, db: &C, course_id: i32) -> ModelResult<()> {
+        if self.name.is_empty() || self.name.chars().count() > NAME_MAX_LENGTH {
+            return Err(ModelError::Any(Error::InvalidName.into()));
+        }
+        if self.end_at <= self.start_at || self.late_end_at.is_some_and(|t| t < self.end_at) {
+            return Err(ModelError::Any(Error::InvalidDuration.into()));
+        }
+        if !(0..=100).contains(&self.penalty) {
+            return Err(ModelError::Any(Error::InvalidPenalty.into()));
+        }
+
+        let assigned: Vec<i32> = problem_courses::Entity::find()
+            .select_only()
+            .column(problem_courses::Column::ProblemId)
+            .filter(problem_courses::Column::CourseId.eq(course_id))
+            .filter(problem_courses::Column::ProblemId.is_in(self.problem_ids.iter().copied()))
+            .into_tuple()
+            .all(db)
+            .await?;
+        if let Some(id) = self.problem_ids.iter().find(|id| !assigned.contains(id)) {
+            return Err(ModelError::Any(Error::ProblemNotInCourse(*id).into()));
+        }
+
+        Ok(())
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {
+    // extend activemodel below (keep comment for generators)
+}
+
+impl Model {
+    /// Create a homework in the course.
+    ///
+    /// # Errors
+    ///
+    /// - When the params are invalid, or the name is already used in the course
+    /// - When has DB query error
+    pub async fn add<C: ConnectionTrait + TransactionTrait>(
+        db: &C,
+        course: &courses::Model,
+        params: &Params,
+    ) -> ModelResult<Self> {
+        params.validate(db, course.id).await?;
+
+        let txn = db.begin().await?;
+        let homework = ActiveModel {
+            course_id: ActiveValue::set(course.id),
+            ..Default::default()
+        }
+        .fill(params)
+        .insert(&txn)
+        .await
+        .map_err(transform_db_error)?;
+        homework_problems::Model::set_for_homework(&txn, homework.id, &params.problem_ids).await?;
+        txn.commit().await?;
+
+        Ok(homework)
+    }
+
+    /// Replace content and problems of the homework.
+    ///
+    /// # Errors
+    ///
+    /// - When the params are invalid, or the name is already used in the course
+    /// - When has DB query error
+    pub async fn edit<C: ConnectionTrait + TransactionTrait>(
+        self,
+        db: &C,
+        params: &Params,
+    ) -> ModelResult<Self> {
+        params.validate(db, self.course_id).await?;
+
+        let txn = db.begin().await?;
+        let homework = self
+            .into_active_model()
+            .fill(params)
+            .update(&txn)
+            .await
+            .map_err(transform_db_error)?;
+        homework_problems::Model::set_for_homework(&txn, homework.id, &params.problem_ids).await?;
+        txn.commit().await?;
+
+        Ok(homework)
+    }
+
+    /// List homeworks of the course, the earliest one comes first.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error.
+    pub async fn list_in_course<C: ConnectionTrait>(
+        db: &C,
+        course_id: i32,
+    ) -> ModelResult<Vec<Self>> {
+        let homeworks = Entity::find()
+            .filter(Column::CourseId.eq(course_id))
+            .order_by(Column::StartAt, Order::Asc)
+            .order_by(Column::Id, Order::Asc)
+            .all(db)
+            .await?;
+
+        Ok(homeworks)
+    }
+
+    /// Find a homework of the course by its primary id
+    ///
+    /// # Errors
+    ///
+    /// - When could not query the homework from DB
+    /// - When the homework does not exist or belongs to another course
+    pub async fn find_in_course<C: ConnectionTrait>(
+        db: &C,
+        course_id: i32,
+        id: i32,
+    ) -> ModelResult<Self> {
+        Entity::find_by_id(id)
+            .filter(Column::CourseId.eq(course_id))
+            .one(db)
+            .await
+            .map_err(transform_db_error)?
+            .ok_or(ModelError::EntityNotFound)
+    }
+
+    /// Ids of problems in this homework
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error.
+    pub async fn problem_ids<C: ConnectionTrait>(&self, db: &C) -> ModelResult<Vec<i32>> {
+        homework_problems::Model::problem_ids(db, self.id).await
+    }
+
+    /// Last time to submit, including the late submission period.
+    #[must_use]
+    pub fn deadline(&self) -> DateTime {
+        self.late_end_at.unwrap_or(self.end_at)
+    }
+
+    /// Whether submissions are accepted at the time.
+    #[must_use]
+    pub fn is_open_at(&self, time: DateTime) -> bool {
+        self.start_at <= time && time <= self.deadline()
+    }
+
+    /// Score counted by this homework, late submissions are penalized.
+    #[must_use]
+    pub fn score_of(&self, score: i32, submitted_at: DateTime) -> i32 {
+        if submitted_at > self.end_at {
+            score * (100 - self.penalty) / 100
+        } else {
+            score
+        }
+    }
+
+    /// Whether the user can submit to the problem at the time. Problems without
+    /// homework are always open, otherwise students can only submit when one of
+    /// the homeworks in their courses is open. Problem managers, teachers and TAs
+    /// are not limited.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error.
+    pub async fn accepts_submission<C: ConnectionTrait>(
+        db: &C,
+        problem: &super::problems::Model,
+        user: &users::Model,
+        time: DateTime,
+    ) -> ModelResult<bool> {
+        if problem.is_managed_by(user) {
+            return Ok(true);
+        }
+        let homeworks = Entity::find()
+            .find_also_related(courses::Entity)
+            .filter(
+                Column::Id.in_subquery(
+                    Query::select()
+                        .column(homework_problems::Column::HomeworkId)
+                        .from(homework_problems::Entity)
+                        .and_where(homework_problems::Column::ProblemId.eq(problem.id))
+                        .to_owned(),
+                ),
+            )
+            .all(db)
+            .await?;
+
+        let mut restricted = false;
+        for (homework, course) in homeworks {
+            let Some(course) = course else {
+                continue;
+            };
+            match course.role_of(db, user).await? {
+                Some(CourseRole::Teacher | CourseRole::Ta) => return Ok(true),
+                Some(CourseRole::Student) if homework.is_open_at(time) => return Ok(true),
+                Some(CourseRole::Student) => restricted = true,
+                // homeworks of other courses do not apply
+                None => {}
+            }
+        }
+
+        Ok(!restricted)
+    }
+
+    /// Best score of each student in the course to each problem of this homework,
+    /// only submissions before the deadline are counted. Scores are in the same
+    /// order as [`Self::problem_ids`], and students are ordered by username.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error.
+    pub async fn scoreboard<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        problem_ids: &[i32],
+    ) -> ModelResult<Vec<(users::Model, Vec<i32>)>> {
+        let course = courses::Entity::find_by_id(self.course_id)
+            .one(db)
+            .await?
+            .ok_or(ModelError::EntityNotFound)?;
+        let mut students = course
+            .members(db)
+            .await?
+            .into_iter()
+            .filter(|(_, role)| *role == CourseRole::Student)
+            .map(|(u, _)| u)
+            .collect::<Vec<_>>();
+        students.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let submissions = submissions::Entity::find()
+            .filter(submissions::Column::ProblemId.is_in(problem_ids.iter().copied()))
+            .filter(submissions::Column::UserId.is_in(students.iter().map(|u| u.id)))
+            .filter(submissions::Column::CreatedAt.between(self.start_at, self.deadline()))
+            .all(db)
+            .await?;
+        let mut best: HashMap<(i32, i32), i32> = HashMap::new();
+        for s in submissions {
+            let score = self.score_of(s.score, s.created_at);
+            let entry = best.entry((s.user_id, s.problem_id)).or_default();
+            *entry = score.max(*entry);
+        }
+
+        Ok(students
+            .into_iter()
+            .map(|u| {
+                let scores = problem_ids
+                    .iter()
+                    .map(|p| best.get(&(u.id, *p)).copied().unwrap_or(0))
+                    .collect();
+                (u, scores)
+            })
+            .collect())
+    }
+}
+
+impl ActiveModel {
+    fn fill(mut self, params: &Params) -> Self {
+        self.name = ActiveValue::set(params.name.clone());
+        self.description = ActiveValue::set(params.description.clone());
+        self.start_at = ActiveValue::set(params.start_at);
+        self.end_at = ActiveValue::set(params.end_at);
+        self.late_end_at = ActiveValue::set(params.late_end_at);
+        self.penalty = ActiveValue::set(params.penalty);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    #[test]
+    fn test_late_submission() {
+        let at = |hour| {
+            NaiveDate::from_ymd_opt(2024, 7, 1)
+                .unwrap()
+                .and_hms_opt(hour, 0, 0)
+                .unwrap()
+        };
+        let mut homework = Model {
+            created_at: at(0),
+            updated_at: at(0),
+            id: 1,
+            course_id: 1,
+            name: "hw1".to_string(),
+            description: String::new(),
+            start_at: at(8),
+            end_at: at(12),
+            late_end_at: None,
+            penalty: 30,
+        };
+        assert!(!homework.is_open_at(at(7)));
+        assert!(homework.is_open_at(at(12)));
+        assert!(!homework.is_open_at(at(13)));
+
+        homework.late_end_at = Some(at(14));
+        assert!(homework.is_open_at(at(13)));
+        assert!(!homework.is_open_at(at(15)));
+        assert_eq!(homework.score_of(100, at(12)), 100);
+        assert_eq!(homework.score_of(100, at(13)), 70);
+    }
+}
@@ -1,8 +1,117 @@
+use num_derive::FromPrimitive;
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+
 /// Supported languages by Normal OJ
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, Serialize_repr, Deserialize_repr, PartialEq, Eq, FromPrimitive)]
 #[repr(i8)]
 pub enum Language {
     C = 0,
     Cpp = 1,
     Python = 2,
 }
+
+impl Language {
+    pub const ALL: [Self; 3] = [Self::C, Self::Cpp, Self::Python];
+
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::C => "c",
+            Self::Cpp => "cpp",
+            Self::Python => "python",
+        }
+    }
+
+    const fn bit(self) -> i32 {
+        1 << self as i32
+    }
+}
+
+/// Set of languages, stored as a bitmask where bit `n` stands for the language
+/// whose value is `n`. Compatible with `allowed_language` of Normal-OJ.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(try_from = "i32", into = "i32")]
+pub struct LanguageSet(i32);
+
+impl LanguageSet {
+    pub const ALL: Self = Self(Language::C.bit() | Language::Cpp.bit() | Language::Python.bit());
+
+    /// Returns `None` if the set is empty or contains unknown languages.
+    #[must_use]
+    pub const fn from_bits(bits: i32) -> Option<Self> {
+        if bits == 0 || bits & !Self::ALL.0 != 0 {
+            None
+        } else {
+            Some(Self(bits))
+        }
+    }
+
+    /// Drop unknown languages, the result may be empty.
+    #[must_use]
+    pub const fn from_bits_truncate(bits: i32) -> Self {
+        Self(bits & Self::ALL.0)
+    }
+
+    #[must_use]
+    pub const fn bits(self) -> i32 {
+        self.0
+    }
+
+    #[must_use]
+    pub const fn contains(self, language: Language) -> bool {
+        self.0 & language.bit() != 0
+    }
+
+    pub fn iter(self) -> impl Iterator<Item = Language> {
+        Language::ALL.into_iter().filter(move |l| self.contains(*l))
+    }
+}
+
+impl TryFrom<i32> for LanguageSet {
+    type Error = String;
+
+    fn try_from(bits: i32) -> Result<Self, Self::Error> {
+        Self::from_bits(bits).ok_or_else(|| format!("invalid language set: {bits}"))
+    }
+}
+
+impl From<LanguageSet> for i32 {
+    fn from(set: LanguageSet) -> Self {
+        set.bits()
+    }
+}
+
+impl FromIterator<Language> for LanguageSet {
+    fn from_iter<T: IntoIterator<Item = Language>>(iter: T) -> Self {
+        Self(iter.into_iter().fold(0, |bits, l| bits | l.bit()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_serde_language_set() {
+        assert_eq!(json!(LanguageSet::ALL), json!(7));
+        assert_eq!(
+            serde_json::from_str::<LanguageSet>("5").unwrap(),
+            [Language::C, Language::Python].into_iter().collect(),
+        );
+        assert!(serde_json::from_str::<LanguageSet>("0").is_err());
+        assert!(serde_json::from_str::<LanguageSet>("8").is_err());
+    }
+
+    #[test]
+    fn test_language_set_contains() {
+        let set = LanguageSet::from_bits(0b110).unwrap();
+        assert!(!set.contains(Language::C));
+        assert!(set.contains(Language::Cpp));
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            vec![Language::Cpp, Language::Python]
+        );
+    }
+}
@@ -1,14 +1,29 @@
 pub mod _entities;
+pub mod contests;
+pub mod course_members;
 pub mod courses;
+pub mod homework_problems;
+pub mod homeworks;
 pub mod language;
 pub mod notes;
 pub mod problems;
+pub mod submissions;
 pub mod users;
 
-pub use language::Language;
+pub use language::{Language, LanguageSet};
 
 use loco_rs::model::ModelError;
-use sea_orm::{DbErr, SqlErr};
+use sea_orm::{prelude::DateTime, DbErr, SqlErr};
+
+/// Current time of the app.
+///
+/// Times compared with each other, like submission times and homework or
+/// contest windows, should all come from this clock instead of the DB's
+/// `CURRENT_TIMESTAMP`, which may be in another time zone.
+#[must_use]
+pub fn now() -> DateTime {
+    chrono::Local::now().naive_local()
+}
 
 pub(crate) fn is_unique_constraint_violation_err(e: &DbErr) -> bool {
     matches!(e.sql_err(), Some(SqlErr::UniqueConstraintViolation(_)))
@@ -0,0 +1,88 @@
+use crate::models::transform_db_error;
+
+pub use super::_entities::problem_courses::{ActiveModel, Column, Entity, Model};
+use super::_entities::{course_members, courses, sea_orm_active_enums::CourseRole};
+use loco_rs::model::ModelResult;
+use sea_orm::{
+    entity::prelude::*,
+    sea_query::{Query, SelectStatement},
+    ActiveValue, Condition,
+};
+
+impl Model {
+    /// Replace courses of a problem
+    ///
+    /// # Errors
+    ///
+    /// When there is DB error.
+    pub async fn set_for_problem<C: ConnectionTrait>(
+        db: &C,
+        problem_id: i32,
+        course_ids: &[i32],
+    ) -> ModelResult<()> {
+        Entity::delete_many()
+            .filter(Column::ProblemId.eq(problem_id))
+            .exec(db)
+            .await?;
+        if course_ids.is_empty() {
+            return Ok(());
+        }
+
+        Entity::insert_many(course_ids.iter().map(|&course_id| ActiveModel {
+            problem_id: ActiveValue::set(problem_id),
+            course_id: ActiveValue::set(course_id),
+            ..Default::default()
+        }))
+        .exec_without_returning(db)
+        .await
+        .map_err(transform_db_error)?;
+
+        Ok(())
+    }
+}
+
+/// Sub query selecting ids of problems assigned to the course.
+#[must_use]
+pub fn problem_ids_in_course(course_name: &str) -> SelectStatement {
+    Query::select()
+        .column((Entity, Column::ProblemId))
+        .from(Entity)
+        .inner_join(
+            courses::Entity,
+            Expr::col((courses::Entity, courses::Column::Id)).equals((Entity, Column::CourseId)),
+        )
+        .and_where(Expr::col((courses::Entity, courses::Column::Name)).eq(course_name))
+        .to_owned()
+}
+
+/// Sub query selecting ids of problems assigned to courses in which the user
+/// has one of the given roles.
+#[must_use]
+pub fn problem_ids_in_courses_of(user_id: i32, roles: &[CourseRole]) -> SelectStatement {
+    let member_courses = Query::select()
+        .column(course_members::Column::CourseId)
+        .from(course_members::Entity)
+        .and_where(course_members::Column::UserId.eq(user_id))
+        .and_where(course_members::Column::Role.is_in(roles.iter().copied()))
+        .to_owned();
+    let mut cond = Condition::any().add(Column::CourseId.in_subquery(member_courses));
+    // the course teacher may not be listed in course members
+    if roles.contains(&CourseRole::Teacher) {
+        let teaching_courses = Query::select()
+            .column(courses::Column::Id)
+            .from(courses::Entity)
+            .and_where(courses::Column::TeacherId.eq(user_id))
+            .to_owned();
+        cond = cond.add(Column::CourseId.in_subquery(teaching_courses));
+    }
+
+    Query::select()
+        .column(Column::ProblemId)
+        .from(Entity)
+        .cond_where(cond)
+        .to_owned()
+}
+
+impl ActiveModelBehavior for ActiveModel {
+    // extend activemodel below (keep comment for generators)
+}
@@ -2,7 +2,7 @@ use crate::models::is_unique_constraint_violation_err;
 
 pub use super::_entities::problem_descriptions::{ActiveModel, Model};
 use loco_rs::model::{ModelError, ModelResult};
-use sea_orm::{entity::prelude::*, ActiveValue};
+use sea_orm::{entity::prelude::*, ActiveValue, IntoActiveModel};
 use serde::Deserialize;
 
 impl ActiveModelBehavior for ActiveModel {
@@ -47,4 +47,20 @@ impl Model {
 
         Ok(problem_description)
     }
+    /// Replace content of the description
+    ///
+    /// # Errors
+    ///
+    /// When could not save the description into DB
+    pub async fn edit<C: ConnectionTrait>(self, db: &C, params: &AddParams) -> ModelResult<Self> {
+        let mut description = self.into_active_model();
+        description.description = ActiveValue::set(params.description.clone());
+        description.input = ActiveValue::set(params.input.clone());
+        description.output = ActiveValue::set(params.output.clone());
+        description.hint = ActiveValue::set(params.hint.clone());
+        description.sample_input = ActiveValue::set(params.sample_input.clone());
+        description.sample_output = ActiveValue::set(params.sample_output.clone());
+
+        Ok(description.update(db).await?)
+    }
 }
@@ -1,28 +1,29 @@
+pub mod courses;
 pub mod descriptions;
+pub mod tags;
 pub mod tasks;
+pub mod templates;
 pub mod test_case;
+pub mod test_case_versions;
 
-use std::collections::HashSet;
+use std::{
+    io::{Read, Seek},
+    path::PathBuf,
+};
 
 use super::_entities::{self, prelude::Problems, problems};
-use crate::models::transform_db_error;
+use crate::models::{transform_db_error, Language, LanguageSet};
 
 pub use _entities::problems::{ActiveModel, Model};
-use axum::body::Bytes;
 use loco_rs::model::{ModelError, ModelResult};
 use num_derive::FromPrimitive;
-use sea_orm::{entity::prelude::*, ActiveValue, Order, QueryOrder, TransactionTrait};
+use sea_orm::{
+    entity::prelude::*, ActiveValue, Condition, IntoActiveModel, Order, QueryOrder, QuerySelect,
+    TransactionTrait,
+};
 use serde::Deserialize;
 use serde_repr::{Deserialize_repr, Serialize_repr};
-
-#[derive(Debug, thiserror::Error)]
-#[non_exhaustive]
-pub enum BadTestCase {
-    #[error("error reading zip file: {0}")]
-    ZipError(#[from] zip::result::ZipError),
-    #[error("{0}")]
-    Custom(String),
-}
+pub use test_case::BadTestCase;
 
 #[derive(Debug, thiserror::Error)]
 #[non_exhaustive]
@@ -31,6 +32,10 @@ pub enum Error {
     PermissionDenied,
     #[error("bad test cacse: {0}")]
     BadTestCase(BadTestCase),
+    #[error("course {0} does not exist")]
+    CourseNotFound(String),
+    #[error("tag should be 1 to 64 characters: {0:?}")]
+    InvalidTag(String),
 }
 
 #[derive(Clone, Copy, Debug, Serialize_repr, Deserialize_repr, PartialEq, Eq, FromPrimitive)]
@@ -44,14 +49,29 @@ pub enum Visibility {
 #[repr(i8)]
 pub enum Type {
     Normal = 0,
+    /// Submissions only fill the blanks of a code template, see [`templates`]
     FillInTemplate = 1,
     Handwritten = 2,
+    /// The submission talks to an interactor in the test case through pipes
+    Interactive = 3,
 }
 
 impl ActiveModelBehavior for ActiveModel {
     // extend activemodel below (keep comment for generators)
 }
 
+/// Path of test case zip inside app's storage.
+#[must_use]
+pub fn test_case_path(test_case_id: &str) -> PathBuf {
+    PathBuf::from("test-case").join(format!("{test_case_id}.zip"))
+}
+
+/// Path of custom checker source code inside app's storage.
+#[must_use]
+pub fn checker_path(checker_id: &str) -> PathBuf {
+    PathBuf::from("checker").join(checker_id)
+}
+
 #[derive(Debug, Deserialize)]
 pub struct AddParams {
     pub owner: _entities::users::Model,
@@ -63,21 +83,100 @@ pub struct AddParams {
     /// Problem description struct
     pub description: descriptions::AddParams,
     pub r#type: Option<Type>,
-    pub allowed_language: Option<i32>,
+    pub allowed_language: Option<LanguageSet>,
     pub quota: Option<i32>,
     pub tasks: Vec<tasks::AddParams>,
+    pub tags: Vec<String>,
+}
+
+/// Fields of a problem to update, `None` means unchanged. Tasks, courses and tags
+/// are replaced as a whole.
+#[derive(Debug, Deserialize)]
+pub struct EditParams {
+    pub name: Option<String>,
+    pub status: Option<Visibility>,
+    pub description: Option<descriptions::AddParams>,
+    pub r#type: Option<Type>,
+    pub allowed_language: Option<LanguageSet>,
+    pub quota: Option<i32>,
+    pub tasks: Option<Vec<tasks::AddParams>>,
+    /// list of course names
+    pub courses: Option<Vec<String>>,
+    pub tags: Option<Vec<String>>,
+}
+
+/// Column to sort the problem list by
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SortBy {
+    #[default]
+    Id,
+    Name,
+    CreatedAt,
+    /// Ratio of users who have solved the problem to users who have submitted
+    AcRate,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl From<SortOrder> for Order {
+    fn from(order: SortOrder) -> Self {
+        match order {
+            SortOrder::Asc => Self::Asc,
+            SortOrder::Desc => Self::Desc,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct ListParams {
     pub viewer: _entities::users::Model,
-    pub offset: Option<usize>,
-    pub count: Option<usize>,
+    pub offset: Option<u64>,
+    /// Max count of problems to list, all problems are listed if not set
+    pub count: Option<u64>,
+    pub sort_by: SortBy,
+    pub order: SortOrder,
     pub name: Option<String>,
     pub tags: Option<Vec<String>>,
     pub course: Option<String>,
 }
 
+/// Ratio of users who have solved the problem to users who have submitted, 0 if
+/// nobody has submitted. Used to sort problems by [`SortBy::AcRate`].
+fn ac_rate() -> sea_orm::sea_query::SimpleExpr {
+    use _entities::submissions;
+    use sea_orm::sea_query::{Alias, Expr, Func, Query, SimpleExpr};
+
+    let ac_user = Expr::expr(Expr::case(
+        submissions::Column::Status.eq(super::submissions::Status::Accepted as i32),
+        Expr::col(submissions::Column::UserId),
+    ))
+    .count_distinct()
+    .cast_as(Alias::new("real"));
+    let submitter = Func::cust(Alias::new("NULLIF"))
+        .arg(Expr::col(submissions::Column::UserId).count_distinct())
+        .arg(0);
+    let rate = Query::select()
+        .expr(Func::coalesce([
+            ac_user.div(SimpleExpr::from(submitter)),
+            Expr::val(0.0).into(),
+        ]))
+        .from(submissions::Entity)
+        .and_where(
+            Expr::col((submissions::Entity, submissions::Column::ProblemId))
+                .equals((problems::Entity, problems::Column::Id)),
+        )
+        .to_owned();
+
+    SimpleExpr::SubQuery(None, Box::new(rate.into_sub_query_statement()))
+}
+
 impl _entities::problems::Model {
     /// Create a problem without test case binary
     ///
@@ -85,6 +184,8 @@ impl _entities::problems::Model {
     ///
     /// - When could not save the problem into DB
     /// - When the owner is not a teacher or admin
+    /// - When a course does not exist or cannot be managed by the owner
+    /// - When a tag is invalid
     pub async fn add<C: ConnectionTrait + TransactionTrait>(
         db: &C,
         params: &AddParams,
@@ -111,7 +212,7 @@ impl _entities::problems::Model {
             description_id: ActiveValue::set(description.id),
             allowed_language: params
                 .allowed_language
-                .map_or(ActiveValue::NotSet, ActiveValue::set),
+                .map_or(ActiveValue::NotSet, |l| ActiveValue::set(l.bits())),
             quota: params.quota.map_or(ActiveValue::NotSet, ActiveValue::set),
             ..Default::default()
         }
@@ -120,34 +221,208 @@ impl _entities::problems::Model {
         .map_err(transform_db_error)?;
 
         tasks::Model::add_many(&txn, problem.id, &params.tasks).await?;
+        let course_ids = Self::resolve_courses(&txn, &params.owner, &params.courses).await?;
+        courses::Model::set_for_problem(&txn, problem.id, &course_ids).await?;
+        Self::validate_tags(&params.tags)?;
+        tags::Model::set_for_problem(&txn, problem.id, &params.tags).await?;
 
         txn.commit().await.map_err(transform_db_error)?;
 
         Ok(problem)
     }
 
-    /// List problems
+    /// Update the problem. If test case counts of tasks are changed, or the type
+    /// is changed from or to [`Type::Interactive`], the uploaded test case is
+    /// marked as stale and should be uploaded again.
     ///
     /// # Errors
     ///
-    /// When cloud not query problems from DB
-    pub async fn list<C: ConnectionTrait>(db: &C, params: &ListParams) -> ModelResult<Vec<Self>> {
-        // TODO: check course && tags
+    /// - When could not save the problem into DB
+    /// - When a course does not exist or cannot be managed by the editor
+    /// - When a tag is invalid
+    pub async fn edit<C: ConnectionTrait + TransactionTrait>(
+        self,
+        db: &C,
+        editor: &_entities::users::Model,
+        params: &EditParams,
+    ) -> ModelResult<Self> {
+        let txn = db.begin().await?;
 
-        let mut q = Problems::find().order_by(problems::Column::Id, Order::Asc);
+        if let Some(description) = &params.description {
+            let current = self
+                .find_related(_entities::problem_descriptions::Entity)
+                .one(&txn)
+                .await?
+                .ok_or(ModelError::EntityNotFound)?;
+            current.edit(&txn, description).await?;
+        }
+        let mut test_case_stale = self.test_case_stale;
+        if let Some(new_tasks) = &params.tasks {
+            let old_counts = self
+                .tasks(&txn)
+                .await?
+                .iter()
+                .map(|t| t.test_case_count)
+                .collect::<Vec<_>>();
+            let new_counts = new_tasks
+                .iter()
+                .map(|t| t.test_case_count)
+                .collect::<Vec<_>>();
+            if self.test_case_id.is_some() && old_counts != new_counts {
+                test_case_stale = true;
+            }
+            tasks::Model::set_for_problem(&txn, self.id, new_tasks).await?;
+        }
+        // interactive test case has a different layout
+        let interactive = |t: Type| t == Type::Interactive;
+        if params
+            .r#type
+            .is_some_and(|t| interactive(t) != interactive(self.problem_type()))
+            && self.test_case_id.is_some()
+        {
+            test_case_stale = true;
+        }
+        if let Some(names) = &params.courses {
+            let course_ids = Self::resolve_courses(&txn, editor, names).await?;
+            courses::Model::set_for_problem(&txn, self.id, &course_ids).await?;
+        }
+        if let Some(new_tags) = &params.tags {
+            Self::validate_tags(new_tags)?;
+            tags::Model::set_for_problem(&txn, self.id, new_tags).await?;
+        }
 
+        let mut problem = self.into_active_model();
         if let Some(name) = &params.name {
-            q = q.filter(problems::Column::Name.eq(name));
+            problem.name = ActiveValue::set(name.clone());
+        }
+        if let Some(status) = params.status {
+            problem.status = ActiveValue::set(status as i32);
+        }
+        if let Some(r#type) = params.r#type {
+            problem.r#type = ActiveValue::set(r#type as i32);
+        }
+        if let Some(allowed_language) = params.allowed_language {
+            problem.allowed_language = ActiveValue::set(allowed_language.bits());
+        }
+        if let Some(quota) = params.quota {
+            problem.quota = ActiveValue::set(quota);
+        }
+        problem.test_case_stale = ActiveValue::set(test_case_stale);
+        let problem = problem.update(&txn).await.map_err(transform_db_error)?;
+
+        txn.commit().await.map_err(transform_db_error)?;
+
+        Ok(problem)
+    }
+
+    fn validate_tags(tags: &[String]) -> ModelResult<()> {
+        tags.iter()
+            .find(|t| !tags::is_valid_tag(t))
+            .map_or(Ok(()), |tag| {
+                Err(ModelError::Any(Error::InvalidTag(tag.clone()).into()))
+            })
+    }
+
+    /// Find ids of courses by their names, the user should be able to manage them.
+    async fn resolve_courses<C: ConnectionTrait>(
+        db: &C,
+        user: &_entities::users::Model,
+        names: &[String],
+    ) -> ModelResult<Vec<i32>> {
+        let mut ids = Vec::with_capacity(names.len());
+        for name in names {
+            let course = match super::courses::Model::find_by_name(db, name).await {
+                Ok(c) => c,
+                Err(ModelError::EntityNotFound) => {
+                    return Err(ModelError::Any(Error::CourseNotFound(name.clone()).into()));
+                }
+                Err(e) => return Err(e),
+            };
+            if !course.is_managed_by(db, user).await? {
+                return Err(ModelError::Any(Error::PermissionDenied.into()));
+            }
+            if !ids.contains(&course.id) {
+                ids.push(course.id);
+            }
         }
+        Ok(ids)
+    }
+
+    /// Condition of problems visible to the user. Admins can see everything,
+    /// including archived problems. Other users cannot see archived problems;
+    /// problem owners, course teachers and TAs can see all problems of their
+    /// courses, and students can only see shown problems of their courses.
+    /// Contest participants can also see problems of the contest while it runs.
+    pub(crate) fn visible_to(user: &_entities::users::Model) -> Condition {
+        use super::contests::problems::problem_ids_in_running_contests_of;
+
+        use super::users::Role;
+        use _entities::sea_orm_active_enums::CourseRole;
+
+        if user.role == Role::Admin {
+            return Condition::all();
+        }
+        let readable = Condition::any()
+            .add(problems::Column::OwnerId.eq(user.id))
+            .add(
+                problems::Column::Id.in_subquery(courses::problem_ids_in_courses_of(
+                    user.id,
+                    &[CourseRole::Teacher, CourseRole::Ta],
+                )),
+            )
+            .add(
+                Condition::all()
+                    .add(problems::Column::Status.eq(Visibility::Show as i32))
+                    .add(
+                        problems::Column::Id.in_subquery(courses::problem_ids_in_courses_of(
+                            user.id,
+                            &[CourseRole::Student],
+                        )),
+                    ),
+            )
+            .add(
+                problems::Column::Id
+                    .in_subquery(problem_ids_in_running_contests_of(user.id, super::now())),
+            );
+        Condition::all()
+            .add(problems::Column::ArchivedAt.is_null())
+            .add(readable)
+    }
+
+    /// List problems visible to the viewer with the count of all matched problems.
+    /// Problems with the same sort key are ordered by id.
+    ///
+    /// # Errors
+    ///
+    /// When cloud not query problems from DB
+    pub async fn list<C: ConnectionTrait>(
+        db: &C,
+        params: &ListParams,
+    ) -> ModelResult<(Vec<Self>, u64)> {
+        let order = Order::from(params.order);
+        let mut q = Problems::find().filter(Self::visible_to(&params.viewer));
+        q = match params.sort_by {
+            SortBy::Id => q,
+            SortBy::Name => q.order_by(problems::Column::Name, order.clone()),
+            SortBy::CreatedAt => q.order_by(problems::Column::CreatedAt, order.clone()),
+            SortBy::AcRate => q.order_by(ac_rate(), order.clone()),
+        };
+        q = q.order_by(problems::Column::Id, order);
 
-        let problems = q.all(db).await?.into_iter();
-        // TODO: permission check
+        if let Some(name) = &params.name {
+            q = q.filter(problems::Column::Name.eq(name));
+        }
+        if let Some(course) = &params.course {
+            q = q.filter(problems::Column::Id.in_subquery(courses::problem_ids_in_course(course)));
+        }
+        if let Some(tags) = params.tags.as_ref().filter(|t| !t.is_empty()) {
+            q = q.filter(problems::Column::Id.in_subquery(tags::problem_ids_with_tags(tags)));
+        }
 
-        let offset = params.offset.unwrap_or(0);
-        let count = params.count.unwrap_or(usize::MAX);
-        let problems = problems.skip(offset).take(count);
+        let total = q.clone().count(db).await?;
+        let problems = q.offset(params.offset).limit(params.count).all(db).await?;
 
-        Ok(problems.collect())
+        Ok((problems, total))
     }
 
     /// Find a problem by its primary id
@@ -165,6 +440,159 @@ impl _entities::problems::Model {
         p.ok_or(ModelError::EntityNotFound)
     }
 
+    /// Languages accepted by this problem
+    #[must_use]
+    pub const fn allowed_languages(&self) -> LanguageSet {
+        LanguageSet::from_bits_truncate(self.allowed_language)
+    }
+
+    /// Type of this problem, unknown values are treated as [`Type::Normal`].
+    #[must_use]
+    pub fn problem_type(&self) -> Type {
+        use num_traits::FromPrimitive;
+
+        Type::from_i32(self.r#type).unwrap_or(Type::Normal)
+    }
+
+    /// Language of the custom checker, `None` if outputs are compared exactly.
+    #[must_use]
+    pub fn checker_language(&self) -> Option<Language> {
+        use num_traits::FromPrimitive;
+
+        self.checker_id
+            .as_ref()
+            .and(self.checker_language)
+            .and_then(Language::from_i32)
+    }
+
+    /// Whether the user can read this problem, see [`Self::list`] for the rules.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error.
+    pub async fn is_visible_to<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        user: &_entities::users::Model,
+    ) -> ModelResult<bool> {
+        use super::users::Role;
+
+        if user.role == Role::Admin {
+            return Ok(true);
+        }
+        let count = Problems::find()
+            .filter(problems::Column::Id.eq(self.id))
+            .filter(Self::visible_to(user))
+            .count(db)
+            .await?;
+
+        Ok(count > 0)
+    }
+
+    /// Whether the problem is archived
+    #[must_use]
+    pub const fn is_archived(&self) -> bool {
+        self.archived_at.is_some()
+    }
+
+    /// Delete the problem with its description, tasks, submissions, test case
+    /// versions, checker and associations. Returns paths of files in app's
+    /// storage which belong to the problem and should be removed by the caller.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error.
+    pub async fn delete_with_relations<C: ConnectionTrait + TransactionTrait>(
+        self,
+        db: &C,
+    ) -> ModelResult<Vec<PathBuf>> {
+        use _entities::{problem_descriptions, problem_tasks, submissions};
+
+        let txn = db.begin().await?;
+
+        let mut paths = submissions::Entity::find()
+            .filter(submissions::Column::ProblemId.eq(self.id))
+            .all(&txn)
+            .await?
+            .iter()
+            .map(|s| super::submissions::code_path(&s.code_id))
+            .collect::<Vec<_>>();
+        // test case uploaded before versions were recorded has no version
+        let mut test_case_paths =
+            test_case_versions::Model::paths_of_problem(&txn, self.id).await?;
+        if let Some(test_case_id) = &self.test_case_id {
+            let path = test_case_path(test_case_id);
+            if !test_case_paths.contains(&path) {
+                test_case_paths.push(path);
+            }
+        }
+        paths.extend(test_case_paths);
+        if let Some(checker_id) = &self.checker_id {
+            paths.push(checker_path(checker_id));
+        }
+
+        submissions::Entity::delete_many()
+            .filter(submissions::Column::ProblemId.eq(self.id))
+            .exec(&txn)
+            .await?;
+        problem_tasks::Entity::delete_many()
+            .filter(problem_tasks::Column::ProblemId.eq(self.id))
+            .exec(&txn)
+            .await?;
+        // courses, tags, templates, test case versions, homework and contest
+        // entries are removed by the database
+        let description_id = self.description_id;
+        self.delete(&txn).await?;
+        problem_descriptions::Entity::delete_by_id(description_id)
+            .exec(&txn)
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(paths)
+    }
+
+    /// Whether the user can manage this problem, only the owner and admins are allowed.
+    #[must_use]
+    pub fn is_managed_by(&self, user: &_entities::users::Model) -> bool {
+        use super::users::Role;
+
+        user.role == Role::Admin || self.owner_id == user.id
+    }
+
+    /// Find courses the problem is assigned to
+    ///
+    /// # Errors
+    ///
+    /// When there is DB error.
+    pub async fn courses<C: ConnectionTrait>(
+        &self,
+        db: &C,
+    ) -> ModelResult<Vec<_entities::courses::Model>> {
+        let courses = self
+            .find_related(_entities::courses::Entity)
+            .order_by(_entities::courses::Column::Id, Order::Asc)
+            .all(db)
+            .await?;
+
+        Ok(courses)
+    }
+
+    /// Find tags of the problem
+    ///
+    /// # Errors
+    ///
+    /// When there is DB error.
+    pub async fn tags<C: ConnectionTrait>(&self, db: &C) -> ModelResult<Vec<String>> {
+        let tags = self
+            .find_related(_entities::problem_tags::Entity)
+            .order_by(_entities::problem_tags::Column::Id, Order::Asc)
+            .all(db)
+            .await?;
+
+        Ok(tags.into_iter().map(|t| t.tag).collect())
+    }
+
     /// Find problem tasks from DB
     ///
     /// # Errors
@@ -180,87 +608,112 @@ impl _entities::problems::Model {
         Ok(tasks)
     }
 
-    /// Validate test case binary according to current problem test case meta.
+    /// Validate test case zip and read its `meta.json`. If `replace_tasks` is
+    /// set, cases are checked against tasks declared in `meta.json`. Otherwise they
+    /// are checked against current problem tasks, which `meta.json` should agree
+    /// with if it exists. The zip is decompressed on a blocking thread because it
+    /// may be large.
     ///
     /// # Errors
     ///
     /// - When the given binary is not a zip file
+    /// - When `meta.json` is malformed or does not match the problem tasks
     /// - When the zip file contains invalid files
     /// - When there is missing/extra files inside zip
-    pub async fn validate_test_case<C: ConnectionTrait>(
+    /// - When files exceed the limits
+    pub async fn validate_test_case<C: ConnectionTrait, R: Read + Seek + Send + 'static>(
         &self,
         db: &C,
-        test_case: &Bytes,
-    ) -> loco_rs::Result<()> {
-        let wrap_zip_error =
-            |e| loco_rs::Error::Any(Box::new(Error::BadTestCase(BadTestCase::ZipError(e))));
-        let custom_error =
-            |e| loco_rs::Error::Any(Box::new(Error::BadTestCase(BadTestCase::Custom(e))));
-
-        let cursor = std::io::Cursor::new(test_case);
-        let mut zipfile = zip::ZipArchive::new(cursor).map_err(wrap_zip_error)?;
-
-        // TODO: valiadte according to problem test case meta
-        let tasks = self.tasks(db).await?;
-        let mut expected_input_output = tasks
-            .iter()
-            .enumerate()
-            .flat_map(|(i, t)| {
-                (0..t.test_case_count).flat_map(move |j| {
-                    vec![
-                        format!("test-case/{i:02}{j:02}/STDIN"),
-                        format!("test-case/{i:02}{j:02}/STDOUT"),
-                    ]
-                })
-            })
-            .collect::<HashSet<_>>();
-
-        for i in 0..zipfile.len() {
-            let file = zipfile.by_index(i).map_err(wrap_zip_error)?;
-            if file.is_symlink() {
-                return Err(custom_error(format!(
-                    "symlink is not allowed: {}",
-                    file.name()
-                )));
-            }
-            // skip directory for now
-            if file.is_dir() {
-                continue;
-            }
-            let name = file.enclosed_name().ok_or_else(|| {
-                custom_error(format!("invalid path found in zip file: {}", file.name()))
-            })?;
-            let name = name.to_str().ok_or_else(|| {
-                custom_error(format!(
-                    "invalid path found in zip file (maybe non-UTF8 path?): {}",
-                    file.name()
-                ))
-            })?;
-
-            if !expected_input_output.remove(name) {
-                return Err(custom_error(format!(
-                    "duplicated or extra file found: {}",
-                    file.name()
-                )));
-            }
-        }
-
-        if !expected_input_output.is_empty() {
-            return Err(custom_error(format!(
-                "missing files: {}",
-                expected_input_output
-                    .into_iter()
-                    .collect::<Vec<_>>()
-                    .join(",")
-            )));
-        }
-
-        Ok(())
+        mut test_case: R,
+        replace_tasks: bool,
+        limits: &test_case::Limits,
+    ) -> loco_rs::Result<Option<test_case::Meta>> {
+        let task_counts = if replace_tasks {
+            None
+        } else {
+            Some(
+                self.tasks(db)
+                    .await?
+                    .iter()
+                    .map(|t| t.test_case_count)
+                    .collect::<Vec<_>>(),
+            )
+        };
+        let problem_type = self.problem_type();
+        let limits = limits.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let meta = test_case::read_meta(&mut test_case)?;
+            let case_counts = match task_counts {
+                None => meta
+                    .as_ref()
+                    .map(test_case::Meta::case_counts)
+                    .ok_or_else(|| {
+                        BadTestCase::Custom(format!(
+                            "{} is required to replace tasks",
+                            test_case::META_FILE
+                        ))
+                    })?,
+                Some(counts) => {
+                    if meta.as_ref().is_some_and(|m| m.case_counts() != counts) {
+                        return Err(BadTestCase::Custom(format!(
+                            "tasks in {} do not match the problem",
+                            test_case::META_FILE
+                        )));
+                    }
+                    counts
+                }
+            };
+            test_case::validate(&mut test_case, &case_counts, problem_type, &limits)?;
+
+            Ok(meta)
+        })
+        .await
+        .map_err(|e| loco_rs::Error::Any(e.into()))?
+        .map_err(|e| loco_rs::Error::Any(Box::new(Error::BadTestCase(e))))
     }
 }
 
 impl ActiveModel {
-    /// Update test case id. The actual file content is handled by app's storage.
+    /// Archive the problem, so that it is hidden from everyone except admins.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error.
+    pub async fn archive(mut self, db: &impl ConnectionTrait) -> ModelResult<Model> {
+        self.archived_at = ActiveValue::set(Some(chrono::Local::now().naive_local()));
+        Ok(self.update(db).await?)
+    }
+
+    /// Restore an archived problem.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error.
+    pub async fn restore(mut self, db: &impl ConnectionTrait) -> ModelResult<Model> {
+        self.archived_at = ActiveValue::set(None);
+        Ok(self.update(db).await?)
+    }
+
+    /// Set or remove the custom checker. The source code is handled by app's
+    /// storage, see [`checker_path`].
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error.
+    pub async fn update_checker(
+        mut self,
+        db: &impl ConnectionTrait,
+        checker: Option<(String, Language)>,
+    ) -> ModelResult<Model> {
+        let (checker_id, language) = checker.unzip();
+        self.checker_id = ActiveValue::set(checker_id);
+        self.checker_language = ActiveValue::set(language.map(|l| l as i32));
+        Ok(self.update(db).await?)
+    }
+
+    /// Update test case id and clear the stale mark. The actual file content is
+    /// handled by app's storage.
     ///
     /// # Errors
     ///
@@ -271,6 +724,7 @@ impl ActiveModel {
         test_case_id: Option<String>,
     ) -> ModelResult<Model> {
         self.test_case_id = ActiveValue::set(test_case_id);
+        self.test_case_stale = ActiveValue::set(false);
         Ok(self.update(db).await?)
     }
 }
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use crate::models::transform_db_error;
+
+pub use super::_entities::problem_tags::{ActiveModel, Column, Entity, Model};
+use loco_rs::model::ModelResult;
+use sea_orm::{
+    entity::prelude::*,
+    sea_query::{Query, SelectStatement},
+    ActiveValue, Order, QueryOrder,
+};
+
+/// Max length of a tag, limited by the DB column
+pub const TAG_MAX_LENGTH: usize = 64;
+
+/// Whether the tag is non-empty and fits in the DB column
+#[must_use]
+pub fn is_valid_tag(tag: &str) -> bool {
+    !tag.is_empty() && tag.chars().count() <= TAG_MAX_LENGTH
+}
+
+/// Remove duplicated tags and keep their order
+fn unique(tags: &[String]) -> Vec<&String> {
+    let mut result: Vec<&String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        if !result.contains(&tag) {
+            result.push(tag);
+        }
+    }
+    result
+}
+
+impl Model {
+    /// Replace tags of a problem, duplicated tags are ignored.
+    ///
+    /// # Errors
+    ///
+    /// When there is DB error.
+    pub async fn set_for_problem<C: ConnectionTrait>(
+        db: &C,
+        problem_id: i32,
+        tags: &[String],
+    ) -> ModelResult<()> {
+        Entity::delete_many()
+            .filter(Column::ProblemId.eq(problem_id))
+            .exec(db)
+            .await?;
+
+        let unique_tags = unique(tags);
+        if unique_tags.is_empty() {
+            return Ok(());
+        }
+
+        Entity::insert_many(unique_tags.into_iter().map(|tag| ActiveModel {
+            problem_id: ActiveValue::set(problem_id),
+            tag: ActiveValue::set(tag.clone()),
+            ..Default::default()
+        }))
+        .exec_without_returning(db)
+        .await
+        .map_err(transform_db_error)?;
+
+        Ok(())
+    }
+
+    /// Find tags of given problems, problems without tags are not included.
+    ///
+    /// # Errors
+    ///
+    /// When there is DB error.
+    pub async fn find_by_problems<C: ConnectionTrait>(
+        db: &C,
+        problem_ids: &[i32],
+    ) -> ModelResult<HashMap<i32, Vec<String>>> {
+        let tags = Entity::find()
+            .filter(Column::ProblemId.is_in(problem_ids.iter().copied()))
+            .order_by(Column::Id, Order::Asc)
+            .all(db)
+            .await?;
+
+        let mut result: HashMap<i32, Vec<String>> = HashMap::new();
+        for t in tags {
+            result.entry(t.problem_id).or_default().push(t.tag);
+        }
+        Ok(result)
+    }
+}
+
+/// Sub query selecting ids of problems carrying all of the tags.
+#[must_use]
+pub fn problem_ids_with_tags(tags: &[String]) -> SelectStatement {
+    let unique_tags = unique(tags);
+    let count = i64::try_from(unique_tags.len()).unwrap_or(i64::MAX);
+
+    Query::select()
+        .column(Column::ProblemId)
+        .from(Entity)
+        .and_where(Column::Tag.is_in(unique_tags.into_iter().cloned()))
+        .group_by_col(Column::ProblemId)
+        .and_having(Expr::col(Column::Tag).count_distinct().eq(count))
+        .to_owned()
+}
+
+impl ActiveModelBehavior for ActiveModel {
+    // extend activemodel below (keep comment for generators)
+}
@@ -1,6 +1,6 @@
 use crate::models::transform_db_error;
 
-pub use super::_entities::problem_tasks::{ActiveModel, Model};
+pub use super::_entities::problem_tasks::{ActiveModel, Column, Entity, Model};
 use loco_rs::model::ModelResult;
 use sea_orm::{entity::prelude::*, ActiveValue, IntoActiveModel, TransactionTrait};
 use serde::Deserialize;
@@ -35,6 +35,26 @@ impl Model {
         txn.commit().await?;
         Ok(tasks)
     }
+
+    /// Replace all tasks of a problem
+    ///
+    /// # Errors
+    ///
+    /// When there is DB error.
+    pub async fn set_for_problem<C: ConnectionTrait + TransactionTrait>(
+        db: &C,
+        problem_id: i32,
+        params: &[AddParams],
+    ) -> ModelResult<Vec<Self>> {
+        let txn = db.begin().await?;
+        Entity::delete_many()
+            .filter(Column::ProblemId.eq(problem_id))
+            .exec(&txn)
+            .await?;
+        let tasks = Self::add_many(&txn, problem_id, params).await?;
+        txn.commit().await?;
+        Ok(tasks)
+    }
 }
 
 impl ActiveModelBehavior for ActiveModel {
@@ -0,0 +1,266 @@
+//! Code templates of [`super::Type::FillInTemplate`] problems.
+//!
+//! A template is the complete source code of a language, where each blank is
+//! wrapped by [`BLANK_START`] and [`BLANK_END`], e.g. `int main() { {{@return 0;@}} }`.
+//! The code between the markers is the reference answer, which is redacted when
+//! showing the template to students. Submissions only contain the code of each
+//! blank, and are spliced into the template in order before being judged.
+
+use loco_rs::model::{ModelError, ModelResult};
+use sea_orm::{entity::prelude::*, ActiveValue, IntoActiveModel, Order, QueryOrder};
+
+pub use super::_entities::problem_templates::{ActiveModel, Column, Entity, Model};
+use crate::models::{transform_db_error, Language};
+
+pub const BLANK_START: &str = "{{@";
+pub const BLANK_END: &str = "@}}";
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum BadTemplate {
+    #[error("blank starting at line {0} is not closed")]
+    Unclosed(usize),
+    #[error("unexpected end of blank at line {0}")]
+    UnexpectedEnd(usize),
+    #[error("template should contain at least one blank")]
+    NoBlank,
+    #[error("expected {expected} blanks, found {found}")]
+    BlankCount { expected: usize, found: usize },
+}
+
+/// Template split by blanks, there is always one more fixed part than blanks.
+struct Parsed<'a> {
+    fixed: Vec<&'a str>,
+    blanks: Vec<&'a str>,
+}
+
+fn parse(template: &str) -> Result<Parsed<'_>, BadTemplate> {
+    let line_of = |offset: usize| template[..offset].matches('\n').count() + 1;
+
+    let mut fixed = vec![];
+    let mut blanks = vec![];
+    let mut offset = 0;
+    loop {
+        let rest = &template[offset..];
+        let Some(start) = rest.find(BLANK_START) else {
+            if let Some(end) = rest.find(BLANK_END) {
+                return Err(BadTemplate::UnexpectedEnd(line_of(offset + end)));
+            }
+            fixed.push(rest);
+            break;
+        };
+        if let Some(end) = rest[..start].find(BLANK_END) {
+            return Err(BadTemplate::UnexpectedEnd(line_of(offset + end)));
+        }
+
+        let content_start = start + BLANK_START.len();
+        let blank = rest[content_start..]
+            .find(BLANK_END)
+            .map(|len| &rest[content_start..content_start + len])
+            // blanks can not be nested
+            .filter(|blank| !blank.contains(BLANK_START))
+            .ok_or_else(|| BadTemplate::Unclosed(line_of(offset + start)))?;
+        fixed.push(&rest[..start]);
+        blanks.push(blank);
+        offset += content_start + blank.len() + BLANK_END.len();
+    }
+
+    if blanks.is_empty() {
+        return Err(BadTemplate::NoBlank);
+    }
+    Ok(Parsed { fixed, blanks })
+}
+
+/// Check the markers of a template and count its blanks.
+///
+/// # Errors
+///
+/// When the markers are unbalanced or there is no blank.
+pub fn validate(template: &str) -> Result<usize, BadTemplate> {
+    Ok(parse(template)?.blanks.len())
+}
+
+/// Remove reference answers in blanks, the markers are kept.
+///
+/// # Errors
+///
+/// When the template is invalid.
+pub fn redact(template: &str) -> Result<String, BadTemplate> {
+    Ok(parse(template)?
+        .fixed
+        .join(&format!("{BLANK_START}{BLANK_END}")))
+}
+
+/// Splice submitted blanks into the template to get the complete source code.
+///
+/// # Errors
+///
+/// When the template is invalid or the number of blanks does not match.
+pub fn fill(template: &str, blanks: &[String]) -> Result<String, BadTemplate> {
+    let parsed = parse(template)?;
+    if parsed.blanks.len() != blanks.len() {
+        return Err(BadTemplate::BlankCount {
+            expected: parsed.blanks.len(),
+            found: blanks.len(),
+        });
+    }
+
+    let mut code = String::with_capacity(template.len());
+    for (fixed, blank) in parsed.fixed.iter().zip(blanks) {
+        code.push_str(fixed);
+        code.push_str(blank);
+    }
+    // the last fixed part is not paired with a blank
+    code.push_str(parsed.fixed[blanks.len()]);
+    Ok(code)
+}
+
+impl Model {
+    /// Find templates of a problem ordered by language.
+    ///
+    /// # Errors
+    ///
+    /// When there is DB error.
+    pub async fn find_for_problem<C: ConnectionTrait>(
+        db: &C,
+        problem_id: i32,
+    ) -> ModelResult<Vec<Self>> {
+        let templates = Entity::find()
+            .filter(Column::ProblemId.eq(problem_id))
+            .order_by(Column::Language, Order::Asc)
+            .all(db)
+            .await?;
+
+        Ok(templates)
+    }
+
+    /// Find the template of a problem in given language.
+    ///
+    /// # Errors
+    ///
+    /// - When there is DB error
+    /// - When the problem has no template in the language
+    pub async fn find_by_language<C: ConnectionTrait>(
+        db: &C,
+        problem_id: i32,
+        language: Language,
+    ) -> ModelResult<Self> {
+        Entity::find()
+            .filter(Column::ProblemId.eq(problem_id))
+            .filter(Column::Language.eq(language as i32))
+            .one(db)
+            .await
+            .map_err(transform_db_error)?
+            .ok_or(ModelError::EntityNotFound)
+    }
+
+    /// Create or replace the template of a problem in given language. The
+    /// content should be checked by [`validate`] first.
+    ///
+    /// # Errors
+    ///
+    /// When there is DB error.
+    pub async fn set<C: ConnectionTrait>(
+        db: &C,
+        problem_id: i32,
+        language: Language,
+        content: String,
+    ) -> ModelResult<Self> {
+        let template = match Self::find_by_language(db, problem_id, language).await {
+            Ok(template) => {
+                let mut template = template.into_active_model();
+                template.content = ActiveValue::set(content);
+                template.update(db).await
+            }
+            Err(ModelError::EntityNotFound) => {
+                ActiveModel {
+                    problem_id: ActiveValue::set(problem_id),
+                    language: ActiveValue::set(language as i32),
+                    content: ActiveValue::set(content),
+                    ..Default::default()
+                }
+                .insert(db)
+                .await
+            }
+            Err(e) => return Err(e),
+        };
+
+        template.map_err(transform_db_error)
+    }
+
+    /// Remove the template of a problem in given language.
+    ///
+    /// # Errors
+    ///
+    /// - When there is DB error
+    /// - When the problem has no template in the language
+    pub async fn remove<C: ConnectionTrait>(
+        db: &C,
+        problem_id: i32,
+        language: Language,
+    ) -> ModelResult<()> {
+        let result = Entity::delete_many()
+            .filter(Column::ProblemId.eq(problem_id))
+            .filter(Column::Language.eq(language as i32))
+            .exec(db)
+            .await?;
+        if result.rows_affected == 0 {
+            return Err(ModelError::EntityNotFound);
+        }
+
+        Ok(())
+    }
+
+    /// Language of the template, `None` if the value is unknown.
+    #[must_use]
+    pub fn language(&self) -> Option<Language> {
+        use num_traits::FromPrimitive;
+
+        Language::from_i32(self.language)
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {
+    // extend activemodel below (keep comment for generators)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill_and_redact() {
+        let template = "a = {{@1@}}\nb = {{@ 2 @}}\nprint(a + b)\n";
+        assert_eq!(validate(template), Ok(2));
+        assert_eq!(
+            redact(template).unwrap(),
+            "a = {{@@}}\nb = {{@@}}\nprint(a + b)\n"
+        );
+        assert_eq!(
+            fill(template, &["3".to_string(), "4".to_string()]).unwrap(),
+            "a = 3\nb = 4\nprint(a + b)\n"
+        );
+        assert_eq!(
+            fill(template, &["3".to_string()]),
+            Err(BadTemplate::BlankCount {
+                expected: 2,
+                found: 1
+            })
+        );
+        assert_eq!(fill("{{@@}}", &[String::new()]).unwrap(), "");
+    }
+
+    #[test]
+    fn test_invalid_template() {
+        assert_eq!(validate("print(1)"), Err(BadTemplate::NoBlank));
+        assert_eq!(validate("a\n{{@ 1"), Err(BadTemplate::Unclosed(2)));
+        assert_eq!(validate("{{@ {{@ 1 @}} @}}"), Err(BadTemplate::Unclosed(1)));
+        assert_eq!(
+            validate("{{@1@}}\n2 @}}"),
+            Err(BadTemplate::UnexpectedEnd(2))
+        );
+        assert_eq!(
+            validate("1 @}}\n{{@2@}}"),
+            Err(BadTemplate::UnexpectedEnd(1))
+        );
+    }
+}
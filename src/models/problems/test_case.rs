@@ -1 +1,618 @@
 // Problem test case
+
+use std::{
+    collections::HashSet,
+    io::{self, Read, Seek, Write},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{tasks, Type};
+use crate::models::Language;
+
+/// Name of the optional manifest at the root of test case zip
+pub const META_FILE: &str = "meta.json";
+/// Max size of [`META_FILE`] in bytes
+const META_MAX_SIZE: u64 = 64 * 1024;
+/// Source files of the interactor accepted at the root of test case zip of
+/// interactive problems, with their languages
+pub const INTERACTOR_FILES: [(&str, Language); 3] = [
+    ("interactor.c", Language::C),
+    ("interactor.cpp", Language::Cpp),
+    ("interactor.py", Language::Python),
+];
+/// Max count of tasks, and of cases in a task, since both are numbered with two
+/// digits in the zip
+pub const MAX_CASE_COUNT: usize = 100;
+/// Compression ratio is only checked for files larger than this, since small
+/// files can have a high ratio without harm.
+const RATIO_CHECK_MIN_SIZE: u64 = 64 * 1024;
+
+/// Line ending policy of STDOUT files
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LineEnding {
+    /// Do not check line endings
+    Any,
+    /// Only LF is allowed
+    Lf,
+    /// Either LF or CRLF is allowed, but should not be mixed in a file
+    #[default]
+    Consistent,
+}
+
+/// Limits applied when validating uploaded test cases, read from
+/// `settings.test_case` in the config file.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct Limits {
+    /// Max total uncompressed size of files in bytes
+    pub max_total_size: u64,
+    /// Max uncompressed size of a single file in bytes
+    pub max_file_size: u64,
+    /// Max ratio of uncompressed size to compressed size of a file
+    pub max_compression_ratio: u64,
+    /// Reject STDOUT files which are not valid UTF-8
+    pub require_utf8_output: bool,
+    pub line_ending: LineEnding,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_total_size: 512 * 1024 * 1024,
+            max_file_size: 128 * 1024 * 1024,
+            max_compression_ratio: 100,
+            require_utf8_output: true,
+            line_ending: LineEnding::default(),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum BadTestCase {
+    #[error("error reading zip file: {0}")]
+    ZipError(#[from] zip::result::ZipError),
+    #[error("invalid meta.json: {0}")]
+    InvalidMeta(#[from] serde_json::Error),
+    #[error("error reading file: {0}")]
+    Io(#[from] io::Error),
+    #[error("{0}")]
+    Custom(String),
+}
+
+/// Task declared in `meta.json`, the format is compatible with Normal-OJ.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskMeta {
+    pub case_count: i32,
+    pub task_score: i32,
+    /// Memory limit in KB
+    pub memory_limit: i32,
+    /// CPU time limit in ms
+    pub time_limit: i32,
+}
+
+impl From<&TaskMeta> for tasks::AddParams {
+    fn from(task: &TaskMeta) -> Self {
+        Self {
+            test_case_count: task.case_count,
+            score: task.task_score,
+            time_limit: task.time_limit,
+            memory_limit: task.memory_limit,
+        }
+    }
+}
+
+/// Content of `meta.json`
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Meta {
+    pub tasks: Vec<TaskMeta>,
+}
+
+impl Meta {
+    /// Test case count of each task
+    #[must_use]
+    pub fn case_counts(&self) -> Vec<i32> {
+        self.tasks.iter().map(|t| t.case_count).collect()
+    }
+
+    fn validate(&self) -> Result<(), BadTestCase> {
+        if self.tasks.len() > MAX_CASE_COUNT {
+            return Err(BadTestCase::Custom(format!(
+                "too many tasks in {META_FILE}, at most {MAX_CASE_COUNT} are allowed"
+            )));
+        }
+        self.tasks
+            .iter()
+            .position(|t| {
+                usize::try_from(t.case_count).map_or(true, |c| c > MAX_CASE_COUNT)
+                    || t.task_score < 0
+                    || t.memory_limit <= 0
+                    || t.time_limit <= 0
+            })
+            .map_or(Ok(()), |i| {
+                Err(BadTestCase::Custom(format!(
+                    "task {i} in {META_FILE} has invalid case count, score or limits"
+                )))
+            })
+    }
+}
+
+/// Parse `meta.json` of the test case zip, returns `None` if it does not exist.
+///
+/// # Errors
+///
+/// - When the given binary is not a zip file
+/// - When `meta.json` is malformed
+pub fn read_meta<R: Read + Seek>(test_case: R) -> Result<Option<Meta>, BadTestCase> {
+    let mut zipfile = zip::ZipArchive::new(test_case)?;
+    let mut file = match zipfile.by_name(META_FILE) {
+        Ok(f) => f,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let mut content = vec![];
+    (&mut file)
+        .take(META_MAX_SIZE + 1)
+        .read_to_end(&mut content)?;
+    if content.len() as u64 > META_MAX_SIZE {
+        return Err(BadTestCase::Custom(format!("{META_FILE} is too large")));
+    }
+    let meta: Meta = serde_json::from_slice(&content)?;
+    meta.validate()?;
+
+    Ok(Some(meta))
+}
+
+/// Validate files inside test case zip.
+///
+/// Each task `i` should have `case_counts[i]` cases, and each case `j` is stored
+/// at `test-case/{i:02}{j:02}/STDIN` and `test-case/{i:02}{j:02}/STDOUT`. Besides
+/// the cases, only [`META_FILE`] is allowed. Cases of interactive problems only
+/// have `STDIN`, and exactly one of [`INTERACTOR_FILES`] is required. File sizes
+/// are checked by actually decompressing them, the sizes declared in the zip are
+/// not trusted.
+///
+/// # Errors
+///
+/// - When the given binary is not a zip file
+/// - When the zip file contains invalid files
+/// - When there is missing/extra files inside zip
+/// - When files exceed the limits
+pub fn validate<R: Read + Seek>(
+    test_case: R,
+    case_counts: &[i32],
+    problem_type: Type,
+    limits: &Limits,
+) -> Result<(), BadTestCase> {
+    let mut zipfile = zip::ZipArchive::new(test_case)?;
+    let mut total_size = 0u64;
+
+    let interactive = problem_type == Type::Interactive;
+    let case_files: &[&str] = if interactive {
+        &["STDIN"]
+    } else {
+        &["STDIN", "STDOUT"]
+    };
+    // check counts before building the expected file names
+    let case_counts = check_case_counts(case_counts, case_files.len(), zipfile.len())?;
+    let mut expected_input_output = case_counts
+        .iter()
+        .enumerate()
+        .flat_map(|(i, &count)| {
+            (0..count).flat_map(move |j| {
+                case_files
+                    .iter()
+                    .map(move |f| format!("test-case/{i:02}{j:02}/{f}"))
+            })
+        })
+        .collect::<HashSet<_>>();
+    let mut interactor = None;
+
+    for i in 0..zipfile.len() {
+        let mut file = zipfile.by_index(i)?;
+        if file.is_symlink() {
+            return Err(BadTestCase::Custom(format!(
+                "symlink is not allowed: {}",
+                file.name()
+            )));
+        }
+        // skip directory for now
+        if file.is_dir() {
+            continue;
+        }
+        let name = file.enclosed_name().ok_or_else(|| {
+            BadTestCase::Custom(format!("invalid path found in zip file: {}", file.name()))
+        })?;
+        let name = name.to_str().ok_or_else(|| {
+            BadTestCase::Custom(format!(
+                "invalid path found in zip file (maybe non-UTF8 path?): {}",
+                file.name()
+            ))
+        })?;
+
+        let name = name.to_string();
+
+        let is_interactor = interactive && interactor_language(&name).is_some();
+        if is_interactor && interactor.replace(name.clone()).is_some() {
+            return Err(BadTestCase::Custom(format!(
+                "more than one interactor found: {name}"
+            )));
+        }
+        if name != META_FILE && !is_interactor && !expected_input_output.remove(&name) {
+            return Err(BadTestCase::Custom(format!(
+                "duplicated or extra file found: {}",
+                file.name()
+            )));
+        }
+
+        if file.size() > limits.max_file_size {
+            return Err(BadTestCase::Custom(format!("file is too large: {name}")));
+        }
+        let compressed_size = file.compressed_size();
+        let mut reader = (&mut file).take(limits.max_file_size + 1);
+        let size = if name.ends_with("/STDOUT") {
+            let mut checker = OutputChecker::new(limits);
+            let size = io::copy(&mut reader, &mut checker)?;
+            checker.finish(&name)?;
+            size
+        } else {
+            io::copy(&mut reader, &mut io::sink())?
+        };
+        if size > limits.max_file_size {
+            return Err(BadTestCase::Custom(format!("file is too large: {name}")));
+        }
+        if size > RATIO_CHECK_MIN_SIZE
+            && size / compressed_size.max(1) > limits.max_compression_ratio
+        {
+            return Err(BadTestCase::Custom(format!(
+                "compression ratio of file is too high: {name}"
+            )));
+        }
+        total_size += size;
+        if total_size > limits.max_total_size {
+            return Err(BadTestCase::Custom(
+                "total size of test case is too large".to_string(),
+            ));
+        }
+    }
+
+    if interactive && interactor.is_none() {
+        return Err(BadTestCase::Custom(format!(
+            "interactor is required, expected one of: {}",
+            INTERACTOR_FILES.map(|(f, _)| f).join(",")
+        )));
+    }
+    if !expected_input_output.is_empty() {
+        return Err(BadTestCase::Custom(format!(
+            "missing files: {}",
+            expected_input_output
+                .into_iter()
+                .collect::<Vec<_>>()
+                .join(",")
+        )));
+    }
+
+    Ok(())
+}
+
+/// Check that case counts can be named in the zip, and the zip has enough entries
+/// for `files_per_case` files of each case.
+fn check_case_counts(
+    case_counts: &[i32],
+    files_per_case: usize,
+    entry_count: usize,
+) -> Result<Vec<usize>, BadTestCase> {
+    let case_counts = case_counts
+        .iter()
+        .map(|&c| usize::try_from(c).ok().filter(|c| *c <= MAX_CASE_COUNT))
+        .collect::<Option<Vec<_>>>()
+        .filter(|c| c.len() <= MAX_CASE_COUNT)
+        .ok_or_else(|| {
+            BadTestCase::Custom(format!(
+                "at most {MAX_CASE_COUNT} tasks with at most {MAX_CASE_COUNT} cases are allowed"
+            ))
+        })?;
+    let expected_count = case_counts.iter().sum::<usize>() * files_per_case;
+    if expected_count > entry_count {
+        return Err(BadTestCase::Custom(format!(
+            "missing files, expected {expected_count} case files but the zip only has {entry_count} entries"
+        )));
+    }
+
+    Ok(case_counts)
+}
+
+/// Language of the interactor by its path in test case zip, `None` if the file
+/// is not an interactor.
+#[must_use]
+pub fn interactor_language(name: &str) -> Option<Language> {
+    INTERACTOR_FILES
+        .iter()
+        .find(|(f, _)| *f == name)
+        .map(|(_, language)| *language)
+}
+
+/// Check encoding and line endings of expected output while it is written, so
+/// the output does not have to be loaded into memory.
+struct OutputChecker<'a> {
+    limits: &'a Limits,
+    /// Trailing bytes of an incomplete UTF-8 character in the last write
+    pending: Vec<u8>,
+    utf8: bool,
+    last_byte: Option<u8>,
+    crlf: bool,
+    lf: bool,
+}
+
+impl<'a> OutputChecker<'a> {
+    const fn new(limits: &'a Limits) -> Self {
+        Self {
+            limits,
+            pending: vec![],
+            utf8: true,
+            last_byte: None,
+            crlf: false,
+            lf: false,
+        }
+    }
+
+    fn check_utf8(&mut self, buf: &[u8]) {
+        let mut pending = std::mem::take(&mut self.pending);
+        let content = if pending.is_empty() {
+            buf
+        } else {
+            pending.extend_from_slice(buf);
+            &pending
+        };
+        match std::str::from_utf8(content) {
+            Ok(_) => {}
+            // the character may be completed by the next write
+            Err(e) if e.error_len().is_none() => {
+                self.pending = content[e.valid_up_to()..].to_vec();
+            }
+            Err(_) => self.utf8 = false,
+        }
+    }
+
+    fn finish(self, name: &str) -> Result<(), BadTestCase> {
+        if self.limits.require_utf8_output && (!self.utf8 || !self.pending.is_empty()) {
+            return Err(BadTestCase::Custom(format!("{name} is not valid UTF-8")));
+        }
+
+        match self.limits.line_ending {
+            LineEnding::Lf if self.crlf => Err(BadTestCase::Custom(format!(
+                "{name} contains CRLF line endings"
+            ))),
+            LineEnding::Consistent if self.crlf && self.lf => Err(BadTestCase::Custom(format!(
+                "{name} mixes CRLF and LF line endings"
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Write for OutputChecker<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.limits.require_utf8_output && self.utf8 {
+            self.check_utf8(buf);
+        }
+        for (i, _) in buf.iter().enumerate().filter(|(_, &b)| b == b'\n') {
+            let prev = if i > 0 {
+                Some(buf[i - 1])
+            } else {
+                self.last_byte
+            };
+            if prev == Some(b'\r') {
+                self.crlf = true;
+            } else {
+                self.lf = true;
+            }
+        }
+        if let Some(&b) = buf.last() {
+            self.last_byte = Some(b);
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use zip::{write::SimpleFileOptions, CompressionMethod};
+
+    use super::*;
+
+    fn limits() -> Limits {
+        Limits {
+            max_total_size: 4096,
+            max_file_size: 2048,
+            max_compression_ratio: 10,
+            ..Default::default()
+        }
+    }
+
+    fn make_zip(stdin: &[u8], stdout: &[u8], method: CompressionMethod) -> Cursor<Vec<u8>> {
+        let mut buf = Cursor::new(Vec::new());
+        {
+            let mut zip = zip::ZipWriter::new(&mut buf);
+            let opt = SimpleFileOptions::default().compression_method(method);
+            zip.start_file("test-case/0000/STDIN", opt).unwrap();
+            zip.write_all(stdin).unwrap();
+            zip.start_file("test-case/0000/STDOUT", opt).unwrap();
+            zip.write_all(stdout).unwrap();
+        }
+        buf.set_position(0);
+        buf
+    }
+
+    fn error_of(zip: Cursor<Vec<u8>>, limits: &Limits) -> Option<String> {
+        validate(zip, &[1], Type::Normal, limits)
+            .err()
+            .map(|e| e.to_string())
+    }
+
+    #[test]
+    fn test_validate_sizes() {
+        let stored = CompressionMethod::Stored;
+        assert_eq!(error_of(make_zip(b"1", b"1\n", stored), &limits()), None);
+        assert_eq!(
+            error_of(make_zip(&[b'1'; 2049], b"1\n", stored), &limits()),
+            Some("file is too large: test-case/0000/STDIN".to_string())
+        );
+        let total = Limits {
+            max_total_size: 3000,
+            ..limits()
+        };
+        assert_eq!(
+            error_of(make_zip(&[b'1'; 2000], &[b'1'; 2000], stored), &total),
+            Some("total size of test case is too large".to_string())
+        );
+
+        let ratio = Limits {
+            max_file_size: 1024 * 1024,
+            max_total_size: 1024 * 1024,
+            ..limits()
+        };
+        assert_eq!(
+            error_of(
+                make_zip(&[b'1'; 128 * 1024], b"1\n", CompressionMethod::Deflated),
+                &ratio
+            ),
+            Some("compression ratio of file is too high: test-case/0000/STDIN".to_string())
+        );
+    }
+
+    #[test]
+    fn test_validate_output() {
+        let stored = CompressionMethod::Stored;
+        let lf_only = Limits {
+            line_ending: LineEnding::Lf,
+            ..limits()
+        };
+        let any = Limits {
+            line_ending: LineEnding::Any,
+            require_utf8_output: false,
+            ..limits()
+        };
+        for (stdout, consistent_ok, lf_ok) in [
+            (&b"1\n2\n"[..], true, true),
+            (b"1\r\n2\r\n", true, false),
+            (b"1\r\n2\n", false, false),
+            (b"\xff\n", false, false),
+        ] {
+            assert_eq!(
+                error_of(make_zip(b"", stdout, stored), &limits()).is_none(),
+                consistent_ok
+            );
+            assert_eq!(
+                error_of(make_zip(b"", stdout, stored), &lf_only).is_none(),
+                lf_ok
+            );
+            assert_eq!(error_of(make_zip(b"", stdout, stored), &any), None);
+        }
+    }
+
+    #[test]
+    fn test_output_checker_across_writes() {
+        let check = |chunks: &[&[u8]]| {
+            let limits = limits();
+            let mut checker = OutputChecker::new(&limits);
+            for chunk in chunks {
+                checker.write_all(chunk).unwrap();
+            }
+            checker.finish("STDOUT").err().map(|e| e.to_string())
+        };
+        // "é" is split between writes
+        assert_eq!(check(&[b"\xc3", b"\xa9\n"]), None);
+        assert_eq!(
+            check(&[b"1\n\xc3"]),
+            Some("STDOUT is not valid UTF-8".to_string())
+        );
+        assert_eq!(
+            check(&[b"1\r", b"\n2\n"]),
+            Some("STDOUT mixes CRLF and LF line endings".to_string())
+        );
+    }
+
+    #[test]
+    fn test_validate_counts() {
+        let zip = || make_zip(b"1", b"1\n", CompressionMethod::Stored);
+        let error = |case_counts: &[i32]| {
+            validate(zip(), case_counts, Type::Normal, &limits())
+                .err()
+                .map(|e| e.to_string())
+        };
+        assert_eq!(error(&[1]), None);
+        assert!(error(&[1_000_000_000]).unwrap().starts_with("at most 100"));
+        assert!(error(&[1; 101]).unwrap().starts_with("at most 100"));
+        assert!(error(&[100]).unwrap().starts_with("missing files"));
+
+        let meta = |case_count, task_count| Meta {
+            tasks: vec![
+                TaskMeta {
+                    case_count,
+                    task_score: 0,
+                    memory_limit: 1,
+                    time_limit: 1,
+                };
+                task_count
+            ],
+        };
+        assert!(meta(100, 100).validate().is_ok());
+        assert!(meta(101, 1).validate().is_err());
+        assert!(meta(1, 101).validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_interactive() {
+        let build = |files: &[&str]| {
+            let mut buf = Cursor::new(Vec::new());
+            {
+                let mut zip = zip::ZipWriter::new(&mut buf);
+                for name in files {
+                    zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+                    zip.write_all(b"1\n").unwrap();
+                }
+            }
+            buf.set_position(0);
+            buf
+        };
+        let validate_as = |files: &[&str], problem_type| {
+            validate(build(files), &[1], problem_type, &limits())
+                .err()
+                .map(|e| e.to_string())
+        };
+
+        let stdin = "test-case/0000/STDIN";
+        let stdout = "test-case/0000/STDOUT";
+        assert_eq!(
+            validate_as(&[stdin, "interactor.py"], Type::Interactive),
+            None
+        );
+        assert_eq!(
+            validate_as(&[stdin, stdout, "interactor.c"], Type::Interactive),
+            Some(format!("duplicated or extra file found: {stdout}"))
+        );
+        assert_eq!(
+            validate_as(&[stdin, "interactor.c", "interactor.py"], Type::Interactive),
+            Some("more than one interactor found: interactor.py".to_string())
+        );
+        assert!(validate_as(&[stdin], Type::Interactive)
+            .unwrap()
+            .starts_with("interactor is required"));
+        // interactors are not expected by other problems
+        assert_eq!(
+            validate_as(&[stdin, stdout, "interactor.py"], Type::Normal),
+            Some("duplicated or extra file found: interactor.py".to_string())
+        );
+    }
+}
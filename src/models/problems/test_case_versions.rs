@@ -0,0 +1,169 @@
+use std::path::PathBuf;
+
+use loco_rs::model::{ModelError, ModelResult};
+use sea_orm::{entity::prelude::*, ActiveValue, Order, QueryOrder, QuerySelect};
+
+pub use super::_entities::problem_test_case_versions::{ActiveModel, Column, Entity, Model};
+use super::{_entities, test_case_path};
+use crate::models::transform_db_error;
+
+#[derive(Debug)]
+pub struct AddParams {
+    pub problem_id: i32,
+    pub uploader_id: i32,
+    /// id of test case zip stored in app's storage, see [`test_case_path`]
+    pub test_case_id: String,
+    /// Size of the zip in bytes
+    pub size: i64,
+    /// Hex encoded SHA-256 digest of the zip
+    pub sha256: String,
+}
+
+impl Model {
+    /// Record an uploaded test case of a problem.
+    ///
+    /// # Errors
+    ///
+    /// When could not save the version into DB
+    pub async fn add<C: ConnectionTrait>(db: &C, params: &AddParams) -> ModelResult<Self> {
+        let version = ActiveModel {
+            problem_id: ActiveValue::set(params.problem_id),
+            uploader_id: ActiveValue::set(params.uploader_id),
+            test_case_id: ActiveValue::set(params.test_case_id.clone()),
+            size: ActiveValue::set(params.size),
+            sha256: ActiveValue::set(params.sha256.clone()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .map_err(transform_db_error)?;
+
+        Ok(version)
+    }
+
+    /// Find a version of the problem by its primary id
+    ///
+    /// # Errors
+    ///
+    /// - When cloud not query the version from DB
+    /// - When the version does not exist or belongs to another problem
+    pub async fn find_in_problem<C: ConnectionTrait>(
+        db: &C,
+        problem_id: i32,
+        id: i32,
+    ) -> ModelResult<Self> {
+        Entity::find_by_id(id)
+            .filter(Column::ProblemId.eq(problem_id))
+            .one(db)
+            .await
+            .map_err(transform_db_error)?
+            .ok_or(ModelError::EntityNotFound)
+    }
+
+    /// Find the version of an uploaded test case. Test cases uploaded before
+    /// versions were recorded do not have one.
+    ///
+    /// # Errors
+    ///
+    /// When cloud not query the version from DB
+    pub async fn find_by_test_case_id<C: ConnectionTrait>(
+        db: &C,
+        test_case_id: &str,
+    ) -> ModelResult<Option<Self>> {
+        Ok(Entity::find()
+            .filter(Column::TestCaseId.eq(test_case_id))
+            .one(db)
+            .await?)
+    }
+
+    /// List versions of a problem with their uploader, the latest one comes first.
+    ///
+    /// # Errors
+    ///
+    /// When cloud not query versions from DB
+    pub async fn list_for_problem<C: ConnectionTrait>(
+        db: &C,
+        problem_id: i32,
+    ) -> ModelResult<Vec<(Self, _entities::users::Model)>> {
+        let versions = Entity::find()
+            .find_also_related(_entities::users::Entity)
+            .filter(Column::ProblemId.eq(problem_id))
+            .order_by(Column::Id, Order::Desc)
+            .all(db)
+            .await?
+            .into_iter()
+            // uploader should always exist because of the foreign key
+            .filter_map(|(v, u)| u.map(|u| (v, u)))
+            .collect();
+
+        Ok(versions)
+    }
+
+    /// Paths of every version of a problem in app's storage
+    ///
+    /// # Errors
+    ///
+    /// When cloud not query versions from DB
+    pub async fn paths_of_problem<C: ConnectionTrait>(
+        db: &C,
+        problem_id: i32,
+    ) -> ModelResult<Vec<PathBuf>> {
+        let ids = Entity::find()
+            .select_only()
+            .column(Column::TestCaseId)
+            .filter(Column::ProblemId.eq(problem_id))
+            .into_tuple::<String>()
+            .all(db)
+            .await?;
+
+        Ok(ids.iter().map(|id| test_case_path(id)).collect())
+    }
+
+    /// Remove versions of the problem which are neither in use nor referenced by
+    /// any submission. Returns paths of their zips in app's storage, which should
+    /// be removed by the caller.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error.
+    pub async fn remove_unreferenced<C: ConnectionTrait>(
+        db: &C,
+        problem: &_entities::problems::Model,
+    ) -> ModelResult<Vec<PathBuf>> {
+        use _entities::submissions;
+        use sea_orm::sea_query::Query;
+
+        let mut q = Entity::find()
+            .filter(Column::ProblemId.eq(problem.id))
+            .filter(
+                Column::Id.not_in_subquery(
+                    Query::select()
+                        .column(submissions::Column::TestCaseVersionId)
+                        .from(submissions::Entity)
+                        .and_where(submissions::Column::TestCaseVersionId.is_not_null())
+                        .to_owned(),
+                ),
+            );
+        if let Some(current) = &problem.test_case_id {
+            q = q.filter(Column::TestCaseId.ne(current.as_str()));
+        }
+        let versions = q.all(db).await?;
+        if versions.is_empty() {
+            return Ok(vec![]);
+        }
+
+        Entity::delete_many()
+            .filter(Column::Id.is_in(versions.iter().map(|v| v.id)))
+            .exec(db)
+            .await?;
+
+        Ok(versions
+            .iter()
+            .map(|v| test_case_path(&v.test_case_id))
+            .collect())
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {
+    // extend activemodel below (keep comment for generators)
+}
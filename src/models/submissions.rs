@@ -0,0 +1,615 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use loco_rs::model::{ModelError, ModelResult};
+use num_derive::FromPrimitive;
+use sea_orm::{entity::prelude::*, ActiveValue, FromQueryResult, Order, QueryOrder, QuerySelect};
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+
+pub use super::_entities::submissions::{ActiveModel, Entity, Model};
+use super::{
+    _entities::{self, prelude::Submissions, problem_tasks, submissions},
+    transform_db_error, Language,
+};
+
+/// Judge result of a submission, the values are compatible with Normal-OJ.
+#[derive(Clone, Copy, Debug, Serialize_repr, Deserialize_repr, PartialEq, Eq, FromPrimitive)]
+#[repr(i8)]
+pub enum Status {
+    Pending = -1,
+    Accepted = 0,
+    WrongAnswer = 1,
+    CompileError = 2,
+    TimeLimitExceed = 3,
+    MemoryLimitExceed = 4,
+    RuntimeError = 5,
+    JudgeError = 6,
+    OutputLimitExceed = 7,
+}
+
+/// Judge result of a single test case
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CaseResult {
+    pub status: Status,
+    /// CPU time in ms
+    pub exec_time: i32,
+    /// Max resident set size in KB
+    pub memory_usage: i32,
+    /// Truncated stderr of the program, or compiler message if compile failed
+    pub stderr: String,
+    /// Percentage of credit given to a wrong answer by the custom checker
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partial_score: Option<i32>,
+}
+
+impl CaseResult {
+    /// Percentage of credit of this case, 100 for accepted cases.
+    #[must_use]
+    pub fn credit(&self) -> i32 {
+        if self.status == Status::Accepted {
+            100
+        } else {
+            self.partial_score.unwrap_or(0).clamp(0, 100)
+        }
+    }
+}
+
+/// Judge result of a problem task
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TaskResult {
+    pub status: Status,
+    /// Awarded score, full task score only if every case is accepted
+    #[serde(default)]
+    pub score: i32,
+    pub exec_time: i32,
+    pub memory_usage: i32,
+    pub cases: Vec<CaseResult>,
+}
+
+impl TaskResult {
+    /// Aggregate case results. The task status is the first non-AC case status,
+    /// score is not awarded until [`Self::grade`] is called.
+    #[must_use]
+    pub fn from_cases(cases: Vec<CaseResult>) -> Self {
+        let status = cases
+            .iter()
+            .map(|c| c.status)
+            .find(|s| *s != Status::Accepted)
+            .unwrap_or(Status::Accepted);
+        Self {
+            status,
+            score: 0,
+            exec_time: cases.iter().map(|c| c.exec_time).max().unwrap_or(-1),
+            memory_usage: cases.iter().map(|c| c.memory_usage).max().unwrap_or(-1),
+            cases,
+        }
+    }
+
+    /// Award the task score if all of its cases are accepted. Cases given partial
+    /// credit by a custom checker award part of the score, and the task takes the
+    /// least credit among its cases.
+    pub fn grade(&mut self, task: &problem_tasks::Model) {
+        let complete = usize::try_from(task.test_case_count).is_ok_and(|n| n == self.cases.len());
+        let credit = if self.status == Status::Accepted {
+            100
+        } else {
+            self.cases.iter().map(CaseResult::credit).min().unwrap_or(0)
+        };
+        self.score = if complete {
+            task.score * credit / 100
+        } else {
+            0
+        };
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {
+    // extend activemodel below (keep comment for generators)
+}
+
+/// Path of submission source code inside app's storage.
+#[must_use]
+pub fn code_path(code_id: &str) -> PathBuf {
+    PathBuf::from("submission").join(code_id)
+}
+
+#[derive(Debug)]
+pub struct AddParams {
+    pub user: _entities::users::Model,
+    pub problem: _entities::problems::Model,
+    pub language: Language,
+    /// id of source code stored in app's storage, see [`code_path`]
+    pub code_id: String,
+}
+
+/// Content types of files accepted by handwritten problems
+pub const HANDWRITTEN_CONTENT_TYPES: [&str; 3] = ["application/pdf", "image/png", "image/jpeg"];
+
+/// Max score given to handwritten submissions by graders
+pub const MAX_GRADE: i32 = 100;
+
+#[derive(Debug)]
+pub struct AddHandwrittenParams {
+    pub user: _entities::users::Model,
+    pub problem: _entities::problems::Model,
+    /// id of the uploaded file stored in app's storage, see [`code_path`]
+    pub code_id: String,
+    /// One of [`HANDWRITTEN_CONTENT_TYPES`]
+    pub content_type: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListParams {
+    pub viewer: _entities::users::Model,
+    pub offset: Option<u64>,
+    pub count: Option<u64>,
+    pub username: Option<String>,
+    pub problem_id: Option<i32>,
+    pub status: Option<Status>,
+    pub language: Option<Language>,
+    pub course: Option<String>,
+}
+
+/// Submission statistics of a problem
+#[derive(Debug, Default, Clone, Copy, FromQueryResult)]
+pub struct ProblemStats {
+    pub problem_id: i32,
+    /// Count of all submissions
+    pub submit_count: i64,
+    /// Count of distinct users who have submitted
+    pub submitter: i64,
+    /// Count of distinct users who have at least one accepted submission
+    pub ac_user: i64,
+}
+
+impl Model {
+    /// Create a pending submission. The source code should be uploaded to storage before.
+    ///
+    /// # Errors
+    ///
+    /// When could not save the submission into DB
+    pub async fn add<C: ConnectionTrait>(db: &C, params: &AddParams) -> ModelResult<Self> {
+        let submission = ActiveModel {
+            user_id: ActiveValue::set(params.user.id),
+            problem_id: ActiveValue::set(params.problem.id),
+            language: ActiveValue::set(Some(params.language as i32)),
+            code_id: ActiveValue::set(params.code_id.clone()),
+            status: ActiveValue::set(Status::Pending as i32),
+            // compared with homework and contest windows set by the app clock
+            created_at: ActiveValue::set(super::now()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .map_err(transform_db_error)?;
+
+        Ok(submission)
+    }
+
+    /// Create a handwritten submission, which is pending until graded by
+    /// [`ActiveModel::grade`]. The file should be uploaded to storage before.
+    ///
+    /// # Errors
+    ///
+    /// When could not save the submission into DB
+    pub async fn add_handwritten<C: ConnectionTrait>(
+        db: &C,
+        params: &AddHandwrittenParams,
+    ) -> ModelResult<Self> {
+        let submission = ActiveModel {
+            user_id: ActiveValue::set(params.user.id),
+            problem_id: ActiveValue::set(params.problem.id),
+            language: ActiveValue::set(None),
+            code_id: ActiveValue::set(params.code_id.clone()),
+            content_type: ActiveValue::set(Some(params.content_type.clone())),
+            status: ActiveValue::set(Status::Pending as i32),
+            // compared with homework and contest windows set by the app clock
+            created_at: ActiveValue::set(super::now()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .map_err(transform_db_error)?;
+
+        Ok(submission)
+    }
+
+    /// Find a submission by its primary id
+    ///
+    /// # Errors
+    ///
+    /// - When cloud not query submission from DB
+    /// - When the submission with id does not exist
+    pub async fn find_by_id<C: ConnectionTrait>(db: &C, id: i32) -> ModelResult<Self> {
+        let s = Submissions::find_by_id(id)
+            .one(db)
+            .await
+            .map_err(transform_db_error)?;
+        s.ok_or(ModelError::EntityNotFound)
+    }
+
+    /// Parse judge result of each task, returns empty list if not judged yet.
+    #[must_use]
+    pub fn task_results(&self) -> Vec<TaskResult> {
+        self.tasks
+            .clone()
+            .and_then(|t| serde_json::from_value(t).ok())
+            .unwrap_or_default()
+    }
+
+    /// Language of the source code, `None` for handwritten submissions.
+    #[must_use]
+    pub fn language(&self) -> Option<Language> {
+        use num_traits::FromPrimitive;
+
+        self.language.and_then(Language::from_i32)
+    }
+
+    /// Whether this is a handwritten submission, which is graded manually
+    /// instead of being judged.
+    #[must_use]
+    pub const fn is_handwritten(&self) -> bool {
+        self.content_type.is_some()
+    }
+
+    /// Whether the user can grade this submission. Problem managers, teachers and
+    /// TAs of courses the problem is assigned to are allowed.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error.
+    pub async fn is_gradable_by<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        user: &_entities::users::Model,
+    ) -> ModelResult<bool> {
+        use super::{course_members::CourseRole, problems::courses::problem_ids_in_courses_of};
+
+        let problem = super::problems::Model::find_by_id(db, self.problem_id).await?;
+        if problem.is_managed_by(user) {
+            return Ok(true);
+        }
+        let count = _entities::problems::Entity::find_by_id(self.problem_id)
+            .filter(
+                _entities::problems::Column::Id.in_subquery(problem_ids_in_courses_of(
+                    user.id,
+                    &[CourseRole::Teacher, CourseRole::Ta],
+                )),
+            )
+            .count(db)
+            .await?;
+
+        Ok(count > 0)
+    }
+
+    /// Whether the user can read source code of this submission. Only the submitter,
+    /// problem owner and admins are allowed.
+    ///
+    /// # Errors
+    ///
+    /// When cloud not query the problem from DB
+    pub async fn is_code_visible_to<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        user: &_entities::users::Model,
+    ) -> ModelResult<bool> {
+        if self.user_id == user.id {
+            return Ok(true);
+        }
+        let problem = super::problems::Model::find_by_id(db, self.problem_id).await?;
+        Ok(problem.is_managed_by(user))
+    }
+
+    /// Lock the user's row until the end of the transaction. Checking
+    /// [`Self::remaining_quota`] and creating the submission after it in the
+    /// same transaction cannot exceed the quota even if the user submits
+    /// concurrently.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error.
+    pub async fn lock_quota<C: ConnectionTrait>(
+        db: &C,
+        user: &_entities::users::Model,
+    ) -> ModelResult<()> {
+        _entities::users::Entity::find_by_id(user.id)
+            .lock_exclusive()
+            .one(db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Remaining submission quota of the user to a problem, -1 means unlimited.
+    /// Problem managers are not limited by the quota.
+    ///
+    /// # Errors
+    ///
+    /// When cloud not count submissions from DB
+    pub async fn remaining_quota<C: ConnectionTrait>(
+        db: &C,
+        problem: &_entities::problems::Model,
+        user: &_entities::users::Model,
+    ) -> ModelResult<i32> {
+        if problem.quota == -1 || problem.is_managed_by(user) {
+            return Ok(-1);
+        }
+        let submitted = Submissions::find()
+            .filter(submissions::Column::ProblemId.eq(problem.id))
+            .filter(submissions::Column::UserId.eq(user.id))
+            .count(db)
+            .await?;
+        let submitted = i32::try_from(submitted).unwrap_or(i32::MAX);
+
+        Ok((problem.quota - submitted).max(0))
+    }
+
+    /// Whether the submission is hidden from the viewer because its problem is
+    /// in a running contest, whose results should only be seen on the
+    /// scoreboard. Submitters and contest managers can still see it.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error.
+    pub async fn is_hidden_by_contest<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        viewer: &_entities::users::Model,
+    ) -> ModelResult<bool> {
+        use super::{
+            contests::problems::problem_ids_in_running_contests_not_owned_by, users::Role,
+        };
+
+        if self.user_id == viewer.id || viewer.role == Role::Admin {
+            return Ok(false);
+        }
+        let count = _entities::problems::Entity::find()
+            .filter(_entities::problems::Column::Id.eq(self.problem_id))
+            .filter(_entities::problems::Column::Id.in_subquery(
+                problem_ids_in_running_contests_not_owned_by(viewer.id, super::now()),
+            ))
+            .count(db)
+            .await?;
+
+        Ok(count > 0)
+    }
+
+    /// List submissions with their submitter, the latest submission comes first.
+    /// Only the viewer's own submissions and submissions to problems visible to
+    /// the viewer are listed, except those hidden by running contests, see
+    /// [`Self::is_hidden_by_contest`].
+    ///
+    /// # Errors
+    ///
+    /// When cloud not query submissions from DB
+    pub async fn list<C: ConnectionTrait>(
+        db: &C,
+        params: &ListParams,
+    ) -> ModelResult<Vec<(Self, _entities::users::Model)>> {
+        use _entities::{problems, users};
+        use sea_orm::{sea_query::Query, Condition};
+
+        use super::{
+            contests::problems::problem_ids_in_running_contests_not_owned_by, users::Role,
+        };
+
+        let visible_problem_ids = Query::select()
+            .column(problems::Column::Id)
+            .from(problems::Entity)
+            .cond_where(super::problems::Model::visible_to(&params.viewer))
+            .to_owned();
+        let mut others =
+            Condition::all().add(submissions::Column::ProblemId.in_subquery(visible_problem_ids));
+        if params.viewer.role != Role::Admin {
+            others = others.add(submissions::Column::ProblemId.not_in_subquery(
+                problem_ids_in_running_contests_not_owned_by(params.viewer.id, super::now()),
+            ));
+        }
+        let mut q = Submissions::find()
+            .find_also_related(users::Entity)
+            .filter(
+                Condition::any()
+                    .add(submissions::Column::UserId.eq(params.viewer.id))
+                    .add(others),
+            )
+            .order_by(submissions::Column::Id, Order::Desc);
+
+        if let Some(username) = &params.username {
+            q = q.filter(users::Column::Name.eq(username));
+        }
+        if let Some(problem_id) = params.problem_id {
+            q = q.filter(submissions::Column::ProblemId.eq(problem_id));
+        }
+        if let Some(status) = params.status {
+            q = q.filter(submissions::Column::Status.eq(status as i32));
+        }
+        if let Some(language) = params.language {
+            q = q.filter(submissions::Column::Language.eq(language as i32));
+        }
+        if let Some(course) = &params.course {
+            q = q.filter(
+                submissions::Column::ProblemId
+                    .in_subquery(super::problems::courses::problem_ids_in_course(course)),
+            );
+        }
+
+        let submissions = q
+            .offset(params.offset)
+            .limit(params.count)
+            .all(db)
+            .await?
+            .into_iter()
+            // submitter should always exist because of the foreign key
+            .filter_map(|(s, u)| u.map(|u| (s, u)))
+            .collect();
+
+        Ok(submissions)
+    }
+
+    /// Collect submission statistics of given problems. Problems without any
+    /// submission are not included in the result.
+    ///
+    /// # Errors
+    ///
+    /// When there is DB error.
+    pub async fn problem_stats<C: ConnectionTrait>(
+        db: &C,
+        problem_ids: &[i32],
+    ) -> ModelResult<HashMap<i32, ProblemStats>> {
+        use sea_orm::sea_query::Expr;
+
+        let stats = Submissions::find()
+            .select_only()
+            .column(submissions::Column::ProblemId)
+            .column_as(submissions::Column::Id.count(), "submit_count")
+            .column_as(
+                Expr::col(submissions::Column::UserId).count_distinct(),
+                "submitter",
+            )
+            .column_as(
+                Expr::expr(Expr::case(
+                    submissions::Column::Status.eq(Status::Accepted as i32),
+                    Expr::col(submissions::Column::UserId),
+                ))
+                .count_distinct(),
+                "ac_user",
+            )
+            .filter(submissions::Column::ProblemId.is_in(problem_ids.iter().copied()))
+            .group_by(submissions::Column::ProblemId)
+            .into_model::<ProblemStats>()
+            .all(db)
+            .await?;
+
+        Ok(stats.into_iter().map(|s| (s.problem_id, s)).collect())
+    }
+
+    /// Best score of the user's submissions to a problem, 0 if the user has not
+    /// submitted yet.
+    ///
+    /// # Errors
+    ///
+    /// When there is DB error.
+    pub async fn high_score<C: ConnectionTrait>(
+        db: &C,
+        problem_id: i32,
+        user_id: i32,
+    ) -> ModelResult<i32> {
+        let score = Submissions::find()
+            .select_only()
+            .column_as(submissions::Column::Score.max(), "high_score")
+            .filter(submissions::Column::ProblemId.eq(problem_id))
+            .filter(submissions::Column::UserId.eq(user_id))
+            .into_tuple::<Option<i32>>()
+            .one(db)
+            .await?
+            .flatten();
+
+        Ok(score.unwrap_or(0))
+    }
+}
+
+impl ActiveModel {
+    /// Grade and save judge result of a submission. The overall status is the first
+    /// non-AC task status, and the score is the sum of task scores. Results without
+    /// a corresponding problem task are ignored. Scoreboards of contests containing
+    /// the submission are updated as well.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error.
+    pub async fn update_result<C: ConnectionTrait>(
+        mut self,
+        db: &C,
+        tasks: &[TaskResult],
+    ) -> ModelResult<Model> {
+        let problem = super::problems::Model::find_by_id(db, *self.problem_id.as_ref()).await?;
+        let problem_tasks = problem.tasks(db).await?;
+        let tasks = problem_tasks
+            .iter()
+            .enumerate()
+            .map(|(i, task)| {
+                let mut result = tasks.get(i).cloned().unwrap_or_else(|| {
+                    // the sandbox did not report this task, treat it as judge error
+                    let mut result = TaskResult::from_cases(vec![]);
+                    result.status = Status::JudgeError;
+                    result
+                });
+                result.grade(task);
+                result
+            })
+            .collect::<Vec<_>>();
+
+        let status = tasks
+            .iter()
+            .map(|t| t.status)
+            .find(|s| *s != Status::Accepted)
+            .unwrap_or(Status::Accepted);
+        self.status = ActiveValue::set(status as i32);
+        self.score = ActiveValue::set(tasks.iter().map(|t| t.score).sum());
+        self.exec_time = ActiveValue::set(tasks.iter().map(|t| t.exec_time).max().unwrap_or(-1));
+        self.memory_usage =
+            ActiveValue::set(tasks.iter().map(|t| t.memory_usage).max().unwrap_or(-1));
+        self.tasks = ActiveValue::set(Some(
+            serde_json::to_value(&tasks).map_err(|e| ModelError::Any(e.into()))?,
+        ));
+        let submission = self.update(db).await?;
+        super::contests::Model::record_submission(db, &submission).await?;
+        Ok(submission)
+    }
+
+    /// Record the current test case version of the problem, so that the result
+    /// can be traced back to the test case it was judged against.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error.
+    pub async fn record_test_case_version<C: ConnectionTrait>(
+        mut self,
+        db: &C,
+    ) -> ModelResult<Model> {
+        let problem = super::problems::Model::find_by_id(db, *self.problem_id.as_ref()).await?;
+        let version = match &problem.test_case_id {
+            Some(id) => super::problems::test_case_versions::Model::find_by_test_case_id(db, id)
+                .await?
+                .map(|v| v.id),
+            None => None,
+        };
+        self.test_case_version_id = ActiveValue::set(version);
+        Ok(self.update(db).await?)
+    }
+
+    /// Save the score and comment given by a grader. The submission is accepted
+    /// if it gets [`MAX_GRADE`], otherwise it is marked as wrong answer.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error.
+    pub async fn grade<C: ConnectionTrait>(
+        mut self,
+        db: &C,
+        score: i32,
+        comment: Option<String>,
+    ) -> ModelResult<Model> {
+        let status = if score == MAX_GRADE {
+            Status::Accepted
+        } else {
+            Status::WrongAnswer
+        };
+        self.status = ActiveValue::set(status as i32);
+        self.score = ActiveValue::set(score);
+        self.comment = ActiveValue::set(comment);
+        let submission = self.update(db).await?;
+        super::contests::Model::record_submission(db, &submission).await?;
+        Ok(submission)
+    }
+
+    /// Mark the submission as judge error, used when the judge could not finish its job.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error.
+    pub async fn judge_error<C: ConnectionTrait>(mut self, db: &C) -> ModelResult<Model> {
+        self.status = ActiveValue::set(Status::JudgeError as i32);
+        Ok(self.update(db).await?)
+    }
+}
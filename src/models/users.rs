@@ -247,7 +247,8 @@ impl super::_entities::users::Model {
         Ok(user)
     }
 
-    /// Batch signup multiple users at once.
+    /// Batch signup multiple users at once. Users are enrolled to the course as
+    /// students if it is specified.
     ///
     /// # Errors
     ///
@@ -261,7 +262,7 @@ impl super::_entities::users::Model {
         if let Some(r) = params
             .users
             .iter()
-            .find(|u| u.role.map_or(false, |r| int_to_role(r).is_none()))
+            .find(|u| u.role.is_some_and(|r| int_to_role(r).is_none()))
         {
             return Err(ModelError::Any(Box::new(Error::BatchSignupInvalidRole(
                 r.clone(),
@@ -281,7 +282,7 @@ impl super::_entities::users::Model {
             .await;
 
             let new_user = match register_result {
-                Err(ModelError::EntityAlreadyExists {}) => {
+                Err(ModelError::EntityAlreadyExists) => {
                     match Self::find_by_username(&tx, &u.username).await {
                         Ok(u) => Ok(u),
                         Err(_) => Self::find_by_email(&tx, &u.email).await,
@@ -296,7 +297,9 @@ impl super::_entities::users::Model {
                 r => r,
             }?;
 
-            // TODO: update course info
+            if let Some(course) = &params.course {
+                super::course_members::Model::enroll(&tx, course.id, new_user.id).await?;
+            }
 
             new_users.push(new_user);
         }
@@ -0,0 +1,27 @@
+use loco_rs::prelude::*;
+use serde::Deserialize;
+
+use crate::{judge::SandboxConfig, models::problems::test_case};
+
+/// App specific settings, read from `settings` in the config file.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub sandbox: SandboxConfig,
+    /// Limits of uploaded test cases
+    pub test_case: test_case::Limits,
+}
+
+impl Settings {
+    /// Parse settings of the app, missing fields are filled with default values.
+    ///
+    /// # Errors
+    ///
+    /// When the settings have invalid format.
+    pub fn from_context(ctx: &AppContext) -> Result<Self> {
+        ctx.config.settings.as_ref().map_or_else(
+            || Ok(Self::default()),
+            |settings| Ok(serde_json::from_value(settings.clone())?),
+        )
+    }
+}
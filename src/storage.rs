@@ -0,0 +1,109 @@
+//! Helpers of app's storage.
+//!
+//! Files are stored by a local [`object_store`] under [`ROOT`], or under a
+//! temporary directory in tests.
+
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+use loco_rs::{
+    environment::Environment,
+    prelude::*,
+    storage::drivers::{object_store_adapter::ObjectStoreAdapter, StoreDriver},
+};
+use object_store::{local::LocalFileSystem, ObjectStore};
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+
+/// Directory of the local storage
+pub const ROOT: &str = "storage";
+
+fn root(environment: &Environment) -> PathBuf {
+    if *environment == Environment::Test {
+        std::env::temp_dir().join(concat!(env!("CARGO_CRATE_NAME"), "-storage"))
+    } else {
+        PathBuf::from(ROOT)
+    }
+}
+
+fn object_store(environment: &Environment) -> Result<LocalFileSystem> {
+    let root = root(environment);
+    std::fs::create_dir_all(&root)?;
+    LocalFileSystem::new_with_prefix(root).map_err(|e| Error::Any(e.into()))
+}
+
+/// Build the storage driver of the environment.
+///
+/// # Errors
+///
+/// When the storage directory could not be created.
+pub fn driver(environment: &Environment) -> Result<Box<dyn StoreDriver>> {
+    Ok(Box::new(ObjectStoreAdapter::new(Box::new(object_store(
+        environment,
+    )?))))
+}
+
+/// Copy a file into app's storage without loading it into memory. The file is
+/// written by the same store as [`driver`], so it can be read from
+/// `ctx.storage` afterwards.
+///
+/// # Errors
+///
+/// When the file could not be read or written.
+pub async fn upload_file(ctx: &AppContext, path: &Path, file: File) -> Result<()> {
+    put_file(&object_store(&ctx.environment)?, path, file).await
+}
+
+async fn put_file(store: &dyn ObjectStore, path: &Path, file: File) -> Result<()> {
+    // paths are converted the same way as `ObjectStoreAdapter`
+    let path = object_store::path::Path::from(path.display().to_string());
+    let mut file = tokio::fs::File::from_std(file);
+    file.rewind().await?;
+
+    let (id, mut writer) = store
+        .put_multipart(&path)
+        .await
+        .map_err(|e| Error::Any(e.into()))?;
+    let copied = async {
+        tokio::io::copy(&mut file, &mut writer).await?;
+        writer.shutdown().await
+    }
+    .await;
+    if let Err(err) = copied {
+        if let Err(e) = store.abort_multipart(&path, &id).await {
+            tracing::warn!(error = ?e, path = %path, "could not abort upload");
+        }
+        return Err(err.into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_put_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = LocalFileSystem::new_with_prefix(dir.path()).unwrap();
+        let mut file = tempfile::tempfile().unwrap();
+        let content = b"0123456789".repeat(1024);
+        file.write_all(&content).unwrap();
+
+        put_file(&store, Path::new("test-case/a.zip"), file)
+            .await
+            .unwrap();
+
+        let stored = std::fs::read(dir.path().join("test-case/a.zip")).unwrap();
+        assert_eq!(stored, content);
+        // nothing is left besides the uploaded file
+        let entries = std::fs::read_dir(dir.path().join("test-case"))
+            .unwrap()
+            .count();
+        assert_eq!(entries, 1);
+    }
+}
@@ -0,0 +1,145 @@
+use sea_orm::prelude::DateTime;
+use serde::Serialize;
+
+use super::NojResponseBuilder;
+use crate::models::{
+    contests::{self, scores::Standing, Rule},
+    users,
+};
+
+#[derive(Debug, Serialize)]
+pub struct ContestResponse {
+    pub id: i32,
+    pub name: String,
+    pub description: String,
+    pub rule: Rule,
+    pub start_at: DateTime,
+    pub end_at: DateTime,
+    pub freeze_minutes: i32,
+    pub problem_ids: Vec<i32>,
+}
+
+impl ContestResponse {
+    #[must_use]
+    pub fn new(contest: contests::Model, problem_ids: Vec<i32>) -> Self {
+        Self {
+            id: contest.id,
+            rule: contest.rule(),
+            name: contest.name,
+            description: contest.description,
+            start_at: contest.start_at,
+            end_at: contest.end_at,
+            freeze_minutes: contest.freeze_minutes,
+            problem_ids,
+        }
+    }
+}
+
+pub struct ContestListResponse {}
+
+impl ContestListResponse {
+    #[must_use]
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        contests: Vec<(contests::Model, Vec<i32>)>,
+    ) -> NojResponseBuilder<Vec<ContestResponse>> {
+        let data = contests
+            .into_iter()
+            .map(|(c, problem_ids)| ContestResponse::new(c, problem_ids))
+            .collect();
+
+        NojResponseBuilder::new(data)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ParticipantResponseItem {
+    pub username: String,
+    pub displayed_name: Option<String>,
+}
+
+pub struct ParticipantListResponse {}
+
+impl ParticipantListResponse {
+    #[must_use]
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(participants: &[users::Model]) -> NojResponseBuilder<Vec<ParticipantResponseItem>> {
+        let data = participants
+            .iter()
+            .map(|u| ParticipantResponseItem {
+                username: u.name.clone(),
+                displayed_name: u.displayed_name.clone(),
+            })
+            .collect();
+
+        NojResponseBuilder::new(data)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScoreboardCell {
+    /// rejected submissions before the problem is solved
+    pub attempts: i32,
+    /// minutes from the contest start to the first accepted submission
+    pub solved_at: Option<i64>,
+    pub score: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScoreboardRow {
+    pub rank: usize,
+    pub username: String,
+    pub displayed_name: Option<String>,
+    pub solved: i32,
+    pub penalty: i64,
+    pub score: i32,
+    /// result of each problem, in the same order as `problem_ids`
+    pub problems: Vec<ScoreboardCell>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScoreboardResponse {
+    pub rule: Rule,
+    /// submissions during the freeze are not counted
+    pub frozen: bool,
+    pub problem_ids: Vec<i32>,
+    pub rows: Vec<ScoreboardRow>,
+}
+
+impl ScoreboardResponse {
+    #[must_use]
+    pub fn new(
+        contest: &contests::Model,
+        frozen: bool,
+        problem_ids: Vec<i32>,
+        standings: Vec<(users::Model, Standing)>,
+    ) -> Self {
+        let rows = standings
+            .into_iter()
+            .map(|(u, s)| ScoreboardRow {
+                rank: s.rank,
+                username: u.name,
+                displayed_name: u.displayed_name,
+                solved: s.solved,
+                penalty: s.penalty,
+                score: s.score,
+                problems: s
+                    .cells
+                    .iter()
+                    .map(|c| ScoreboardCell {
+                        attempts: c.attempts,
+                        solved_at: c.accepted_at.map(|t| (t - contest.start_at).num_minutes()),
+                        score: c.score,
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Self {
+            rule: contest.rule(),
+            frozen,
+            problem_ids,
+            rows,
+        }
+    }
+}
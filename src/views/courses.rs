@@ -0,0 +1,32 @@
+use serde::Serialize;
+
+use super::NojResponseBuilder;
+use crate::models::{course_members::CourseRole, users};
+
+#[derive(Debug, Serialize)]
+pub struct CourseMemberResponseItem {
+    pub username: String,
+    pub displayed_name: Option<String>,
+    pub role: CourseRole,
+}
+
+pub struct CourseMemberListResponse {}
+
+impl CourseMemberListResponse {
+    #[must_use]
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        members: &[(users::Model, CourseRole)],
+    ) -> NojResponseBuilder<Vec<CourseMemberResponseItem>> {
+        let data = members
+            .iter()
+            .map(|(u, role)| CourseMemberResponseItem {
+                username: u.name.clone(),
+                displayed_name: u.displayed_name.clone(),
+                role: *role,
+            })
+            .collect();
+
+        NojResponseBuilder::new(data)
+    }
+}
@@ -0,0 +1,107 @@
+use sea_orm::prelude::DateTime;
+use serde::Serialize;
+
+use super::NojResponseBuilder;
+use crate::models::{homeworks, users};
+
+#[derive(Debug, Serialize)]
+pub struct HomeworkResponse {
+    pub id: i32,
+    pub name: String,
+    pub description: String,
+    pub start_at: DateTime,
+    pub end_at: DateTime,
+    pub late_end_at: Option<DateTime>,
+    pub penalty: i32,
+    pub problem_ids: Vec<i32>,
+}
+
+impl HomeworkResponse {
+    #[must_use]
+    pub fn new(homework: homeworks::Model, problem_ids: Vec<i32>) -> Self {
+        Self {
+            id: homework.id,
+            name: homework.name,
+            description: homework.description,
+            start_at: homework.start_at,
+            end_at: homework.end_at,
+            late_end_at: homework.late_end_at,
+            penalty: homework.penalty,
+            problem_ids,
+        }
+    }
+}
+
+pub struct HomeworkListResponse {}
+
+impl HomeworkListResponse {
+    #[must_use]
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        homeworks: Vec<(homeworks::Model, Vec<i32>)>,
+    ) -> NojResponseBuilder<Vec<HomeworkResponse>> {
+        let data = homeworks
+            .into_iter()
+            .map(|(h, problem_ids)| HomeworkResponse::new(h, problem_ids))
+            .collect();
+
+        NojResponseBuilder::new(data)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScoreboardRow {
+    pub username: String,
+    pub displayed_name: Option<String>,
+    /// best score of each problem, in the same order as `problem_ids`
+    pub scores: Vec<i32>,
+    pub total: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScoreboardResponse {
+    pub problem_ids: Vec<i32>,
+    pub rows: Vec<ScoreboardRow>,
+}
+
+impl ScoreboardResponse {
+    #[must_use]
+    pub fn new(problem_ids: Vec<i32>, scores: Vec<(users::Model, Vec<i32>)>) -> Self {
+        let rows = scores
+            .into_iter()
+            .map(|(u, scores)| ScoreboardRow {
+                username: u.name,
+                displayed_name: u.displayed_name,
+                total: scores.iter().sum(),
+                scores,
+            })
+            .collect();
+
+        Self { problem_ids, rows }
+    }
+
+    /// Export as CSV, the header is username, problem ids and total.
+    ///
+    /// # Errors
+    ///
+    /// When could not write the CSV.
+    pub fn to_csv(&self) -> csv::Result<Vec<u8>> {
+        let mut writer = csv::Writer::from_writer(vec![]);
+        writer.write_record(
+            std::iter::once("username".to_string())
+                .chain(self.problem_ids.iter().map(ToString::to_string))
+                .chain(std::iter::once("total".to_string())),
+        )?;
+        for row in &self.rows {
+            writer.write_record(
+                std::iter::once(row.username.clone())
+                    .chain(row.scores.iter().map(ToString::to_string))
+                    .chain(std::iter::once(row.total.to_string())),
+            )?;
+        }
+
+        writer
+            .into_inner()
+            .map_err(|e| csv::Error::from(e.into_error()))
+    }
+}
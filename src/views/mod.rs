@@ -1,5 +1,9 @@
 pub mod auth;
+pub mod contests;
+pub mod courses;
+pub mod homeworks;
 pub mod problems;
+pub mod submissions;
 pub mod user;
 
 use loco_rs::controller::views::pagination::PagerMeta;
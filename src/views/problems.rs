@@ -1,13 +1,23 @@
+use std::collections::HashMap;
+
+use loco_rs::controller::views::pagination::{Pager, PagerMeta};
 use num_traits::FromPrimitive;
+use sea_orm::prelude::DateTime;
 use serde::Serialize;
 
 use crate::models::{
-    problems::{self, Type, Visibility},
-    users,
+    courses,
+    problems::{self, templates, test_case_versions, Type, Visibility},
+    submissions::ProblemStats,
+    users, Language,
 };
 
 use super::NojResponseBuilder;
 
+fn count_to_i32(count: i64) -> i32 {
+    i32::try_from(count).unwrap_or(i32::MAX)
+}
+
 #[derive(Debug, Serialize)]
 pub struct ProblemListResponseItem {
     pub id: i32,
@@ -19,6 +29,8 @@ pub struct ProblemListResponseItem {
     pub r#type: Type,
     pub quota: i32,
     pub submit_count: i32,
+    /// Archived problems are only listed to admins
+    pub archived: bool,
 }
 
 pub struct ProblemListResponse {}
@@ -27,24 +39,58 @@ impl ProblemListResponse {
     #[must_use]
     #[allow(clippy::missing_panics_doc)]
     #[allow(clippy::new_ret_no_self)]
-    pub fn new(problems: &[problems::Model]) -> NojResponseBuilder<Vec<ProblemListResponseItem>> {
+    pub fn new(
+        problems: &[problems::Model],
+        stats: &HashMap<i32, ProblemStats>,
+        tags: &HashMap<i32, Vec<String>>,
+        meta: PagerMeta,
+    ) -> NojResponseBuilder<Pager<Vec<ProblemListResponseItem>>> {
         let data = problems
             .iter()
-            .map(|p| ProblemListResponseItem {
-                id: p.id,
-                name: p.name.to_string(),
-                status: Visibility::from_i32(p.status).unwrap(),
-                r#type: Type::from_i32(p.r#type).unwrap(),
-                quota: p.quota,
-                // TODO: impl following fields
-                ac_user: 0,
-                submit_count: 0,
-                submitter: 0,
-                tags: vec![],
+            .map(|p| {
+                let stat = stats.get(&p.id).copied().unwrap_or_default();
+                ProblemListResponseItem {
+                    id: p.id,
+                    name: p.name.to_string(),
+                    status: Visibility::from_i32(p.status).unwrap(),
+                    r#type: Type::from_i32(p.r#type).unwrap(),
+                    quota: p.quota,
+                    ac_user: count_to_i32(stat.ac_user),
+                    submit_count: count_to_i32(stat.submit_count),
+                    submitter: count_to_i32(stat.submitter),
+                    tags: tags.get(&p.id).cloned().unwrap_or_default(),
+                    archived: p.is_archived(),
+                }
             })
             .collect();
 
-        NojResponseBuilder::new(data)
+        NojResponseBuilder::new(Pager::new(data, meta))
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct TemplateResponseItem {
+    pub language: Language,
+    /// Reference answers of blanks are redacted unless the viewer manages the problem
+    pub template: String,
+    pub blank_count: usize,
+}
+
+impl TemplateResponseItem {
+    /// Returns `None` if the stored template is invalid, which should not happen.
+    fn new(template: &templates::Model, redact: bool) -> Option<Self> {
+        let blank_count = templates::validate(&template.content).ok()?;
+        let content = if redact {
+            templates::redact(&template.content).ok()?
+        } else {
+            template.content.clone()
+        };
+
+        Some(Self {
+            language: template.language()?,
+            template: content,
+            blank_count,
+        })
     }
 }
 
@@ -56,41 +102,106 @@ pub struct ProblemDetailResponse {
     /// username of problem owner
     owner: String,
     tags: Vec<String>,
-    allowed_language: i32,
+    /// Names of allowed languages
+    allowed_language: Vec<&'static str>,
     /// list of courses' names
     courses: Vec<String>,
     quota: i32,
     status: Visibility,
     r#type: Type,
     test_case: Vec<problems::tasks::Model>,
+    /// Code templates of fill-in-template problems
+    templates: Vec<TemplateResponseItem>,
     submit_count: i32,
     high_score: i32,
+    /// Remaining submission quota of the viewer, -1 means unlimited
+    remaining_quota: i32,
+    /// Whether the test case no longer matches the tasks and should be uploaded
+    /// again, only shown to problem managers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    test_case_stale: Option<bool>,
 }
 
 impl ProblemDetailResponse {
     #[must_use]
     #[allow(clippy::missing_panics_doc)]
     #[allow(clippy::new_ret_no_self)]
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         problem: &problems::Model,
         description: &problems::descriptions::Model,
         owner: &users::Model,
+        courses: &[courses::Model],
+        tags: &[String],
         tasks: &[problems::tasks::Model],
+        templates: &[templates::Model],
+        is_manager: bool,
+        stats: &ProblemStats,
+        high_score: i32,
+        remaining_quota: i32,
     ) -> NojResponseBuilder<Self> {
         let resp = Self {
             problem_name: problem.name.clone(),
             description: description.clone(),
             owner: owner.name.clone(),
-            tags: vec![],
-            allowed_language: problem.allowed_language,
-            courses: vec![],
+            tags: tags.to_vec(),
+            allowed_language: problem
+                .allowed_languages()
+                .iter()
+                .map(Language::name)
+                .collect(),
+            courses: courses.iter().map(|c| c.name.clone()).collect(),
             quota: problem.quota,
             status: Visibility::from_i32(problem.status).unwrap(),
             r#type: Type::from_i32(problem.r#type).unwrap(),
             test_case: tasks.to_vec(),
-            submit_count: 0,
-            high_score: 0,
+            templates: templates
+                .iter()
+                .filter_map(|t| TemplateResponseItem::new(t, !is_manager))
+                .collect(),
+            submit_count: count_to_i32(stats.submit_count),
+            high_score,
+            remaining_quota,
+            test_case_stale: is_manager.then_some(problem.test_case_stale),
         };
         NojResponseBuilder::new(resp)
     }
 }
+
+#[derive(Debug, Serialize)]
+pub struct TestCaseVersionResponseItem {
+    pub id: i32,
+    /// username of uploader
+    pub uploader: String,
+    /// Size of the zip in bytes
+    pub size: i64,
+    pub sha256: String,
+    /// Whether this version is currently used by the problem
+    pub current: bool,
+    pub created_at: DateTime,
+}
+
+pub struct TestCaseVersionListResponse {}
+
+impl TestCaseVersionListResponse {
+    #[must_use]
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        problem: &problems::Model,
+        versions: &[(test_case_versions::Model, users::Model)],
+    ) -> NojResponseBuilder<Vec<TestCaseVersionResponseItem>> {
+        let data = versions
+            .iter()
+            .map(|(v, u)| TestCaseVersionResponseItem {
+                id: v.id,
+                uploader: u.name.clone(),
+                size: v.size,
+                sha256: v.sha256.clone(),
+                current: problem.test_case_id.as_ref() == Some(&v.test_case_id),
+                created_at: v.created_at,
+            })
+            .collect();
+
+        NojResponseBuilder::new(data)
+    }
+}
@@ -0,0 +1,101 @@
+use num_traits::FromPrimitive;
+use sea_orm::prelude::DateTime;
+use serde::Serialize;
+
+use crate::models::{
+    submissions::{self, Status, TaskResult},
+    users, Language,
+};
+
+use super::NojResponseBuilder;
+
+#[derive(Debug, Serialize)]
+pub struct SubmissionListResponseItem {
+    pub id: i32,
+    pub problem_id: i32,
+    /// username of submitter
+    pub user: String,
+    /// `None` for handwritten submissions
+    pub language: Option<Language>,
+    pub status: Status,
+    pub score: i32,
+    pub exec_time: i32,
+    pub memory_usage: i32,
+    pub created_at: DateTime,
+}
+
+impl SubmissionListResponseItem {
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn new(submission: &submissions::Model, user: &users::Model) -> Self {
+        Self {
+            id: submission.id,
+            problem_id: submission.problem_id,
+            user: user.name.clone(),
+            language: submission.language(),
+            status: Status::from_i32(submission.status).unwrap(),
+            score: submission.score,
+            exec_time: submission.exec_time,
+            memory_usage: submission.memory_usage,
+            created_at: submission.created_at,
+        }
+    }
+}
+
+pub struct SubmissionListResponse {}
+
+impl SubmissionListResponse {
+    #[must_use]
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        submissions: &[(submissions::Model, users::Model)],
+    ) -> NojResponseBuilder<Vec<SubmissionListResponseItem>> {
+        let data = submissions
+            .iter()
+            .map(|(s, u)| SubmissionListResponseItem::new(s, u))
+            .collect();
+
+        NojResponseBuilder::new(data)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SubmissionDetailResponse {
+    #[serde(flatten)]
+    pub submission: SubmissionListResponseItem,
+    /// Results of tasks, stderr of cases is only visible to those who can read
+    /// the code because compiler messages quote the source
+    pub tasks: Vec<TaskResult>,
+    /// Id of the test case version judged against, `None` if not judged yet
+    pub test_case_version: Option<i32>,
+    /// Source code, only visible to the submitter and problem managers
+    pub code: Option<String>,
+    /// Comment given by the grader of a handwritten submission
+    pub comment: Option<String>,
+}
+
+impl SubmissionDetailResponse {
+    #[must_use]
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        submission: &submissions::Model,
+        user: &users::Model,
+        code: Option<String>,
+        show_stderr: bool,
+    ) -> NojResponseBuilder<Self> {
+        let mut tasks = submission.task_results();
+        if !show_stderr {
+            for case in tasks.iter_mut().flat_map(|t| t.cases.iter_mut()) {
+                case.stderr.clear();
+            }
+        }
+
+        NojResponseBuilder::new(Self {
+            submission: SubmissionListResponseItem::new(submission, user),
+            tasks,
+            test_case_version: submission.test_case_version_id,
+            code,
+            comment: submission.comment.clone(),
+        })
+    }
+}
@@ -0,0 +1,91 @@
+use loco_rs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    judge,
+    models::{problems, submissions},
+};
+
+pub struct JudgeWorker {
+    pub ctx: AppContext,
+}
+
+#[derive(Deserialize, Debug, Serialize)]
+pub struct JudgeWorkerArgs {
+    pub submission_id: i32,
+}
+
+impl worker::AppWorker<JudgeWorkerArgs> for JudgeWorker {
+    fn build(ctx: &AppContext) -> Self {
+        Self { ctx: ctx.clone() }
+    }
+}
+
+#[async_trait]
+impl worker::Worker<JudgeWorkerArgs> for JudgeWorker {
+    async fn perform(&self, args: JudgeWorkerArgs) -> worker::Result<()> {
+        let submission = submissions::Model::find_by_id(&self.ctx.db, args.submission_id)
+            .await
+            .map_err(Box::from)?;
+        if submission.is_handwritten() {
+            tracing::warn!(
+                submission_id = submission.id,
+                "handwritten submission is graded manually"
+            );
+            return Ok(());
+        }
+        let problem = problems::Model::find_by_id(&self.ctx.db, submission.problem_id)
+            .await
+            .map_err(Box::from)?;
+        if problem.test_case_stale {
+            // the test case no longer matches the tasks since the problem is edited
+            tracing::warn!(
+                submission_id = submission.id,
+                "test case of problem is stale"
+            );
+            submission
+                .into_active_model()
+                .judge_error(&self.ctx.db)
+                .await
+                .map_err(Box::from)?;
+            return Ok(());
+        }
+        let submission = submission
+            .into_active_model()
+            .record_test_case_version(&self.ctx.db)
+            .await
+            .map_err(Box::from)?;
+
+        let result = match judge::from_context(&self.ctx) {
+            Ok(sandbox) => sandbox.judge(&self.ctx, &submission).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(None) => {
+                tracing::info!(submission_id = submission.id, "submission sent to sandbox");
+            }
+            Ok(Some(tasks)) => {
+                let submission = submission
+                    .into_active_model()
+                    .update_result(&self.ctx.db, &tasks)
+                    .await
+                    .map_err(Box::from)?;
+                tracing::info!(
+                    submission_id = submission.id,
+                    status = submission.status,
+                    "submission judged"
+                );
+            }
+            Err(e) => {
+                tracing::error!(submission_id = submission.id, error = %e, "could not judge submission");
+                submission
+                    .into_active_model()
+                    .judge_error(&self.ctx.db)
+                    .await
+                    .map_err(Box::from)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,400 @@
+use axum::http::StatusCode;
+use chrono::{Duration, Local};
+use loco_rs::testing;
+use normal_oj::{
+    app::App,
+    models::{contests, users},
+};
+use serde_json::json;
+use serial_test::serial;
+
+use super::{create_token, prepare_data};
+
+#[tokio::test]
+#[serial]
+async fn admin_can_manage_contests() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let admin = users::Model::find_by_username(&ctx.db, "first_admin")
+            .await
+            .unwrap();
+        let teacher = users::Model::find_by_username(&ctx.db, "teacher1")
+            .await
+            .unwrap();
+        let problem = prepare_data::create_problem(&ctx, &teacher).await;
+        let (auth_key, auth_value) = prepare_data::auth_header(&create_token(&admin, &ctx).await);
+        let params = json!({
+            "name": "contest1",
+            "rule": 0,
+            "start_at": "2024-07-01T08:00:00",
+            "end_at": "2024-07-01T13:00:00",
+            "freeze_minutes": 60,
+            "problem_ids": [problem.id],
+        });
+
+        // only admins can create contests
+        let (teacher_key, teacher_value) =
+            prepare_data::auth_header(&create_token(&teacher, &ctx).await);
+        let response = request
+            .post("/api/contests")
+            .add_header(teacher_key.clone(), teacher_value.clone())
+            .json(&params)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+        let response = request
+            .post("/api/contests")
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&params)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let contest_id = response.json::<serde_json::Value>()["id"].as_i64().unwrap();
+
+        let response = request
+            .post("/api/contests")
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&params)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CONFLICT);
+
+        // the freeze cannot be longer than the contest
+        let response = request
+            .put(&format!("/api/contests/{contest_id}"))
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&json!({
+                "name": "contest1",
+                "rule": 0,
+                "start_at": "2024-07-01T08:00:00",
+                "end_at": "2024-07-01T13:00:00",
+                "freeze_minutes": 301,
+            }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+        let response = request
+            .put(&format!("/api/contests/{contest_id}"))
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&json!({
+                "name": "contest1",
+                "description": "IOI style",
+                "rule": 1,
+                "start_at": "2024-07-01T08:00:00",
+                "end_at": "2024-07-01T13:00:00",
+            }))
+            .await;
+        response.assert_status_ok();
+
+        // only managers and participants can see the contest
+        let response = request
+            .get(&format!("/api/contests/{contest_id}"))
+            .add_header(teacher_key.clone(), teacher_value.clone())
+            .await;
+        assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+        let response = request
+            .get("/api/contests")
+            .add_header(teacher_key.clone(), teacher_value.clone())
+            .await;
+        assert_eq!(response.json::<serde_json::Value>()["data"], json!([]));
+        let response = request
+            .get(&format!("/api/contests/{contest_id}"))
+            .add_header(auth_key.clone(), auth_value.clone())
+            .await;
+        response.assert_status_ok();
+        let contest = response.json::<serde_json::Value>();
+        assert_eq!(contest["rule"], 1);
+        assert_eq!(contest["freeze_minutes"], 0);
+        assert_eq!(contest["problem_ids"], json!([]));
+
+        let response = request
+            .post(&format!("/api/contests/{contest_id}/participants"))
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&json!({"users": ["user1", "nobody"]}))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+        let response = request
+            .post(&format!("/api/contests/{contest_id}/participants"))
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&json!({"users": ["user1", "user2"]}))
+            .await;
+        response.assert_status_ok();
+        let response = request
+            .delete(&format!("/api/contests/{contest_id}/participants/user2"))
+            .add_header(auth_key.clone(), auth_value.clone())
+            .await;
+        response.assert_status_ok();
+        let response = request
+            .get(&format!("/api/contests/{contest_id}/participants"))
+            .add_header(auth_key.clone(), auth_value.clone())
+            .await;
+        let participants = response.json::<serde_json::Value>()["data"].clone();
+        assert_eq!(participants.as_array().unwrap().len(), 1);
+        assert_eq!(participants[0]["username"], "user1");
+        let user1 = users::Model::find_by_username(&ctx.db, "user1")
+            .await
+            .unwrap();
+        let (user_key, user_value) = prepare_data::auth_header(&create_token(&user1, &ctx).await);
+        let response = request
+            .get("/api/contests")
+            .add_header(user_key.clone(), user_value.clone())
+            .await;
+        let contests = response.json::<serde_json::Value>()["data"].clone();
+        assert_eq!(contests.as_array().unwrap().len(), 1);
+        assert_eq!(contests[0]["id"], contest_id);
+        let response = request
+            .get(&format!("/api/contests/{contest_id}"))
+            .add_header(user_key, user_value)
+            .await;
+        response.assert_status_ok();
+
+        let response = request
+            .delete(&format!("/api/contests/{contest_id}"))
+            .add_header(teacher_key, teacher_value)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+        let response = request
+            .delete(&format!("/api/contests/{contest_id}"))
+            .add_header(auth_key.clone(), auth_value.clone())
+            .await;
+        response.assert_status_ok();
+        let response = request
+            .get(&format!("/api/contests/{contest_id}"))
+            .add_header(auth_key, auth_value)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn contest_problems_should_be_visible_to_owner() {
+    testing::request::<App, _, _>(|_request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let teacher = users::Model::find_by_username(&ctx.db, "teacher1")
+            .await
+            .unwrap();
+        let user2 = users::Model::find_by_username(&ctx.db, "user2")
+            .await
+            .unwrap();
+        let problem = prepare_data::create_problem(&ctx, &teacher).await;
+        let params = serde_json::from_value::<contests::Params>(json!({
+            "name": "contest1",
+            "rule": 0,
+            "start_at": "2024-07-01T08:00:00",
+            "end_at": "2024-07-01T13:00:00",
+            "problem_ids": [problem.id],
+        }))
+        .unwrap();
+
+        // user2 is not in the course of the problem
+        let err = contests::Model::add(&ctx.db, &user2, &params)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("problem {} is not visible to the contest owner", problem.id)
+        );
+        assert!(contests::Model::add(&ctx.db, &teacher, &params)
+            .await
+            .is_ok());
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn scoreboard_is_updated_by_judged_submissions() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let admin = users::Model::find_by_username(&ctx.db, "first_admin")
+            .await
+            .unwrap();
+        let teacher = users::Model::find_by_username(&ctx.db, "teacher1")
+            .await
+            .unwrap();
+        let problem = prepare_data::create_problem(&ctx, &teacher).await;
+        let problem = prepare_data::upload_test_case(&ctx, problem, &[("1 2\n", "3\n")]).await;
+        let (auth_key, auth_value) = prepare_data::auth_header(&create_token(&admin, &ctx).await);
+        // user2 is not in the course, but can see the problem during the contest
+        let user2 = users::Model::find_by_username(&ctx.db, "user2")
+            .await
+            .unwrap();
+        let (user_key, user_value) = prepare_data::auth_header(&create_token(&user2, &ctx).await);
+        let response = request
+            .get(&format!("/api/problems/{}", problem.id))
+            .add_header(user_key.clone(), user_value.clone())
+            .await;
+        assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+        let now = Local::now().naive_local();
+        let contest = |freeze_minutes| {
+            json!({
+                "name": "contest1",
+                "rule": 0,
+                "start_at": now - Duration::minutes(60),
+                "end_at": now + Duration::minutes(60),
+                "freeze_minutes": freeze_minutes,
+                "problem_ids": [problem.id],
+            })
+        };
+        let response = request
+            .post("/api/contests")
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&contest(0))
+            .await;
+        let contest_id = response.json::<serde_json::Value>()["id"].as_i64().unwrap();
+        let response = request
+            .post(&format!("/api/contests/{contest_id}/participants"))
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&json!({"users": ["user1", "user2"]}))
+            .await;
+        response.assert_status_ok();
+        let response = request
+            .get(&format!("/api/problems/{}", problem.id))
+            .add_header(user_key.clone(), user_value.clone())
+            .await;
+        response.assert_status_ok();
+
+        let submit = |username: &'static str, code: &'static str| {
+            let request = &request;
+            let ctx = &ctx;
+            let problem_id = problem.id;
+            async move {
+                let user = users::Model::find_by_username(&ctx.db, username)
+                    .await
+                    .unwrap();
+                let (key, value) = prepare_data::auth_header(&create_token(&user, ctx).await);
+                let response = request
+                    .post("/api/submissions")
+                    .add_header(key, value)
+                    .json(&json!({
+                        "problem_id": problem_id,
+                        "language": 2,
+                        "code": code,
+                    }))
+                    .await;
+                response.assert_status_ok();
+            }
+        };
+        submit("user1", "print(0)").await;
+        submit("user1", "print(sum(map(int, input().split())))").await;
+        submit("user2", "print(sum(map(int, input().split())))").await;
+        // submissions after the problem is solved are not counted
+        submit("user2", "print(").await;
+
+        // results of other participants are only shown on the scoreboard
+        let list_submissions = |key, value| {
+            let request = &request;
+            async move {
+                let response = request
+                    .get("/api/submissions")
+                    .add_query_param("problem_id", problem.id)
+                    .add_header(key, value)
+                    .await;
+                response.assert_status_ok();
+                response.json::<serde_json::Value>()["data"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|s| {
+                        (
+                            s["user"].as_str().unwrap().to_string(),
+                            s["id"].as_i64().unwrap(),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            }
+        };
+        let submissions = list_submissions(auth_key.clone(), auth_value.clone()).await;
+        assert_eq!(submissions.len(), 4);
+        let visible = list_submissions(user_key.clone(), user_value.clone()).await;
+        assert_eq!(visible.len(), 2);
+        assert!(visible.iter().all(|(username, _)| username == "user2"));
+        let (_, other_id) = submissions
+            .iter()
+            .find(|(username, _)| username == "user1")
+            .unwrap();
+        let response = request
+            .get(&format!("/api/submissions/{other_id}"))
+            .add_header(user_key.clone(), user_value.clone())
+            .await;
+        assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+        let scoreboard = |key, value| {
+            let request = &request;
+            async move {
+                let response = request
+                    .get(&format!("/api/contests/{contest_id}/scoreboard"))
+                    .add_header(key, value)
+                    .await;
+                response.assert_status_ok();
+                let scoreboard = response.json::<serde_json::Value>();
+                let rows = scoreboard["rows"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|r| {
+                        (
+                            r["username"].as_str().unwrap().to_string(),
+                            r["rank"].as_i64().unwrap(),
+                            r["solved"].as_i64().unwrap(),
+                            r["penalty"].as_i64().unwrap(),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                (scoreboard["frozen"].as_bool().unwrap(), rows)
+            }
+        };
+        assert_eq!(
+            scoreboard(user_key.clone(), user_value.clone()).await,
+            (
+                false,
+                vec![
+                    ("user2".to_string(), 1, 1, 60),
+                    ("user1".to_string(), 2, 1, 80),
+                ]
+            )
+        );
+
+        // submissions above are made after the freeze
+        let response = request
+            .put(&format!("/api/contests/{contest_id}"))
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&contest(90))
+            .await;
+        response.assert_status_ok();
+        assert_eq!(
+            scoreboard(user_key.clone(), user_value.clone()).await,
+            (
+                true,
+                vec![
+                    ("user1".to_string(), 1, 0, 0),
+                    ("user2".to_string(), 1, 0, 0),
+                ]
+            )
+        );
+        assert_eq!(
+            scoreboard(auth_key, auth_value).await,
+            (
+                false,
+                vec![
+                    ("user2".to_string(), 1, 1, 60),
+                    ("user1".to_string(), 2, 1, 80),
+                ]
+            )
+        );
+
+        // users outside the contest cannot see the scoreboard
+        let (teacher_key, teacher_value) =
+            prepare_data::auth_header(&create_token(&teacher, &ctx).await);
+        let response = request
+            .get(&format!("/api/contests/{contest_id}/scoreboard"))
+            .add_header(teacher_key, teacher_value)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+    })
+    .await;
+}
@@ -0,0 +1,286 @@
+use axum::http::StatusCode;
+use insta::{assert_debug_snapshot, with_settings};
+use loco_rs::testing;
+use normal_oj::{
+    app::App,
+    models::{courses, users},
+};
+use serde_json::json;
+use serial_test::serial;
+
+use super::{create_token, prepare_data};
+
+macro_rules! configure_insta {
+    () => {
+        crate::configure_insta!("course_request");
+    };
+}
+
+#[tokio::test]
+#[serial]
+async fn teacher_can_manage_members() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let teacher = users::Model::find_by_username(&ctx.db, "teacher1")
+            .await
+            .unwrap();
+        let (auth_key, auth_value) = prepare_data::auth_header(&create_token(&teacher, &ctx).await);
+
+        let response = request
+            .post("/api/courses/course1/members")
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&json!({"users": ["user2"], "role": "ta"}))
+            .await;
+        response.assert_status_ok();
+
+        let response = request
+            .delete("/api/courses/course1/members/user1")
+            .add_header(auth_key.clone(), auth_value.clone())
+            .await;
+        response.assert_status_ok();
+
+        let response = request
+            .get("/api/courses/course1/members")
+            .add_header(auth_key, auth_value)
+            .await;
+        response.assert_status_ok();
+
+        with_settings!({
+            filters => testing::cleanup_user_model()
+        }, {
+            assert_debug_snapshot!(response.json::<serde_json::Value>());
+        });
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn student_cannot_manage_members() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let user1 = users::Model::find_by_username(&ctx.db, "user1")
+            .await
+            .unwrap();
+        let (auth_key, auth_value) = prepare_data::auth_header(&create_token(&user1, &ctx).await);
+
+        // members can view other members
+        let response = request
+            .get("/api/courses/course1/members")
+            .add_header(auth_key.clone(), auth_value.clone())
+            .await;
+        response.assert_status_ok();
+
+        let response = request
+            .post("/api/courses/course1/members")
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&json!({"users": ["user2"], "role": "student"}))
+            .await;
+        response.assert_status_forbidden();
+
+        let response = request
+            .delete("/api/courses/course1/members/user1")
+            .add_header(auth_key, auth_value)
+            .await;
+        response.assert_status_forbidden();
+
+        // non-members cannot
+        let user2 = users::Model::find_by_username(&ctx.db, "user2")
+            .await
+            .unwrap();
+        let (auth_key, auth_value) = prepare_data::auth_header(&create_token(&user2, &ctx).await);
+        let response = request
+            .get("/api/courses/course1/members")
+            .add_header(auth_key, auth_value)
+            .await;
+        response.assert_status_forbidden();
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_add_unknown_user_or_remove_teacher() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let admin = users::Model::find_by_username(&ctx.db, "first_admin")
+            .await
+            .unwrap();
+        let (auth_key, auth_value) = prepare_data::auth_header(&create_token(&admin, &ctx).await);
+
+        let response = request
+            .post("/api/courses/course1/members")
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&json!({"users": ["user2", "nobody"], "role": "student"}))
+            .await;
+        response.assert_status(StatusCode::NOT_FOUND);
+
+        let response = request
+            .delete("/api/courses/course1/members/teacher1")
+            .add_header(auth_key.clone(), auth_value.clone())
+            .await;
+        response.assert_status_bad_request();
+
+        // nothing is changed by the failed request
+        let response = request
+            .get("/api/courses/course1/members")
+            .add_header(auth_key, auth_value)
+            .await;
+        let members = response.json::<serde_json::Value>()["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["username"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(members, ["teacher1", "user1"]);
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn admin_can_create_edit_and_delete_course() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let admin = users::Model::find_by_username(&ctx.db, "first_admin")
+            .await
+            .unwrap();
+        let (auth_key, auth_value) = prepare_data::auth_header(&create_token(&admin, &ctx).await);
+
+        let response = request
+            .post("/api/courses")
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&json!({"name": "course2", "teacher": "teacher1"}))
+            .await;
+        response.assert_status(StatusCode::CREATED);
+
+        let response = request
+            .post("/api/courses")
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&json!({"name": "course2", "teacher": "teacher1"}))
+            .await;
+        response.assert_status(StatusCode::CONFLICT);
+
+        // students cannot be teacher of a course
+        let response = request
+            .post("/api/courses")
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&json!({"name": "course3", "teacher": "user1"}))
+            .await;
+        response.assert_status_bad_request();
+
+        let response = request
+            .put("/api/courses/course2")
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&json!({"name": "course3", "teacher": "first_admin"}))
+            .await;
+        response.assert_status_ok();
+        let course = courses::Model::find_by_name(&ctx.db, "course3")
+            .await
+            .unwrap();
+        assert_eq!(course.teacher_id, admin.id);
+
+        let response = request
+            .delete("/api/courses/course3")
+            .add_header(auth_key.clone(), auth_value.clone())
+            .await;
+        response.assert_status_ok();
+        assert!(courses::Model::find_by_name(&ctx.db, "course3")
+            .await
+            .is_err());
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn teacher_cannot_create_or_delete_course() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let teacher = users::Model::find_by_username(&ctx.db, "teacher1")
+            .await
+            .unwrap();
+        let (auth_key, auth_value) = prepare_data::auth_header(&create_token(&teacher, &ctx).await);
+
+        let response = request
+            .post("/api/courses")
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&json!({"name": "course2", "teacher": "teacher1"}))
+            .await;
+        response.assert_status_forbidden();
+
+        let response = request
+            .put("/api/courses/course1")
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&json!({"name": "course2"}))
+            .await;
+        response.assert_status_forbidden();
+
+        let response = request
+            .delete("/api/courses/course1")
+            .add_header(auth_key, auth_value)
+            .await;
+        response.assert_status_forbidden();
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn only_members_can_read_course() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        for (username, visible) in [("user1", true), ("teacher1", true), ("user2", false)] {
+            let user = users::Model::find_by_username(&ctx.db, username)
+                .await
+                .unwrap();
+            let (auth_key, auth_value) =
+                prepare_data::auth_header(&create_token(&user, &ctx).await);
+
+            let response = request
+                .get("/api/courses")
+                .add_header(auth_key.clone(), auth_value.clone())
+                .await;
+            response.assert_status_ok();
+            let names = response
+                .json::<Vec<serde_json::Value>>()
+                .iter()
+                .map(|c| c["name"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>();
+            let expected: &[&str] = if visible { &["course1"] } else { &[] };
+            assert_eq!(names, expected, "viewer: {username}");
+
+            let response = request
+                .get("/api/courses/course1")
+                .add_header(auth_key, auth_value)
+                .await;
+            if visible {
+                response.assert_status_ok();
+            } else {
+                response.assert_status_forbidden();
+            }
+        }
+
+        let response = request.get("/api/courses").await;
+        response.assert_status_unauthorized();
+    })
+    .await;
+}
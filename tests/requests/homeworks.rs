@@ -0,0 +1,235 @@
+use axum::http::StatusCode;
+use chrono::{Duration, Local};
+use loco_rs::testing;
+use normal_oj::{app::App, models::users};
+use serde_json::json;
+use serial_test::serial;
+
+use super::{create_token, prepare_data};
+
+#[tokio::test]
+#[serial]
+async fn teacher_can_manage_homeworks() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let teacher = users::Model::find_by_username(&ctx.db, "teacher1")
+            .await
+            .unwrap();
+        let problem = prepare_data::create_problem(&ctx, &teacher).await;
+        let (auth_key, auth_value) = prepare_data::auth_header(&create_token(&teacher, &ctx).await);
+        let params = json!({
+            "name": "hw1",
+            "start_at": "2024-07-01T08:00:00",
+            "end_at": "2024-07-08T08:00:00",
+            "problem_ids": [problem.id],
+        });
+
+        let response = request
+            .post("/api/courses/course1/homeworks")
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&params)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let homework_id = response.json::<serde_json::Value>()["id"].as_i64().unwrap();
+
+        let response = request
+            .post("/api/courses/course1/homeworks")
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&params)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CONFLICT);
+
+        // the homework should end after it starts
+        let response = request
+            .put(&format!("/api/courses/course1/homeworks/{homework_id}"))
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&json!({
+                "name": "hw1",
+                "start_at": "2024-07-01T08:00:00",
+                "end_at": "2024-06-30T08:00:00",
+            }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+        let response = request
+            .put(&format!("/api/courses/course1/homeworks/{homework_id}"))
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&json!({
+                "name": "hw1-renamed",
+                "description": "first homework",
+                "start_at": "2024-07-01T08:00:00",
+                "end_at": "2024-07-08T08:00:00",
+                "late_end_at": "2024-07-09T08:00:00",
+                "penalty": 20,
+            }))
+            .await;
+        response.assert_status_ok();
+        let homework = response.json::<serde_json::Value>();
+        assert_eq!(homework["name"], "hw1-renamed");
+        assert_eq!(homework["problem_ids"], json!([]));
+
+        // students can read homeworks but cannot manage them
+        let student = users::Model::find_by_username(&ctx.db, "user1")
+            .await
+            .unwrap();
+        let (student_key, student_value) =
+            prepare_data::auth_header(&create_token(&student, &ctx).await);
+        let response = request
+            .get("/api/courses/course1/homeworks")
+            .add_header(student_key.clone(), student_value.clone())
+            .await;
+        response.assert_status_ok();
+        assert_eq!(
+            response.json::<serde_json::Value>()["data"][0]["penalty"],
+            20
+        );
+        let response = request
+            .delete(&format!("/api/courses/course1/homeworks/{homework_id}"))
+            .add_header(student_key, student_value)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+        // users outside the course cannot see its homeworks
+        let outsider = users::Model::find_by_username(&ctx.db, "user2")
+            .await
+            .unwrap();
+        let (outsider_key, outsider_value) =
+            prepare_data::auth_header(&create_token(&outsider, &ctx).await);
+        let response = request
+            .get(&format!("/api/courses/course1/homeworks/{homework_id}"))
+            .add_header(outsider_key, outsider_value)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+        let response = request
+            .delete(&format!("/api/courses/course1/homeworks/{homework_id}"))
+            .add_header(auth_key.clone(), auth_value.clone())
+            .await;
+        response.assert_status_ok();
+        let response = request
+            .get("/api/courses/course1/homeworks")
+            .add_header(auth_key, auth_value)
+            .await;
+        assert_eq!(response.json::<serde_json::Value>()["data"], json!([]));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn student_can_only_submit_during_homework() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let teacher = users::Model::find_by_username(&ctx.db, "teacher1")
+            .await
+            .unwrap();
+        let problem = prepare_data::create_problem(&ctx, &teacher).await;
+        let problem = prepare_data::upload_test_case(&ctx, problem, &[("1 2\n", "3\n")]).await;
+        let (auth_key, auth_value) = prepare_data::auth_header(&create_token(&teacher, &ctx).await);
+        let student = users::Model::find_by_username(&ctx.db, "user1")
+            .await
+            .unwrap();
+        let (student_key, student_value) =
+            prepare_data::auth_header(&create_token(&student, &ctx).await);
+
+        let now = Local::now().naive_local();
+        let response = request
+            .post("/api/courses/course1/homeworks")
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&json!({
+                "name": "hw1",
+                "start_at": now - Duration::days(2),
+                "end_at": now - Duration::days(1),
+                "penalty": 20,
+                "problem_ids": [problem.id],
+            }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let homework_id = response.json::<serde_json::Value>()["id"].as_i64().unwrap();
+
+        let submission = json!({
+            "problem_id": problem.id,
+            "language": 2,
+            "code": "print(sum(map(int, input().split())))",
+        });
+        let response = request
+            .post("/api/submissions")
+            .add_header(student_key.clone(), student_value.clone())
+            .json(&submission)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+        // the teacher is not limited by the homework
+        let response = request
+            .post("/api/submissions")
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&submission)
+            .await;
+        response.assert_status_ok();
+
+        // late submissions are penalized
+        let response = request
+            .put(&format!("/api/courses/course1/homeworks/{homework_id}"))
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&json!({
+                "name": "hw1",
+                "start_at": now - Duration::days(2),
+                "end_at": now - Duration::days(1),
+                "late_end_at": now + Duration::days(1),
+                "penalty": 20,
+                "problem_ids": [problem.id],
+            }))
+            .await;
+        response.assert_status_ok();
+        let response = request
+            .post("/api/submissions")
+            .add_header(student_key.clone(), student_value.clone())
+            .json(&submission)
+            .await;
+        response.assert_status_ok();
+        assert_eq!(response.json::<serde_json::Value>()["data"]["score"], 100);
+
+        let response = request
+            .get(&format!(
+                "/api/courses/course1/homeworks/{homework_id}/scoreboard"
+            ))
+            .add_header(student_key, student_value)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+        let response = request
+            .get(&format!(
+                "/api/courses/course1/homeworks/{homework_id}/scoreboard"
+            ))
+            .add_header(auth_key.clone(), auth_value.clone())
+            .await;
+        response.assert_status_ok();
+        assert_eq!(
+            response.json::<serde_json::Value>(),
+            json!({
+                "problem_ids": [problem.id],
+                "rows": [{
+                    "username": "user1",
+                    "displayed_name": "",
+                    "scores": [80],
+                    "total": 80,
+                }],
+            })
+        );
+
+        let response = request
+            .get(&format!(
+                "/api/courses/course1/homeworks/{homework_id}/scoreboard/csv"
+            ))
+            .add_header(auth_key, auth_value)
+            .await;
+        response.assert_status_ok();
+        assert_eq!(response.header("content-type"), "text/csv");
+        assert_eq!(
+            response.text(),
+            format!("username,{},total\nuser1,80,80\n", problem.id)
+        );
+    })
+    .await;
+}
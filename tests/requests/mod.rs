@@ -1,6 +1,10 @@
 mod auth;
+mod contests;
+mod courses;
+mod homeworks;
 mod prepare_data;
 mod problems;
+mod submissions;
 mod user;
 
 use loco_rs::app::AppContext;
@@ -1,6 +1,16 @@
+use std::io::Write;
+
 use axum::http::{HeaderName, HeaderValue};
 use loco_rs::{app::AppContext, TestServer};
-use normal_oj::{models::users, views::auth::LoginResponse};
+use normal_oj::{
+    models::{
+        problems::{self, Type, Visibility},
+        users,
+    },
+    views::auth::LoginResponse,
+};
+use sea_orm::IntoActiveModel;
+use zip::write::SimpleFileOptions;
 
 const USER_EMAIL: &str = "test@loco.com";
 const USER_PASSWORD: &str = "1234";
@@ -59,3 +69,100 @@ pub fn auth_header(token: &str) -> (HeaderName, HeaderValue) {
 
     (HeaderName::from_static("authorization"), auth_header_value)
 }
+
+/// Create a visible problem of `course1` with single task owned by `owner`.
+pub async fn create_problem(ctx: &AppContext, owner: &users::Model) -> problems::Model {
+    create_problem_with_tasks(
+        ctx,
+        owner,
+        vec![problems::tasks::AddParams {
+            test_case_count: 1,
+            score: 100,
+            time_limit: 1000,
+            memory_limit: 65535,
+        }],
+    )
+    .await
+}
+
+/// Create a visible problem of `course1` with given tasks owned by `owner`, who
+/// should be able to manage the course.
+pub async fn create_problem_with_tasks(
+    ctx: &AppContext,
+    owner: &users::Model,
+    tasks: Vec<problems::tasks::AddParams>,
+) -> problems::Model {
+    problems::Model::add(
+        &ctx.db,
+        &problems::AddParams {
+            owner: owner.clone(),
+            courses: vec!["course1".to_string()],
+            tags: vec![],
+            name: "test-problem".to_string(),
+            status: Some(Visibility::Show),
+            description: problems::descriptions::AddParams {
+                description: String::new(),
+                input: String::new(),
+                output: String::new(),
+                hint: String::new(),
+                sample_input: vec![],
+                sample_output: vec![],
+            },
+            r#type: Some(Type::Normal),
+            allowed_language: None,
+            quota: None,
+            tasks,
+        },
+    )
+    .await
+    .unwrap()
+}
+
+/// Store a test case zip for the problem's first task, each item of `cases`
+/// is a pair of (STDIN, STDOUT).
+pub async fn upload_test_case(
+    ctx: &AppContext,
+    problem: problems::Model,
+    cases: &[(&str, &str)],
+) -> problems::Model {
+    upload_task_test_cases(ctx, problem, &[cases]).await
+}
+
+/// Store a test case zip containing cases of each task.
+pub async fn upload_task_test_cases(
+    ctx: &AppContext,
+    problem: problems::Model,
+    tasks: &[&[(&str, &str)]],
+) -> problems::Model {
+    let mut buf = std::io::Cursor::new(Vec::new());
+    {
+        let mut test_case = zip::ZipWriter::new(&mut buf);
+        let opt = SimpleFileOptions::default();
+        for (i, cases) in tasks.iter().enumerate() {
+            for (j, (input, output)) in cases.iter().enumerate() {
+                test_case
+                    .start_file(format!("test-case/{i:02}{j:02}/STDIN"), opt)
+                    .unwrap();
+                test_case.write_all(input.as_bytes()).unwrap();
+                test_case
+                    .start_file(format!("test-case/{i:02}{j:02}/STDOUT"), opt)
+                    .unwrap();
+                test_case.write_all(output.as_bytes()).unwrap();
+            }
+        }
+    }
+
+    let test_case_id = uuid::Uuid::new_v4().to_string();
+    ctx.storage
+        .upload(
+            problems::test_case_path(&test_case_id).as_path(),
+            &buf.into_inner().into(),
+        )
+        .await
+        .unwrap();
+    problem
+        .into_active_model()
+        .update_test_case_id(&ctx.db, Some(test_case_id))
+        .await
+        .unwrap()
+}
@@ -6,11 +6,18 @@ use loco_rs::testing;
 use normal_oj::{
     app::App,
     models::problems::{self, Type, Visibility},
-    models::users,
+    models::{
+        _entities::sea_orm_active_enums::CourseRole,
+        course_members,
+        submissions::{self, Status},
+        users, Language,
+    },
 };
-use sea_orm::ConnectionTrait;
+use rstest::rstest;
+use sea_orm::{ActiveModelTrait, ActiveValue, ConnectionTrait, IntoActiveModel};
 use serde_json::json;
 use serial_test::serial;
+use sha2::{Digest, Sha256};
 use zip::write::SimpleFileOptions;
 
 use super::{create_token, prepare_data};
@@ -68,6 +75,34 @@ async fn student_cannot_create_problem() {
     .await;
 }
 
+#[rstest]
+#[case::empty(0)]
+#[case::unknown_language(8)]
+#[tokio::test]
+#[serial]
+async fn cannot_create_problem_with_invalid_allowed_language(#[case] allowed_language: i32) {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let first_admin = users::Model::find_by_username(&ctx.db, "first_admin")
+            .await
+            .unwrap();
+        let (auth_key, auth_value) =
+            prepare_data::auth_header(&create_token(&first_admin, &ctx).await);
+        let mut payload = create_problem_payload();
+        payload["allowed_language"] = json!(allowed_language);
+        let response = request
+            .post("/api/problems")
+            .add_header(auth_key, auth_value)
+            .json(&payload)
+            .await;
+        response.assert_status_bad_request();
+    })
+    .await;
+}
+
 async fn make_test_case<C: ConnectionTrait>(
     db: &C,
     problem: &problems::Model,
@@ -111,6 +146,7 @@ async fn admin_can_create_problem_and_test_case() {
             &problems::AddParams {
                 owner: first_admin,
                 courses: vec![],
+                tags: vec![],
                 name: "test-course".to_string(),
                 status: Some(Visibility::Show),
                 description: problems::descriptions::AddParams {
@@ -135,6 +171,20 @@ async fn admin_can_create_problem_and_test_case() {
         .await
         .unwrap();
         let test_case_content = make_test_case(&ctx.db, &problem).await.unwrap();
+        let not_zip = Part::bytes(test_case_content.clone())
+            .file_name("test-case.zip")
+            .mime_type("application/octet-stream");
+        let response = request
+            .put(&format!("/api/problems/{}", problem.id))
+            .add_header(auth_key.clone(), auth_value.clone())
+            .multipart(MultipartForm::new().add_part("case", not_zip))
+            .await;
+        response.assert_status_bad_request();
+        assert_eq!(
+            response.json::<serde_json::Value>()["msg"],
+            "Test case not found, expected a multipart field of type application/x-zip"
+        );
+
         let test_case = Part::bytes(test_case_content.clone())
             .file_name("test-case.zip")
             .mime_type("application/x-zip");
@@ -171,11 +221,15 @@ async fn view_single_problem() {
             .await
             .unwrap();
         let (auth_key, auth_value) = prepare_data::auth_header(&user.token);
+        course_members::Model::enroll(&ctx.db, 1, user.user.id)
+            .await
+            .unwrap();
         let problem = problems::Model::add(
             &ctx.db,
             &problems::AddParams {
                 owner: first_admin,
-                courses: vec![],
+                courses: vec!["course1".to_string()],
+                tags: vec![],
                 name: "test-course".to_string(),
                 status: Some(Visibility::Show),
                 description: problems::descriptions::AddParams {
@@ -213,3 +267,994 @@ async fn view_single_problem() {
     })
     .await;
 }
+
+#[tokio::test]
+#[serial]
+async fn can_assign_problem_to_courses() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let first_admin = users::Model::find_by_username(&ctx.db, "first_admin")
+            .await
+            .unwrap();
+        let (auth_key, auth_value) =
+            prepare_data::auth_header(&create_token(&first_admin, &ctx).await);
+
+        let mut payload = create_problem_payload();
+        payload["type"] = json!(0);
+        payload["courses"] = json!(["course1"]);
+        let response = request
+            .post("/api/problems")
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&payload)
+            .await;
+        response.assert_status_ok();
+        let problem_id = response.json::<serde_json::Value>()["id"].clone();
+
+        // problem without course
+        let mut other = payload.clone();
+        other["courses"] = json!([]);
+        let response = request
+            .post("/api/problems")
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&other)
+            .await;
+        response.assert_status_ok();
+
+        payload["courses"] = json!(["no-such-course"]);
+        let response = request
+            .post("/api/problems")
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&payload)
+            .await;
+        response.assert_status_bad_request();
+
+        let response = request
+            .get(&format!("/api/problems/{problem_id}"))
+            .add_header(auth_key.clone(), auth_value.clone())
+            .await;
+        response.assert_status_ok();
+        assert_eq!(
+            response.json::<serde_json::Value>()["data"]["courses"],
+            json!(["course1"])
+        );
+
+        let response = request
+            .get("/api/problems")
+            .add_query_param("course", "course1")
+            .add_header(auth_key, auth_value)
+            .await;
+        response.assert_status_ok();
+        let ids = response.json::<serde_json::Value>()["data"]["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["id"].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, [problem_id]);
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_filter_problems_by_tags() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let first_admin = users::Model::find_by_username(&ctx.db, "first_admin")
+            .await
+            .unwrap();
+        let (auth_key, auth_value) =
+            prepare_data::auth_header(&create_token(&first_admin, &ctx).await);
+
+        let mut ids = vec![];
+        for tags in [json!(["dp", "graph", "dp"]), json!(["dp"]), json!([])] {
+            let mut payload = create_problem_payload();
+            payload["type"] = json!(0);
+            payload["tags"] = tags;
+            let response = request
+                .post("/api/problems")
+                .add_header(auth_key.clone(), auth_value.clone())
+                .json(&payload)
+                .await;
+            response.assert_status_ok();
+            ids.push(response.json::<serde_json::Value>()["id"].clone());
+        }
+
+        let mut payload = create_problem_payload();
+        payload["type"] = json!(0);
+        payload["tags"] = json!([""]);
+        let response = request
+            .post("/api/problems")
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&payload)
+            .await;
+        response.assert_status_bad_request();
+
+        let response = request
+            .get(&format!("/api/problems/{}", ids[0]))
+            .add_header(auth_key.clone(), auth_value.clone())
+            .await;
+        response.assert_status_ok();
+        assert_eq!(
+            response.json::<serde_json::Value>()["data"]["tags"],
+            json!(["dp", "graph"])
+        );
+
+        for (query, expected) in [
+            ("dp,graph", vec![ids[0].clone()]),
+            ("dp", vec![ids[0].clone(), ids[1].clone()]),
+            ("graph,greedy", vec![]),
+        ] {
+            let response = request
+                .get("/api/problems")
+                .add_query_param("tags", query)
+                .add_header(auth_key.clone(), auth_value.clone())
+                .await;
+            response.assert_status_ok();
+            let data = response.json::<serde_json::Value>()["data"]["results"].clone();
+            let found = data
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|p| p["id"].clone())
+                .collect::<Vec<_>>();
+            assert_eq!(found, expected, "tags={query}");
+            if query == "dp,graph" {
+                assert_eq!(data[0]["tags"], json!(["dp", "graph"]));
+            }
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_only_view_visible_problems() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let first_admin = users::Model::find_by_username(&ctx.db, "first_admin")
+            .await
+            .unwrap();
+        let (auth_key, auth_value) =
+            prepare_data::auth_header(&create_token(&first_admin, &ctx).await);
+        let mut ids = vec![];
+        for (status, courses) in [
+            (Visibility::Show, json!(["course1"])),
+            (Visibility::Hidden, json!(["course1"])),
+            (Visibility::Show, json!([])),
+        ] {
+            let mut payload = create_problem_payload();
+            payload["type"] = json!(0);
+            payload["status"] = json!(status);
+            payload["courses"] = courses;
+            let response = request
+                .post("/api/problems")
+                .add_header(auth_key.clone(), auth_value.clone())
+                .json(&payload)
+                .await;
+            response.assert_status_ok();
+            ids.push(response.json::<serde_json::Value>()["id"].clone());
+        }
+
+        // user1 is a student, user2 is a TA and teacher1 is the teacher of course1
+        course_members::Model::upsert(&ctx.db, 1, 2, CourseRole::Ta)
+            .await
+            .unwrap();
+        for (username, visible) in [
+            ("user1", vec![ids[0].clone()]),
+            ("user2", vec![ids[0].clone(), ids[1].clone()]),
+            ("teacher1", vec![ids[0].clone(), ids[1].clone()]),
+            ("first_admin", ids.clone()),
+        ] {
+            let user = users::Model::find_by_username(&ctx.db, username)
+                .await
+                .unwrap();
+            let (auth_key, auth_value) =
+                prepare_data::auth_header(&create_token(&user, &ctx).await);
+            let response = request
+                .get("/api/problems")
+                .add_header(auth_key.clone(), auth_value.clone())
+                .await;
+            response.assert_status_ok();
+            let found = response.json::<serde_json::Value>()["data"]["results"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|p| p["id"].clone())
+                .collect::<Vec<_>>();
+            assert_eq!(found, visible, "user={username}");
+
+            for id in &ids {
+                let response = request
+                    .get(&format!("/api/problems/{id}"))
+                    .add_header(auth_key.clone(), auth_value.clone())
+                    .await;
+                if visible.contains(id) {
+                    response.assert_status_ok();
+                } else {
+                    response.assert_status_forbidden();
+                }
+            }
+        }
+
+        let user1 = users::Model::find_by_username(&ctx.db, "user1")
+            .await
+            .unwrap();
+        let (auth_key, auth_value) = prepare_data::auth_header(&create_token(&user1, &ctx).await);
+        request
+            .post("/api/submissions")
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&json!({"problem_id": ids[1], "language": 0, "code": ""}))
+            .await
+            .assert_status_forbidden();
+        request
+            .get("/api/problems/65535")
+            .add_header(auth_key, auth_value)
+            .await
+            .assert_status_not_found();
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_paginate_and_sort_problems() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let first_admin = users::Model::find_by_username(&ctx.db, "first_admin")
+            .await
+            .unwrap();
+        let (auth_key, auth_value) =
+            prepare_data::auth_header(&create_token(&first_admin, &ctx).await);
+        let mut ids = vec![];
+        for name in ["c", "a", "b"] {
+            let mut payload = create_problem_payload();
+            payload["type"] = json!(0);
+            payload["name"] = json!(name);
+            let response = request
+                .post("/api/problems")
+                .add_header(auth_key.clone(), auth_value.clone())
+                .json(&payload)
+                .await;
+            response.assert_status_ok();
+            ids.push(response.json::<serde_json::Value>()["id"].as_i64().unwrap());
+        }
+
+        // only "b" is solved, "a" has a wrong answer
+        let user1 = users::Model::find_by_username(&ctx.db, "user1")
+            .await
+            .unwrap();
+        for (problem_id, status) in [(ids[2], Status::Accepted), (ids[1], Status::WrongAnswer)] {
+            let problem = problems::Model::find_by_id(&ctx.db, i32::try_from(problem_id).unwrap())
+                .await
+                .unwrap();
+            let submission = submissions::Model::add(
+                &ctx.db,
+                &submissions::AddParams {
+                    user: user1.clone(),
+                    problem,
+                    language: Language::C,
+                    code_id: String::new(),
+                },
+            )
+            .await
+            .unwrap();
+            let mut submission = submission.into_active_model();
+            submission.status = ActiveValue::set(status as i32);
+            submission.update(&ctx.db).await.unwrap();
+        }
+
+        for (query, expected, total_pages) in [
+            (vec![("count", "2")], vec![ids[0], ids[1]], 2),
+            (vec![("count", "2"), ("offset", "2")], vec![ids[2]], 2),
+            (vec![("offset", "1")], vec![ids[1], ids[2]], 1),
+            (vec![("sort_by", "name")], vec![ids[1], ids[2], ids[0]], 1),
+            (
+                vec![("sort_by", "name"), ("order", "desc")],
+                vec![ids[0], ids[2], ids[1]],
+                1,
+            ),
+            (
+                vec![("sort_by", "ac_rate"), ("order", "desc")],
+                vec![ids[2], ids[1], ids[0]],
+                1,
+            ),
+            (
+                vec![("sort_by", "ac_rate")],
+                vec![ids[0], ids[1], ids[2]],
+                1,
+            ),
+        ] {
+            let mut req = request
+                .get("/api/problems")
+                .add_header(auth_key.clone(), auth_value.clone());
+            for (key, value) in &query {
+                req = req.add_query_param(key, value);
+            }
+            let response = req.await;
+            response.assert_status_ok();
+            let data = response.json::<serde_json::Value>()["data"].clone();
+            let found = data["results"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|p| p["id"].as_i64().unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(found, expected, "query={query:?}");
+            assert_eq!(data["pagination"]["total_pages"], total_pages);
+        }
+
+        request
+            .get("/api/problems")
+            .add_query_param("sort_by", "owner")
+            .add_header(auth_key, auth_value)
+            .await
+            .assert_status_bad_request();
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_edit_problem() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let teacher = users::Model::find_by_username(&ctx.db, "teacher1")
+            .await
+            .unwrap();
+        let problem = prepare_data::create_problem(&ctx, &teacher).await;
+        let problem = prepare_data::upload_test_case(&ctx, problem, &[("1 2\n", "3\n")]).await;
+        let path = format!("/api/problems/manage/{}", problem.id);
+
+        let student = users::Model::find_by_username(&ctx.db, "user1")
+            .await
+            .unwrap();
+        let (auth_key, auth_value) = prepare_data::auth_header(&create_token(&student, &ctx).await);
+        request
+            .patch(&path)
+            .add_header(auth_key, auth_value)
+            .json(&json!({"name": "hacked"}))
+            .await
+            .assert_status_forbidden();
+
+        let (auth_key, auth_value) = prepare_data::auth_header(&create_token(&teacher, &ctx).await);
+        let response = request
+            .patch(&path)
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&json!({
+                "name": "A + B",
+                "quota": 10,
+                "allowed_language": 1,
+                "tags": ["math"],
+                "description": create_problem_payload()["description"],
+                "tasks": [{
+                    "test_case_count": 1,
+                    "score": 50,
+                    "time_limit": 2000,
+                    "memory_limit": 65535,
+                }],
+            }))
+            .await;
+        response.assert_status_ok();
+        assert_eq!(
+            response.json::<serde_json::Value>()["test_case_stale"],
+            false
+        );
+
+        let response = request
+            .get(&format!("/api/problems/{}", problem.id))
+            .add_header(auth_key.clone(), auth_value.clone())
+            .await;
+        response.assert_status_ok();
+        let data = response.json::<serde_json::Value>()["data"].clone();
+        assert_eq!(data["problem_name"], "A + B");
+        assert_eq!(data["quota"], 10);
+        assert_eq!(data["allowed_language"], json!(["c"]));
+        assert_eq!(data["tags"], json!(["math"]));
+        assert_eq!(data["description"]["hint"], "use +");
+        assert_eq!(data["test_case"][0]["score"], 50);
+        assert_eq!(data["courses"], json!(["course1"]));
+
+        // nothing is changed if any field is invalid
+        for payload in [
+            json!({"name": "renamed", "tags": [""]}),
+            json!({"name": "renamed", "courses": ["no-such-course"]}),
+        ] {
+            request
+                .patch(&path)
+                .add_header(auth_key.clone(), auth_value.clone())
+                .json(&payload)
+                .await
+                .assert_status_bad_request();
+        }
+        let problem = problems::Model::find_by_id(&ctx.db, problem.id)
+            .await
+            .unwrap();
+        assert_eq!(problem.name, "A + B");
+
+        let response = request
+            .patch(&path)
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&json!({
+                "tasks": [{
+                    "test_case_count": 2,
+                    "score": 100,
+                    "time_limit": 1000,
+                    "memory_limit": 65535,
+                }],
+            }))
+            .await;
+        response.assert_status_ok();
+        assert_eq!(
+            response.json::<serde_json::Value>()["test_case_stale"],
+            true
+        );
+
+        // only managers can see the flag, and nobody can submit until the test
+        // case is uploaded again
+        let detail = |key, value| {
+            let request = &request;
+            async move {
+                let response = request
+                    .get(&format!("/api/problems/{}", problem.id))
+                    .add_header(key, value)
+                    .await;
+                response.assert_status_ok();
+                response.json::<serde_json::Value>()["data"]["test_case_stale"].clone()
+            }
+        };
+        let submit = |key, value| {
+            let request = &request;
+            async move {
+                request
+                    .post("/api/submissions")
+                    .add_header(key, value)
+                    .json(&json!({
+                        "problem_id": problem.id,
+                        "language": 0,
+                        "code": "int main() {}",
+                    }))
+                    .await
+            }
+        };
+        let (student_key, student_value) =
+            prepare_data::auth_header(&create_token(&student, &ctx).await);
+        assert_eq!(detail(auth_key.clone(), auth_value.clone()).await, true);
+        assert!(detail(student_key.clone(), student_value.clone())
+            .await
+            .is_null());
+        submit(student_key.clone(), student_value.clone())
+            .await
+            .assert_status_forbidden();
+
+        let problem = problems::Model::find_by_id(&ctx.db, problem.id)
+            .await
+            .unwrap();
+        prepare_data::upload_test_case(&ctx, problem, &[("1 2\n", "3\n"), ("2 3\n", "5\n")]).await;
+        assert_eq!(detail(auth_key, auth_value).await, false);
+        submit(student_key, student_value).await.assert_status_ok();
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_archive_and_delete_problem() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let teacher = users::Model::find_by_username(&ctx.db, "teacher1")
+            .await
+            .unwrap();
+        let problem = prepare_data::create_problem(&ctx, &teacher).await;
+        let problem = prepare_data::upload_test_case(&ctx, problem, &[("1 2\n", "3\n")]).await;
+        let student = users::Model::find_by_username(&ctx.db, "user1")
+            .await
+            .unwrap();
+        submissions::Model::add(
+            &ctx.db,
+            &submissions::AddParams {
+                user: student.clone(),
+                problem: problem.clone(),
+                language: Language::C,
+                code_id: String::new(),
+            },
+        )
+        .await
+        .unwrap();
+        let first_admin = users::Model::find_by_username(&ctx.db, "first_admin")
+            .await
+            .unwrap();
+
+        let student_auth = prepare_data::auth_header(&create_token(&student, &ctx).await);
+        let teacher_auth = prepare_data::auth_header(&create_token(&teacher, &ctx).await);
+        let admin_auth = prepare_data::auth_header(&create_token(&first_admin, &ctx).await);
+        let path = format!("/api/problems/manage/{}", problem.id);
+        let detail = format!("/api/problems/{}", problem.id);
+
+        request
+            .post(&format!("{path}/archive"))
+            .add_header(student_auth.0.clone(), student_auth.1.clone())
+            .await
+            .assert_status_forbidden();
+        request
+            .post(&format!("{path}/archive"))
+            .add_header(teacher_auth.0.clone(), teacher_auth.1.clone())
+            .await
+            .assert_status_ok();
+        for (key, value) in [&student_auth, &teacher_auth] {
+            request
+                .get(&detail)
+                .add_header(key.clone(), value.clone())
+                .await
+                .assert_status_forbidden();
+        }
+        let response = request
+            .get("/api/problems")
+            .add_header(admin_auth.0.clone(), admin_auth.1.clone())
+            .await;
+        response.assert_status_ok();
+        let results = response.json::<serde_json::Value>()["data"]["results"].clone();
+        assert_eq!(results[0]["id"], problem.id);
+        assert_eq!(results[0]["archived"], true);
+
+        request
+            .post(&format!("{path}/restore"))
+            .add_header(teacher_auth.0.clone(), teacher_auth.1.clone())
+            .await
+            .assert_status_forbidden();
+        request
+            .post(&format!("{path}/restore"))
+            .add_header(admin_auth.0.clone(), admin_auth.1.clone())
+            .await
+            .assert_status_ok();
+        request
+            .get(&detail)
+            .add_header(student_auth.0.clone(), student_auth.1.clone())
+            .await
+            .assert_status_ok();
+
+        request
+            .delete(&path)
+            .add_header(teacher_auth.0, teacher_auth.1)
+            .await
+            .assert_status_forbidden();
+        request
+            .delete(&path)
+            .add_header(admin_auth.0.clone(), admin_auth.1.clone())
+            .await
+            .assert_status_ok();
+        request
+            .get(&detail)
+            .add_header(admin_auth.0, admin_auth.1)
+            .await
+            .assert_status_not_found();
+
+        for table in ["problem_descriptions", "problem_tasks", "submissions"] {
+            let count = ctx
+                .db
+                .query_one(sea_orm::Statement::from_string(
+                    ctx.db.get_database_backend(),
+                    format!("SELECT COUNT(*) AS count FROM {table}"),
+                ))
+                .await
+                .unwrap()
+                .unwrap()
+                .try_get::<i64>("", "count")
+                .unwrap();
+            assert_eq!(count, 0, "{table} should be empty");
+        }
+        let test_case = ctx
+            .storage
+            .as_ref()
+            .download::<Vec<u8>>(&problems::test_case_path(&problem.test_case_id.unwrap()))
+            .await;
+        assert!(test_case.is_err());
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_download_test_case() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let teacher = users::Model::find_by_username(&ctx.db, "teacher1")
+            .await
+            .unwrap();
+        let empty = prepare_data::create_problem(&ctx, &teacher).await;
+        let problem = prepare_data::create_problem(&ctx, &teacher).await;
+        let problem = prepare_data::upload_test_case(&ctx, problem, &[("1 2\n", "3\n")]).await;
+        let expected: Vec<u8> = ctx
+            .storage
+            .as_ref()
+            .download(&problems::test_case_path(
+                problem.test_case_id.as_ref().unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        let path = format!("/api/problems/{}/test-case", problem.id);
+        for (username, allowed) in [("user1", false), ("teacher1", true), ("first_admin", true)] {
+            let user = users::Model::find_by_username(&ctx.db, username)
+                .await
+                .unwrap();
+            let (auth_key, auth_value) =
+                prepare_data::auth_header(&create_token(&user, &ctx).await);
+            let response = request.get(&path).add_header(auth_key, auth_value).await;
+            if allowed {
+                response.assert_status_ok();
+                assert_eq!(response.header("content-type"), "application/zip");
+                assert_eq!(response.as_bytes().to_vec(), expected);
+            } else {
+                response.assert_status_forbidden();
+            }
+        }
+
+        let (auth_key, auth_value) = prepare_data::auth_header(&create_token(&teacher, &ctx).await);
+        request
+            .get(&format!("/api/problems/{}/test-case", empty.id))
+            .add_header(auth_key, auth_value)
+            .await
+            .assert_status_not_found();
+
+        let path = format!("/api/problems/{}/testdata", problem.id);
+        request
+            .get(&path)
+            .add_query_param("token", "wrong-token")
+            .await
+            .assert_status_forbidden();
+        let response = request
+            .get(&path)
+            .add_query_param("token", "test-sandbox-token")
+            .await;
+        response.assert_status_ok();
+        assert_eq!(response.as_bytes().to_vec(), expected);
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_manage_test_case_versions() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let teacher = users::Model::find_by_username(&ctx.db, "teacher1")
+            .await
+            .unwrap();
+        let problem = prepare_data::create_problem(&ctx, &teacher).await;
+        let (auth_key, auth_value) = prepare_data::auth_header(&create_token(&teacher, &ctx).await);
+
+        let meta = json!({"tasks": [
+            {"caseCount": 1, "taskScore": 100, "memoryLimit": 65536, "timeLimit": 1000},
+        ]})
+        .to_string();
+        let contents = [
+            make_test_case_with_meta(&[1], None),
+            make_test_case(&ctx.db, &problem).await.unwrap(),
+            make_test_case_with_meta(&[1], Some(&meta)),
+        ];
+        for content in &contents {
+            let test_case = Part::bytes(content.clone())
+                .file_name("test-case.zip")
+                .mime_type("application/x-zip");
+            request
+                .put(&format!("/api/problems/manage/{}", problem.id))
+                .add_header(auth_key.clone(), auth_value.clone())
+                .multipart(MultipartForm::new().add_part("case", test_case))
+                .await
+                .assert_status_ok();
+        }
+
+        let list_path = format!("/api/problems/manage/{}/test-case/versions", problem.id);
+        let list_versions = || async {
+            let response = request
+                .get(&list_path)
+                .add_header(auth_key.clone(), auth_value.clone())
+                .await;
+            response.assert_status_ok();
+            response.json::<serde_json::Value>()["data"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|v| (v["id"].as_i64().unwrap() as i32, v["current"] == true))
+                .collect::<Vec<_>>()
+        };
+
+        // the latest version comes first
+        let versions = list_versions().await;
+        let ids = versions.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+        let currents = versions.iter().map(|(_, c)| *c).collect::<Vec<_>>();
+        assert_eq!(currents, [true, false, false]);
+        let response = request
+            .get(&list_path)
+            .add_header(auth_key.clone(), auth_value.clone())
+            .await;
+        let latest = &response.json::<serde_json::Value>()["data"][0];
+        assert_eq!(latest["uploader"], "teacher1");
+        assert_eq!(latest["size"], contents[2].len());
+        assert_eq!(
+            latest["sha256"],
+            format!("{:x}", Sha256::digest(&contents[2]))
+        );
+
+        let user = users::Model::find_by_username(&ctx.db, "user1")
+            .await
+            .unwrap();
+        let (user_key, user_value) = prepare_data::auth_header(&create_token(&user, &ctx).await);
+        request
+            .get(&list_path)
+            .add_header(user_key, user_value)
+            .await
+            .assert_status_forbidden();
+
+        // submissions remember the version they are judged against
+        let problem = problems::Model::find_by_id(&ctx.db, problem.id)
+            .await
+            .unwrap();
+        let submission = submissions::Model::add(
+            &ctx.db,
+            &submissions::AddParams {
+                user,
+                problem: problem.clone(),
+                language: Language::C,
+                code_id: "code".to_string(),
+            },
+        )
+        .await
+        .unwrap()
+        .into_active_model()
+        .record_test_case_version(&ctx.db)
+        .await
+        .unwrap();
+        assert_eq!(submission.test_case_version_id, Some(ids[0]));
+
+        let rollback_path = |id: i32| {
+            format!(
+                "/api/problems/manage/{}/test-case/versions/{id}/rollback",
+                problem.id
+            )
+        };
+        request
+            .post(&rollback_path(ids[2]))
+            .add_header(auth_key.clone(), auth_value.clone())
+            .await
+            .assert_status_ok();
+        let currents = list_versions()
+            .await
+            .iter()
+            .map(|(_, c)| *c)
+            .collect::<Vec<_>>();
+        assert_eq!(currents, [false, false, true]);
+        let response = request
+            .get(&format!("/api/problems/{}/test-case", problem.id))
+            .add_header(auth_key.clone(), auth_value.clone())
+            .await;
+        assert_eq!(response.as_bytes().to_vec(), contents[0]);
+        request
+            .post(&rollback_path(ids[0] + 1))
+            .add_header(auth_key.clone(), auth_value.clone())
+            .await
+            .assert_status_not_found();
+
+        // only the version neither in use nor judged against is removed
+        let removed =
+            problems::test_case_versions::Model::find_in_problem(&ctx.db, problem.id, ids[1])
+                .await
+                .unwrap();
+        request
+            .post(&format!("/api/problems/manage/{}/test-case/gc", problem.id))
+            .add_header(auth_key.clone(), auth_value.clone())
+            .await
+            .assert_status_ok();
+        let remaining = list_versions()
+            .await
+            .iter()
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        assert_eq!(remaining, [ids[0], ids[2]]);
+        let removed_file: Result<Vec<u8>, _> = ctx
+            .storage
+            .as_ref()
+            .download(&problems::test_case_path(&removed.test_case_id))
+            .await;
+        assert!(removed_file.is_err());
+    })
+    .await;
+}
+
+/// Build a test case zip with cases of given counts and an optional `meta.json`.
+fn make_test_case_with_meta(case_counts: &[i32], meta: Option<&str>) -> Vec<u8> {
+    let mut buf = std::io::Cursor::new(Vec::new());
+    {
+        let mut test_case = zip::ZipWriter::new(&mut buf);
+        let opt = SimpleFileOptions::default();
+        if let Some(meta) = meta {
+            test_case.start_file("meta.json", opt).unwrap();
+            test_case.write_all(meta.as_bytes()).unwrap();
+        }
+        for (task_i, count) in case_counts.iter().enumerate() {
+            for case_i in 0..*count {
+                for name in ["STDIN", "STDOUT"] {
+                    test_case
+                        .start_file(format!("test-case/{task_i:02}{case_i:02}/{name}"), opt)
+                        .unwrap();
+                    test_case.write_all(b"\n").unwrap();
+                }
+            }
+        }
+    }
+    buf.into_inner()
+}
+
+#[tokio::test]
+#[serial]
+async fn can_upload_test_case_with_meta() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let teacher = users::Model::find_by_username(&ctx.db, "teacher1")
+            .await
+            .unwrap();
+        let problem = prepare_data::create_problem(&ctx, &teacher).await;
+        let (auth_key, auth_value) = prepare_data::auth_header(&create_token(&teacher, &ctx).await);
+
+        let one_task = json!({"tasks": [
+            {"caseCount": 1, "taskScore": 100, "memoryLimit": 65536, "timeLimit": 1000},
+        ]})
+        .to_string();
+        let two_tasks = json!({"tasks": [
+            {"caseCount": 2, "taskScore": 30, "memoryLimit": 65536, "timeLimit": 1000},
+            {"caseCount": 1, "taskScore": 70, "memoryLimit": 131072, "timeLimit": 2000},
+        ]})
+        .to_string();
+        for (case_counts, meta, replace_tasks, ok) in [
+            (vec![1], Some(one_task.as_str()), false, true),
+            (vec![1], None, false, true),
+            // meta.json should agree with problem tasks
+            (vec![2, 1], Some(two_tasks.as_str()), false, false),
+            (vec![2, 1], None, true, false),
+            (vec![1], Some("{\"tasks\": 1}"), false, false),
+            (vec![2], Some(two_tasks.as_str()), true, false),
+            (vec![2, 1], Some(two_tasks.as_str()), true, true),
+        ] {
+            let test_case = Part::bytes(make_test_case_with_meta(&case_counts, meta))
+                .file_name("test-case.zip")
+                .mime_type("application/x-zip");
+            let response = request
+                .put(&format!("/api/problems/manage/{}", problem.id))
+                .add_query_param("replace_tasks", replace_tasks)
+                .add_header(auth_key.clone(), auth_value.clone())
+                .multipart(MultipartForm::new().add_part("case", test_case))
+                .await;
+            if ok {
+                response.assert_status_ok();
+            } else {
+                response.assert_status_bad_request();
+            }
+        }
+
+        let tasks = problem.tasks(&ctx.db).await.unwrap();
+        let tasks = tasks
+            .iter()
+            .map(|t| (t.test_case_count, t.score, t.memory_limit, t.time_limit))
+            .collect::<Vec<_>>();
+        assert_eq!(tasks, [(2, 30, 65536, 1000), (1, 70, 131_072, 2000)]);
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_upload_unsafe_test_case() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let teacher = users::Model::find_by_username(&ctx.db, "teacher1")
+            .await
+            .unwrap();
+        let problem = prepare_data::create_problem_with_tasks(
+            &ctx,
+            &teacher,
+            vec![problems::tasks::AddParams {
+                test_case_count: 3,
+                score: 100,
+                time_limit: 1000,
+                memory_limit: 65535,
+            }],
+        )
+        .await;
+        let (auth_key, auth_value) = prepare_data::auth_header(&create_token(&teacher, &ctx).await);
+
+        // limits in config/test.yaml: 1 MiB per file and 4 MiB in total
+        let mib = 1024 * 1024;
+        let large = (0..mib).map(|i| b'!' + (i % 89) as u8).collect::<Vec<_>>();
+        let build = |content: &dyn Fn(&str) -> Vec<u8>, compress: bool| {
+            let mut buf = std::io::Cursor::new(Vec::new());
+            {
+                let mut test_case = zip::ZipWriter::new(&mut buf);
+                let opt = SimpleFileOptions::default().compression_method(if compress {
+                    zip::CompressionMethod::Deflated
+                } else {
+                    zip::CompressionMethod::Stored
+                });
+                for case_i in 0..3 {
+                    for name in ["STDIN", "STDOUT"] {
+                        let path = format!("test-case/00{case_i:02}/{name}");
+                        test_case.start_file(path.as_str(), opt).unwrap();
+                        test_case.write_all(&content(&path)).unwrap();
+                    }
+                }
+            }
+            buf.into_inner()
+        };
+        let first_output = |path: &str, content: &[u8]| {
+            if path == "test-case/0000/STDOUT" {
+                content.to_vec()
+            } else {
+                b"1\n".to_vec()
+            }
+        };
+
+        for (test_case, ok) in [
+            (build(&|p| first_output(p, b"1\r\n2\r\n"), true), true),
+            (build(&|p| first_output(p, b"1\r\n2\n"), true), false),
+            (build(&|p| first_output(p, b"\xff\n"), true), false),
+            // highly compressible file
+            (
+                build(&|p| first_output(p, &vec![b'a'; mib / 2]), true),
+                false,
+            ),
+            (
+                build(
+                    &|p| first_output(p, &[large.clone(), vec![b'\n']].concat()),
+                    false,
+                ),
+                false,
+            ),
+            (build(&|_| large[..mib * 3 / 4].to_vec(), false), false),
+        ] {
+            let test_case = Part::bytes(test_case)
+                .file_name("test-case.zip")
+                .mime_type("application/x-zip");
+            let response = request
+                .put(&format!("/api/problems/manage/{}", problem.id))
+                .add_header(auth_key.clone(), auth_value.clone())
+                .multipart(MultipartForm::new().add_part("case", test_case))
+                .await;
+            if ok {
+                response.assert_status_ok();
+            } else {
+                response.assert_status_bad_request();
+            }
+        }
+    })
+    .await;
+}
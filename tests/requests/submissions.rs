@@ -0,0 +1,1261 @@
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+};
+
+use axum::http::StatusCode;
+use axum_test::multipart::{MultipartForm, Part};
+use insta::{assert_debug_snapshot, with_settings};
+use loco_rs::testing;
+use normal_oj::{
+    app::App,
+    judge::{remote::RemoteSandbox, Sandbox},
+    models::{
+        course_members::{self, CourseRole},
+        problems,
+        submissions::{self, Status},
+        users, Language, LanguageSet,
+    },
+};
+use rstest::rstest;
+use sea_orm::{ActiveModelTrait, ActiveValue, IntoActiveModel};
+use serde_json::json;
+use serial_test::serial;
+
+use super::{create_token, prepare_data};
+
+macro_rules! configure_insta {
+    () => {
+        crate::configure_insta!("submission_request");
+    };
+}
+
+#[tokio::test]
+#[serial]
+async fn can_submit_and_view_submission() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let teacher = users::Model::find_by_username(&ctx.db, "teacher1")
+            .await
+            .unwrap();
+        let problem = prepare_data::create_problem(&ctx, &teacher).await;
+        let student = users::Model::find_by_username(&ctx.db, "user1")
+            .await
+            .unwrap();
+        let (auth_key, auth_value) = prepare_data::auth_header(&create_token(&student, &ctx).await);
+
+        let response = request
+            .post("/api/submissions")
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&json!({
+                "problem_id": problem.id,
+                "language": 2,
+                "code": "print(sum(map(int, input().split())))",
+            }))
+            .await;
+        response.assert_status_ok();
+        let submission_id = response.json::<serde_json::Value>()["data"]["id"]
+            .as_i64()
+            .unwrap();
+
+        let response = request
+            .get(&format!("/api/submissions/{submission_id}"))
+            .add_header(auth_key, auth_value)
+            .await;
+        response.assert_status_ok();
+
+        with_settings!({
+            filters => testing::cleanup_user_model()
+        }, {
+            assert_debug_snapshot!(response.json::<serde_json::Value>());
+        });
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn other_student_cannot_read_code() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let teacher = users::Model::find_by_username(&ctx.db, "teacher1")
+            .await
+            .unwrap();
+        let problem = prepare_data::create_problem(&ctx, &teacher).await;
+        let user1 = users::Model::find_by_username(&ctx.db, "user1")
+            .await
+            .unwrap();
+        let user2 = users::Model::find_by_username(&ctx.db, "user2")
+            .await
+            .unwrap();
+
+        let (auth_key, auth_value) = prepare_data::auth_header(&create_token(&user1, &ctx).await);
+        let response = request
+            .post("/api/submissions")
+            .add_header(auth_key, auth_value)
+            .json(&json!({
+                "problem_id": problem.id,
+                "language": 0,
+                "code": "int main() {}",
+            }))
+            .await;
+        let submission_id = response.json::<serde_json::Value>()["data"]["id"]
+            .as_i64()
+            .unwrap();
+        course_members::Model::enroll(&ctx.db, 1, user2.id)
+            .await
+            .unwrap();
+
+        for (user, code_visible) in [(&user2, false), (&teacher, true)] {
+            let (auth_key, auth_value) = prepare_data::auth_header(&create_token(user, &ctx).await);
+            let response = request
+                .get(&format!("/api/submissions/{submission_id}"))
+                .add_header(auth_key, auth_value)
+                .await;
+            response.assert_status_ok();
+            let code = &response.json::<serde_json::Value>()["data"]["code"];
+            assert_eq!(!code.is_null(), code_visible, "viewer: {}", user.name);
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn other_student_cannot_read_compiler_message() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let teacher = users::Model::find_by_username(&ctx.db, "teacher1")
+            .await
+            .unwrap();
+        let problem = prepare_data::create_problem(&ctx, &teacher).await;
+        let user1 = users::Model::find_by_username(&ctx.db, "user1")
+            .await
+            .unwrap();
+        let user2 = users::Model::find_by_username(&ctx.db, "user2")
+            .await
+            .unwrap();
+        course_members::Model::enroll(&ctx.db, 1, user2.id)
+            .await
+            .unwrap();
+
+        let (auth_key, auth_value) = prepare_data::auth_header(&create_token(&user1, &ctx).await);
+        let response = request
+            .post("/api/submissions")
+            .add_header(auth_key, auth_value)
+            .json(&json!({
+                "problem_id": problem.id,
+                "language": 0,
+                "code": "int main() { return secret; }",
+            }))
+            .await;
+        let submission_id = response.json::<serde_json::Value>()["data"]["id"]
+            .as_i64()
+            .unwrap();
+        // compiler messages quote the source code
+        let stderr = "main.c:1:21: error: 'secret' undeclared\n int main() { return secret; }";
+        submissions::Model::find_by_id(&ctx.db, i32::try_from(submission_id).unwrap())
+            .await
+            .unwrap()
+            .into_active_model()
+            .update_result(
+                &ctx.db,
+                &[submissions::TaskResult::from_cases(vec![
+                    submissions::CaseResult {
+                        status: Status::CompileError,
+                        exec_time: -1,
+                        memory_usage: -1,
+                        stderr: stderr.to_string(),
+                        partial_score: None,
+                    },
+                ])],
+            )
+            .await
+            .unwrap();
+
+        for (user, visible) in [(&user2, false), (&user1, true), (&teacher, true)] {
+            let (auth_key, auth_value) = prepare_data::auth_header(&create_token(user, &ctx).await);
+            let response = request
+                .get(&format!("/api/submissions/{submission_id}"))
+                .add_header(auth_key, auth_value)
+                .await;
+            response.assert_status_ok();
+            let submission = response.json::<serde_json::Value>()["data"].clone();
+            assert_eq!(submission["status"], Status::CompileError as i32);
+            let expected = if visible { stderr } else { "" };
+            assert_eq!(
+                submission["tasks"][0]["cases"][0]["stderr"], expected,
+                "viewer: {}",
+                user.name
+            );
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_view_submissions_of_invisible_problem() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let teacher = users::Model::find_by_username(&ctx.db, "teacher1")
+            .await
+            .unwrap();
+        let problem = prepare_data::create_problem(&ctx, &teacher).await;
+        let user1 = users::Model::find_by_username(&ctx.db, "user1")
+            .await
+            .unwrap();
+        let user2 = users::Model::find_by_username(&ctx.db, "user2")
+            .await
+            .unwrap();
+        course_members::Model::enroll(&ctx.db, 1, user2.id)
+            .await
+            .unwrap();
+
+        let (auth_key, auth_value) = prepare_data::auth_header(&create_token(&user1, &ctx).await);
+        let response = request
+            .post("/api/submissions")
+            .add_header(auth_key, auth_value)
+            .json(&json!({
+                "problem_id": problem.id,
+                "language": 0,
+                "code": "int main() {}",
+            }))
+            .await;
+        let submission_id = response.json::<serde_json::Value>()["data"]["id"]
+            .as_i64()
+            .unwrap();
+
+        let mut problem = problem.into_active_model();
+        problem.status = ActiveValue::set(problems::Visibility::Hidden as i32);
+        problem.update(&ctx.db).await.unwrap();
+
+        // the submitter and the problem owner can still see the submission
+        for (user, visible) in [(&user1, true), (&teacher, true), (&user2, false)] {
+            let (auth_key, auth_value) = prepare_data::auth_header(&create_token(user, &ctx).await);
+            let response = request
+                .get(&format!("/api/submissions/{submission_id}"))
+                .add_header(auth_key.clone(), auth_value.clone())
+                .await;
+            let expected = if visible {
+                StatusCode::OK
+            } else {
+                StatusCode::FORBIDDEN
+            };
+            assert_eq!(response.status_code(), expected, "viewer: {}", user.name);
+
+            let response = request
+                .get("/api/submissions")
+                .add_header(auth_key, auth_value)
+                .await;
+            response.assert_status_ok();
+            let count = response.json::<serde_json::Value>()["data"]
+                .as_array()
+                .unwrap()
+                .len();
+            assert_eq!(count, usize::from(visible), "viewer: {}", user.name);
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_filter_submissions_and_count_problem_stats() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let teacher = users::Model::find_by_username(&ctx.db, "teacher1")
+            .await
+            .unwrap();
+        let problem = prepare_data::create_problem(&ctx, &teacher).await;
+        let user2 = users::Model::find_by_username(&ctx.db, "user2")
+            .await
+            .unwrap();
+        course_members::Model::enroll(&ctx.db, 1, user2.id)
+            .await
+            .unwrap();
+        for (username, language) in [("user1", 0), ("user1", 1), ("user2", 2)] {
+            let user = users::Model::find_by_username(&ctx.db, username)
+                .await
+                .unwrap();
+            let (auth_key, auth_value) =
+                prepare_data::auth_header(&create_token(&user, &ctx).await);
+            request
+                .post("/api/submissions")
+                .add_header(auth_key, auth_value)
+                .json(&json!({
+                    "problem_id": problem.id,
+                    "language": language,
+                    "code": "",
+                }))
+                .await
+                .assert_status_ok();
+        }
+
+        let (auth_key, auth_value) = prepare_data::auth_header(&create_token(&teacher, &ctx).await);
+        let response = request
+            .get("/api/submissions")
+            .add_query_param("user", "user1")
+            .add_header(auth_key.clone(), auth_value.clone())
+            .await;
+        response.assert_status_ok();
+        let submissions = response.json::<serde_json::Value>()["data"]
+            .as_array()
+            .unwrap()
+            .clone();
+        assert_eq!(submissions.len(), 2);
+        assert!(submissions.iter().all(|s| s["user"] == "user1"));
+
+        let response = request
+            .get("/api/submissions")
+            .add_query_param("language", 2)
+            .add_header(auth_key.clone(), auth_value.clone())
+            .await;
+        assert_eq!(
+            response.json::<serde_json::Value>()["data"]
+                .as_array()
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // the problem is only assigned to course1
+        for (course, count) in [("course1", 3), ("course2", 0)] {
+            let response = request
+                .get("/api/submissions")
+                .add_query_param("course", course)
+                .add_header(auth_key.clone(), auth_value.clone())
+                .await;
+            response.assert_status_ok();
+            assert_eq!(
+                response.json::<serde_json::Value>()["data"]
+                    .as_array()
+                    .unwrap()
+                    .len(),
+                count
+            );
+        }
+
+        let response = request
+            .get("/api/problems")
+            .add_header(auth_key, auth_value)
+            .await;
+        response.assert_status_ok();
+        let problems = response.json::<serde_json::Value>();
+        assert_eq!(problems["data"]["results"][0]["submit_count"], 3);
+        assert_eq!(problems["data"]["results"][0]["submitter"], 2);
+        assert_eq!(problems["data"]["results"][0]["ac_user"], 0);
+    })
+    .await;
+}
+
+const A_PLUS_B_C: &str = r#"
+#include <stdio.h>
+int main() {
+    int a, b;
+    scanf("%d %d", &a, &b);
+    printf("%d\n", a + b);
+    return 0;
+}
+"#;
+const A_PLUS_B_CPP: &str = r#"
+#include <iostream>
+int main() {
+    int a, b;
+    std::cin >> a >> b;
+    std::cout << a + b << std::endl;
+}
+"#;
+const A_PLUS_B_PY: &str = "print(sum(map(int, input().split())))";
+
+#[rstest]
+#[case::c_accepted(0, A_PLUS_B_C, Status::Accepted)]
+#[case::cpp_accepted(1, A_PLUS_B_CPP, Status::Accepted)]
+#[case::python_accepted(2, A_PLUS_B_PY, Status::Accepted)]
+#[case::wrong_answer(2, "print(0)", Status::WrongAnswer)]
+#[case::compile_error(0, "int main() { return }", Status::CompileError)]
+#[case::runtime_error(2, "raise Exception()", Status::RuntimeError)]
+#[case::time_limit_exceed(0, "int main() { while (1); }", Status::TimeLimitExceed)]
+#[case::memory_limit_exceed(
+    1,
+    "#include <vector>\nint main() { std::vector<char> v(100 << 20, 1); return v[1 << 20] - 1; }",
+    Status::MemoryLimitExceed
+)]
+#[tokio::test]
+#[serial]
+async fn can_judge_submission(
+    #[case] language: i32,
+    #[case] code: &str,
+    #[case] expected_status: Status,
+) {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let teacher = users::Model::find_by_username(&ctx.db, "teacher1")
+            .await
+            .unwrap();
+        let problem = prepare_data::create_problem(&ctx, &teacher).await;
+        let problem = prepare_data::upload_test_case(&ctx, problem, &[("1 2\n", "3\n")]).await;
+        let student = users::Model::find_by_username(&ctx.db, "user1")
+            .await
+            .unwrap();
+        let (auth_key, auth_value) = prepare_data::auth_header(&create_token(&student, &ctx).await);
+
+        let response = request
+            .post("/api/submissions")
+            .add_header(auth_key, auth_value)
+            .json(&json!({
+                "problem_id": problem.id,
+                "language": language,
+                "code": code,
+            }))
+            .await;
+        response.assert_status_ok();
+
+        let submission = &response.json::<serde_json::Value>()["data"];
+        assert_eq!(submission["status"], json!(expected_status), "{submission}");
+    })
+    .await;
+}
+
+/// Start a mock Normal-OJ sandbox which forwards received form fields of each
+/// submission through the returned channel.
+async fn start_mock_sandbox() -> (
+    String,
+    tokio::sync::mpsc::UnboundedReceiver<(i32, HashMap<String, Vec<u8>>)>,
+) {
+    use axum::{
+        extract::{Multipart, Path},
+        routing::post,
+        Json, Router,
+    };
+
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let app = Router::new().route(
+        "/submit/:submission_id",
+        post(
+            |Path(submission_id): Path<i32>, mut multipart: Multipart| async move {
+                let mut fields = HashMap::new();
+                while let Some(field) = multipart.next_field().await.unwrap() {
+                    let name = field.name().unwrap().to_string();
+                    fields.insert(name, field.bytes().await.unwrap().to_vec());
+                }
+                tx.send((submission_id, fields)).unwrap();
+                Json(json!({"status": "ok", "msg": "ok", "data": "ok"}))
+            },
+        ),
+    );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    (url, rx)
+}
+
+#[tokio::test]
+#[serial]
+async fn can_judge_with_remote_sandbox() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let teacher = users::Model::find_by_username(&ctx.db, "teacher1")
+            .await
+            .unwrap();
+        let problem = prepare_data::create_problem(&ctx, &teacher).await;
+        let student = users::Model::find_by_username(&ctx.db, "user1")
+            .await
+            .unwrap();
+
+        let code = "int main() { return 0; }";
+        let code_id = uuid::Uuid::new_v4().to_string();
+        ctx.storage
+            .as_ref()
+            .upload(
+                submissions::code_path(&code_id).as_path(),
+                &axum::body::Bytes::from(code),
+            )
+            .await
+            .unwrap();
+        let submission = submissions::Model::add(
+            &ctx.db,
+            &submissions::AddParams {
+                user: student.clone(),
+                problem: problem.clone(),
+                language: Language::Cpp,
+                code_id,
+            },
+        )
+        .await
+        .unwrap();
+
+        let (url, mut rx) = start_mock_sandbox().await;
+        let sandbox = RemoteSandbox::new(&url, "test-sandbox-token".to_string());
+        let result = sandbox.judge(&ctx, &submission).await.unwrap();
+        assert!(result.is_none());
+
+        let (submission_id, fields) = rx.recv().await.unwrap();
+        assert_eq!(submission_id, submission.id);
+        assert_eq!(fields["token"], b"test-sandbox-token");
+        assert_eq!(fields["language"], b"1");
+        assert_eq!(fields["problem_id"], problem.id.to_string().as_bytes());
+        let mut src = zip::ZipArchive::new(std::io::Cursor::new(fields["src"].clone())).unwrap();
+        let mut received = String::new();
+        src.by_name("main.cpp")
+            .unwrap()
+            .read_to_string(&mut received)
+            .unwrap();
+        assert_eq!(received, code);
+
+        let result = |token: &str| {
+            json!({
+                "tasks": [[{
+                    "exitCode": 0,
+                    "status": "AC",
+                    "stdout": "",
+                    "stderr": "",
+                    "execTime": 3,
+                    "memoryUsage": 1024,
+                }]],
+                "token": token,
+            })
+        };
+        let complete_url = format!("/api/submissions/{}/complete", submission.id);
+
+        let response = request.put(&complete_url).json(&result("wrong")).await;
+        response.assert_status(StatusCode::FORBIDDEN);
+
+        let response = request
+            .put(&complete_url)
+            .json(&result("test-sandbox-token"))
+            .await;
+        response.assert_status_ok();
+        let submission = submissions::Model::find_by_id(&ctx.db, submission.id)
+            .await
+            .unwrap();
+        assert_eq!(submission.status, Status::Accepted as i32);
+        assert_eq!(submission.score, 100);
+        assert_eq!(submission.exec_time, 3);
+        assert_eq!(submission.memory_usage, 1024);
+
+        // result can only be reported once
+        let response = request
+            .put(&complete_url)
+            .json(&result("test-sandbox-token"))
+            .await;
+        response.assert_status(StatusCode::CONFLICT);
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_score_tasks_and_show_high_score() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let teacher = users::Model::find_by_username(&ctx.db, "teacher1")
+            .await
+            .unwrap();
+        let task = |test_case_count, score| problems::tasks::AddParams {
+            test_case_count,
+            score,
+            time_limit: 1000,
+            memory_limit: 65535,
+        };
+        let problem =
+            prepare_data::create_problem_with_tasks(&ctx, &teacher, vec![task(1, 40), task(2, 60)])
+                .await;
+        let problem = prepare_data::upload_task_test_cases(
+            &ctx,
+            problem,
+            &[&[("1 2\n", "3\n")], &[("1 1\n", "2\n"), ("2 3\n", "5\n")]],
+        )
+        .await;
+        let student = users::Model::find_by_username(&ctx.db, "user1")
+            .await
+            .unwrap();
+        let (auth_key, auth_value) = prepare_data::auth_header(&create_token(&student, &ctx).await);
+
+        // `print(3)` only passes the first task, partially correct tasks get no score
+        for (code, expected_score) in [("print(3)", 40), (A_PLUS_B_PY, 100), ("print(2)", 0)] {
+            let response = request
+                .post("/api/submissions")
+                .add_header(auth_key.clone(), auth_value.clone())
+                .json(&json!({
+                    "problem_id": problem.id,
+                    "language": 2,
+                    "code": code,
+                }))
+                .await;
+            response.assert_status_ok();
+            let submission = &response.json::<serde_json::Value>()["data"];
+            assert_eq!(submission["score"], json!(expected_score), "{submission}");
+        }
+
+        for (user, expected_high_score) in [(&student, 100), (&teacher, 0)] {
+            let (auth_key, auth_value) = prepare_data::auth_header(&create_token(user, &ctx).await);
+            let response = request
+                .get(&format!("/api/problems/{}", problem.id))
+                .add_header(auth_key, auth_value)
+                .await;
+            response.assert_status_ok();
+            let high_score = &response.json::<serde_json::Value>()["data"]["high_score"];
+            assert_eq!(
+                high_score,
+                &json!(expected_high_score),
+                "viewer: {}",
+                user.name
+            );
+        }
+    })
+    .await;
+}
+
+/// Accept any two numbers whose sum is the answer, and give half credit if only
+/// the first number is the answer.
+const SUM_CHECKER: &str = r#"
+import sys
+
+a, b = map(int, open(sys.argv[1]).read().split())
+try:
+    x, y = map(int, open(sys.argv[3]).read().split())
+except ValueError:
+    sys.exit(1)
+if x + y == a + b:
+    sys.exit(0)
+print(50 if x == a + b else 0)
+sys.exit(1)
+"#;
+
+#[tokio::test]
+#[serial]
+async fn can_judge_with_custom_checker() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let teacher = users::Model::find_by_username(&ctx.db, "teacher1")
+            .await
+            .unwrap();
+        let problem = prepare_data::create_problem_with_tasks(
+            &ctx,
+            &teacher,
+            vec![problems::tasks::AddParams {
+                test_case_count: 2,
+                score: 80,
+                time_limit: 1000,
+                memory_limit: 65535,
+            }],
+        )
+        .await;
+        let problem =
+            prepare_data::upload_test_case(&ctx, problem, &[("1 2\n", "3\n"), ("2 2\n", "4\n")])
+                .await;
+        let student = users::Model::find_by_username(&ctx.db, "user1")
+            .await
+            .unwrap();
+        let (auth_key, auth_value) = prepare_data::auth_header(&create_token(&student, &ctx).await);
+        let (teacher_key, teacher_value) =
+            prepare_data::auth_header(&create_token(&teacher, &ctx).await);
+        let checker_path = format!("/api/problems/manage/{}/checker", problem.id);
+
+        let submit = |code: &'static str| {
+            request
+                .post("/api/submissions")
+                .add_header(auth_key.clone(), auth_value.clone())
+                .json(&json!({
+                    "problem_id": problem.id,
+                    "language": 2,
+                    "code": code,
+                }))
+        };
+
+        // only problem managers can upload checkers
+        request
+            .put(&checker_path)
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&json!({"language": 2, "code": SUM_CHECKER}))
+            .await
+            .assert_status_forbidden();
+        request
+            .put(&checker_path)
+            .add_header(teacher_key.clone(), teacher_value.clone())
+            .json(&json!({"language": 2, "code": SUM_CHECKER}))
+            .await
+            .assert_status_ok();
+
+        let swapped = "a, b = map(int, input().split())\nprint(b, a)";
+        for (code, status, score) in [
+            (swapped, Status::Accepted, 80),
+            (
+                "a, b = map(int, input().split())\nprint(a + b, 1)",
+                Status::WrongAnswer,
+                40,
+            ),
+            ("print(0)", Status::WrongAnswer, 0),
+        ] {
+            let response = submit(code).await;
+            response.assert_status_ok();
+            let submission = &response.json::<serde_json::Value>()["data"];
+            assert_eq!(submission["status"], json!(status), "{submission}");
+            assert_eq!(submission["score"], json!(score), "{submission}");
+        }
+
+        // a broken checker fails the judge instead of the submission
+        request
+            .put(&checker_path)
+            .add_header(teacher_key.clone(), teacher_value.clone())
+            .json(&json!({"language": 0, "code": "int main() { return }"}))
+            .await
+            .assert_status_ok();
+        let response = submit(swapped).await;
+        let submission = &response.json::<serde_json::Value>()["data"];
+        assert_eq!(submission["status"], json!(Status::JudgeError));
+
+        // outputs are compared line by line again without checker
+        request
+            .delete(&checker_path)
+            .add_header(teacher_key, teacher_value)
+            .await
+            .assert_status_ok();
+        let problem = problems::Model::find_by_id(&ctx.db, problem.id)
+            .await
+            .unwrap();
+        assert_eq!(problem.checker_id, None);
+        let response = submit(swapped).await;
+        let submission = &response.json::<serde_json::Value>()["data"];
+        assert_eq!(submission["status"], json!(Status::WrongAnswer));
+    })
+    .await;
+}
+
+/// Answer guesses of the hidden number in the input, at most 10 guesses are allowed.
+const GUESS_INTERACTOR: &str = r#"
+import sys
+
+n = int(open(sys.argv[1]).read())
+for _ in range(10):
+    try:
+        guess = int(input())
+    except (EOFError, ValueError):
+        sys.exit(1)
+    if guess == n:
+        print("correct", flush=True)
+        sys.exit(0)
+    print("higher" if guess < n else "lower", flush=True)
+open(sys.argv[2], "w").write("30")
+sys.exit(1)
+"#;
+
+const BINARY_SEARCH: &str = r#"
+lo, hi = 1, 100
+while True:
+    mid = (lo + hi) // 2
+    print(mid, flush=True)
+    reply = input()
+    if reply == "correct":
+        break
+    if reply == "higher":
+        lo = mid + 1
+    else:
+        hi = mid - 1
+"#;
+
+const LINEAR_SEARCH: &str = r#"
+for i in range(1, 101):
+    print(i, flush=True)
+    if input() == "correct":
+        break
+"#;
+
+fn make_interactive_test_case(secrets: &[&str], interactor: Option<&str>) -> Vec<u8> {
+    let mut buf = std::io::Cursor::new(Vec::new());
+    {
+        let mut zip = zip::ZipWriter::new(&mut buf);
+        let opt = zip::write::SimpleFileOptions::default();
+        for (i, secret) in secrets.iter().enumerate() {
+            zip.start_file(format!("test-case/00{i:02}/STDIN"), opt)
+                .unwrap();
+            zip.write_all(secret.as_bytes()).unwrap();
+        }
+        if let Some(interactor) = interactor {
+            zip.start_file("interactor.py", opt).unwrap();
+            zip.write_all(interactor.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+    buf.into_inner()
+}
+
+#[tokio::test]
+#[serial]
+async fn can_judge_interactive_problem() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let teacher = users::Model::find_by_username(&ctx.db, "teacher1")
+            .await
+            .unwrap();
+        let mut problem = prepare_data::create_problem_with_tasks(
+            &ctx,
+            &teacher,
+            vec![problems::tasks::AddParams {
+                test_case_count: 2,
+                score: 100,
+                time_limit: 1000,
+                memory_limit: 65535,
+            }],
+        )
+        .await
+        .into_active_model();
+        problem.r#type = ActiveValue::set(problems::Type::Interactive as i32);
+        let problem = problem.update(&ctx.db).await.unwrap();
+        let student = users::Model::find_by_username(&ctx.db, "user1")
+            .await
+            .unwrap();
+        let (auth_key, auth_value) = prepare_data::auth_header(&create_token(&student, &ctx).await);
+        let (teacher_key, teacher_value) =
+            prepare_data::auth_header(&create_token(&teacher, &ctx).await);
+
+        // the interactor is required by interactive problems
+        for (test_case, ok) in [
+            (make_interactive_test_case(&["7", "42"], None), false),
+            (
+                make_interactive_test_case(&["7", "42"], Some(GUESS_INTERACTOR)),
+                true,
+            ),
+        ] {
+            let test_case = Part::bytes(test_case)
+                .file_name("test-case.zip")
+                .mime_type("application/x-zip");
+            let response = request
+                .put(&format!("/api/problems/manage/{}", problem.id))
+                .add_header(teacher_key.clone(), teacher_value.clone())
+                .multipart(MultipartForm::new().add_part("case", test_case))
+                .await;
+            if ok {
+                response.assert_status_ok();
+            } else {
+                response.assert_status_bad_request();
+            }
+        }
+
+        for (code, status, score) in [
+            (BINARY_SEARCH, Status::Accepted, 100),
+            // only the first number is found in 10 guesses
+            (LINEAR_SEARCH, Status::WrongAnswer, 30),
+            ("print('hello')", Status::WrongAnswer, 0),
+            ("exit(1)", Status::WrongAnswer, 0),
+        ] {
+            let response = request
+                .post("/api/submissions")
+                .add_header(auth_key.clone(), auth_value.clone())
+                .json(&json!({
+                    "problem_id": problem.id,
+                    "language": 2,
+                    "code": code,
+                }))
+                .await;
+            response.assert_status_ok();
+            let submission = &response.json::<serde_json::Value>()["data"];
+            assert_eq!(submission["status"], json!(status), "{submission}");
+            assert_eq!(submission["score"], json!(score), "{submission}");
+        }
+    })
+    .await;
+}
+
+const A_PLUS_B_TEMPLATE: &str = "a, b = map(int, input().split())\nprint({{@a + b@}})\n";
+
+#[tokio::test]
+#[serial]
+async fn can_submit_to_fill_in_template_problem() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let teacher = users::Model::find_by_username(&ctx.db, "teacher1")
+            .await
+            .unwrap();
+        let mut problem = prepare_data::create_problem(&ctx, &teacher)
+            .await
+            .into_active_model();
+        problem.r#type = ActiveValue::set(problems::Type::FillInTemplate as i32);
+        let problem = problem.update(&ctx.db).await.unwrap();
+        let problem = prepare_data::upload_test_case(&ctx, problem, &[("1 2\n", "3\n")]).await;
+        let student = users::Model::find_by_username(&ctx.db, "user1")
+            .await
+            .unwrap();
+        let (auth_key, auth_value) = prepare_data::auth_header(&create_token(&student, &ctx).await);
+        let (teacher_key, teacher_value) =
+            prepare_data::auth_header(&create_token(&teacher, &ctx).await);
+        let template_path = format!("/api/problems/manage/{}/templates/2", problem.id);
+
+        request
+            .put(&template_path)
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&json!({"template": A_PLUS_B_TEMPLATE}))
+            .await
+            .assert_status_forbidden();
+        let response = request
+            .put(&template_path)
+            .add_header(teacher_key.clone(), teacher_value.clone())
+            .json(&json!({"template": "print({{@a + b)"}))
+            .await;
+        response.assert_status_bad_request();
+        assert_eq!(
+            response.json::<serde_json::Value>()["msg"],
+            "Invalid template: blank starting at line 1 is not closed"
+        );
+        request
+            .put(&template_path)
+            .add_header(teacher_key.clone(), teacher_value.clone())
+            .json(&json!({"template": A_PLUS_B_TEMPLATE}))
+            .await
+            .assert_status_ok();
+
+        // reference answers are only visible to problem managers
+        for (key, value, template) in [
+            (
+                auth_key.clone(),
+                auth_value.clone(),
+                "a, b = map(int, input().split())\nprint({{@@}})\n",
+            ),
+            (
+                teacher_key.clone(),
+                teacher_value.clone(),
+                A_PLUS_B_TEMPLATE,
+            ),
+        ] {
+            let response = request
+                .get(&format!("/api/problems/{}", problem.id))
+                .add_header(key, value)
+                .await;
+            response.assert_status_ok();
+            assert_eq!(
+                response.json::<serde_json::Value>()["data"]["templates"],
+                json!([{"language": 2, "template": template, "blank_count": 1}])
+            );
+        }
+
+        let mut accepted = None;
+        for (body, status, expected) in [
+            (
+                json!({"blanks": ["a + b"]}),
+                StatusCode::OK,
+                Some(Status::Accepted),
+            ),
+            (
+                json!({"blanks": ["a - b"]}),
+                StatusCode::OK,
+                Some(Status::WrongAnswer),
+            ),
+            (json!({"blanks": ["a", "b"]}), StatusCode::BAD_REQUEST, None),
+            (json!({"code": A_PLUS_B_PY}), StatusCode::BAD_REQUEST, None),
+            (
+                json!({"blanks": ["a + b"], "language": 0}),
+                StatusCode::BAD_REQUEST,
+                None,
+            ),
+        ] {
+            let mut body = body;
+            body["problem_id"] = json!(problem.id);
+            if body.get("language").is_none() {
+                body["language"] = json!(2);
+            }
+            let response = request
+                .post("/api/submissions")
+                .add_header(auth_key.clone(), auth_value.clone())
+                .json(&body)
+                .await;
+            assert_eq!(response.status_code(), status, "{body}");
+            if let Some(expected) = expected {
+                let submission = &response.json::<serde_json::Value>()["data"];
+                assert_eq!(submission["status"], json!(expected), "{submission}");
+                accepted = accepted.or_else(|| submission["id"].as_i64());
+            }
+        }
+
+        // the spliced code is stored as the submission
+        let submission =
+            submissions::Model::find_by_id(&ctx.db, i32::try_from(accepted.unwrap()).unwrap())
+                .await
+                .unwrap();
+        let code: String = ctx
+            .storage
+            .download(submissions::code_path(&submission.code_id).as_path())
+            .await
+            .unwrap();
+        assert_eq!(code, "a, b = map(int, input().split())\nprint(a + b)\n");
+
+        request
+            .delete(&template_path)
+            .add_header(teacher_key.clone(), teacher_value.clone())
+            .await
+            .assert_status_ok();
+        request
+            .delete(&template_path)
+            .add_header(teacher_key, teacher_value)
+            .await
+            .assert_status_not_found();
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_grade_handwritten_submission() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let teacher = users::Model::find_by_username(&ctx.db, "teacher1")
+            .await
+            .unwrap();
+        let mut problem = prepare_data::create_problem(&ctx, &teacher)
+            .await
+            .into_active_model();
+        problem.r#type = ActiveValue::set(problems::Type::Handwritten as i32);
+        let problem = problem.update(&ctx.db).await.unwrap();
+        let student = users::Model::find_by_username(&ctx.db, "user1")
+            .await
+            .unwrap();
+        let grader = users::Model::find_by_username(&ctx.db, "user2")
+            .await
+            .unwrap();
+        let (auth_key, auth_value) = prepare_data::auth_header(&create_token(&student, &ctx).await);
+        let (grader_key, grader_value) =
+            prepare_data::auth_header(&create_token(&grader, &ctx).await);
+        let upload_path = format!("/api/submissions/handwritten/{}", problem.id);
+        let pdf = b"%PDF-1.4 handwritten answer".to_vec();
+
+        // code is not accepted by handwritten problems
+        request
+            .post("/api/submissions")
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&json!({
+                "problem_id": problem.id,
+                "language": 2,
+                "code": A_PLUS_B_PY,
+            }))
+            .await
+            .assert_status_bad_request();
+        let text = Part::bytes(pdf.clone())
+            .file_name("answer.txt")
+            .mime_type("text/plain");
+        request
+            .post(&upload_path)
+            .add_header(auth_key.clone(), auth_value.clone())
+            .multipart(MultipartForm::new().add_part("file", text))
+            .await
+            .assert_status_bad_request();
+
+        let file = Part::bytes(pdf.clone())
+            .file_name("answer.pdf")
+            .mime_type("application/pdf");
+        let response = request
+            .post(&upload_path)
+            .add_header(auth_key.clone(), auth_value.clone())
+            .multipart(MultipartForm::new().add_part("file", file))
+            .await;
+        response.assert_status_ok();
+        let submission = response.json::<serde_json::Value>()["data"].clone();
+        assert_eq!(submission["status"], json!(Status::Pending));
+        assert_eq!(submission["language"], json!(null));
+        let submission_id = submission["id"].as_i64().unwrap();
+
+        let response = request
+            .get(&format!("/api/submissions/{submission_id}/file"))
+            .add_header(auth_key.clone(), auth_value.clone())
+            .await;
+        response.assert_status_ok();
+        assert_eq!(response.header("content-type"), "application/pdf");
+        assert_eq!(response.as_bytes().to_vec(), pdf);
+
+        // only teachers and TAs of the problem's courses can grade
+        let grade_path = format!("/api/submissions/{submission_id}/grade");
+        for (key, value) in [
+            (auth_key.clone(), auth_value.clone()),
+            (grader_key.clone(), grader_value.clone()),
+        ] {
+            request
+                .put(&grade_path)
+                .add_header(key, value)
+                .json(&json!({"score": 80}))
+                .await
+                .assert_status_forbidden();
+        }
+        course_members::Model::upsert(&ctx.db, 1, grader.id, CourseRole::Ta)
+            .await
+            .unwrap();
+        request
+            .get(&format!("/api/submissions/{submission_id}/file"))
+            .add_header(grader_key.clone(), grader_value.clone())
+            .await
+            .assert_status_ok();
+        request
+            .put(&grade_path)
+            .add_header(grader_key.clone(), grader_value.clone())
+            .json(&json!({"score": 101}))
+            .await
+            .assert_status_bad_request();
+
+        for (score, status) in [(80, Status::WrongAnswer), (100, Status::Accepted)] {
+            request
+                .put(&grade_path)
+                .add_header(grader_key.clone(), grader_value.clone())
+                .json(&json!({"score": score, "comment": "nice handwriting"}))
+                .await
+                .assert_status_ok();
+            let response = request
+                .get(&format!("/api/submissions/{submission_id}"))
+                .add_header(auth_key.clone(), auth_value.clone())
+                .await;
+            response.assert_status_ok();
+            let submission = &response.json::<serde_json::Value>()["data"];
+            assert_eq!(submission["status"], json!(status));
+            assert_eq!(submission["score"], json!(score));
+            assert_eq!(submission["comment"], json!("nice handwriting"));
+            assert_eq!(submission["code"], json!(null));
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_submit_after_quota_used() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let teacher = users::Model::find_by_username(&ctx.db, "teacher1")
+            .await
+            .unwrap();
+        let mut problem = prepare_data::create_problem(&ctx, &teacher)
+            .await
+            .into_active_model();
+        problem.quota = ActiveValue::set(2);
+        let problem = problem.update(&ctx.db).await.unwrap();
+        let student = users::Model::find_by_username(&ctx.db, "user1")
+            .await
+            .unwrap();
+
+        let submit = |user: users::Model| {
+            let request = &request;
+            let ctx = &ctx;
+            async move {
+                let (auth_key, auth_value) =
+                    prepare_data::auth_header(&create_token(&user, ctx).await);
+                request
+                    .post("/api/submissions")
+                    .add_header(auth_key, auth_value)
+                    .json(&json!({
+                        "problem_id": problem.id,
+                        "language": 2,
+                        "code": A_PLUS_B_PY,
+                    }))
+                    .await
+            }
+        };
+        let remaining_quota = |user: users::Model| {
+            let request = &request;
+            let ctx = &ctx;
+            async move {
+                let (auth_key, auth_value) =
+                    prepare_data::auth_header(&create_token(&user, ctx).await);
+                let response = request
+                    .get(&format!("/api/problems/{}", problem.id))
+                    .add_header(auth_key, auth_value)
+                    .await;
+                response.json::<serde_json::Value>()["data"]["remaining_quota"].clone()
+            }
+        };
+
+        assert_eq!(remaining_quota(student.clone()).await, json!(2));
+        for _ in 0..2 {
+            submit(student.clone()).await.assert_status_ok();
+        }
+        assert_eq!(remaining_quota(student.clone()).await, json!(0));
+
+        let response = submit(student.clone()).await;
+        response.assert_status(StatusCode::FORBIDDEN);
+        assert_eq!(
+            response.json::<serde_json::Value>(),
+            json!({"msg": "You have used all your quotas"})
+        );
+
+        // the problem owner is not limited
+        for _ in 0..3 {
+            submit(teacher.clone()).await.assert_status_ok();
+        }
+        assert_eq!(remaining_quota(teacher.clone()).await, json!(-1));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_submit_in_disallowed_language() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let teacher = users::Model::find_by_username(&ctx.db, "teacher1")
+            .await
+            .unwrap();
+        let mut problem = prepare_data::create_problem(&ctx, &teacher)
+            .await
+            .into_active_model();
+        problem.allowed_language = ActiveValue::set(
+            [Language::C, Language::Cpp]
+                .into_iter()
+                .collect::<LanguageSet>()
+                .bits(),
+        );
+        let problem = problem.update(&ctx.db).await.unwrap();
+        let student = users::Model::find_by_username(&ctx.db, "user1")
+            .await
+            .unwrap();
+        let (auth_key, auth_value) = prepare_data::auth_header(&create_token(&student, &ctx).await);
+
+        for (language, status) in [(1, StatusCode::OK), (2, StatusCode::FORBIDDEN)] {
+            let response = request
+                .post("/api/submissions")
+                .add_header(auth_key.clone(), auth_value.clone())
+                .json(&json!({
+                    "problem_id": problem.id,
+                    "language": language,
+                    "code": "",
+                }))
+                .await;
+            response.assert_status(status);
+        }
+    })
+    .await;
+}
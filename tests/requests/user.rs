@@ -3,7 +3,11 @@ use insta::{assert_debug_snapshot, assert_json_snapshot, with_settings};
 use loco_rs::testing;
 use normal_oj::{
     app::App,
-    models::users::{self, Role},
+    models::{
+        course_members::CourseRole,
+        courses,
+        users::{self, Role},
+    },
     views::{user::UserInfoResponse, PaginatedResponse},
 };
 use rstest::rstest;
@@ -248,6 +252,48 @@ async fn can_batch_signup() {
     .await;
 }
 
+#[tokio::test]
+#[serial]
+async fn can_batch_signup_to_course() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        // user2 already exists and should also be enrolled
+        let payload = vec!["username,email,password".to_string()]
+            .into_iter()
+            .chain((2..5).map(|i| format!("user{i},user{i}@noj.tw,user{i}")))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let payload = json!({"new_users": payload, "course": "course1"});
+
+        let user = users::Model::find_by_username(&ctx.db, "first_admin")
+            .await
+            .unwrap();
+        let (auth_key, auth_value) = prepare_data::auth_header(&create_token(&user, &ctx).await);
+        let response = request
+            .post("/api/auth/batch-signup")
+            .add_header(auth_key, auth_value)
+            .json(&payload)
+            .await;
+        response.assert_status_success();
+
+        let course = courses::Model::find_by_name(&ctx.db, "course1")
+            .await
+            .unwrap();
+        for i in 1..5 {
+            let u = users::Model::find_by_username(&ctx.db, &format!("user{i}"))
+                .await
+                .unwrap();
+            assert_eq!(
+                course.role_of(&ctx.db, &u).await.unwrap(),
+                Some(CourseRole::Student)
+            );
+        }
+    })
+    .await;
+}
+
 #[tokio::test]
 #[serial]
 async fn non_admin_cannot_edit_user() {
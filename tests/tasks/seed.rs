@@ -19,7 +19,7 @@ use loco_rs::{db, prelude::*};
 use migration::Migrator;
 use normal_oj::app::App;
 
-#[allow(clippy::module_name_repetitions)]
+#[allow(clippy::module_name_repetitions, dead_code)]
 pub struct SeedData;
 #[async_trait]
 impl Task for SeedData {